@@ -0,0 +1,544 @@
+# German. Keys missing here are shown in English.
+
+## File dialogs
+
+filter-image = Bild
+filter-audio = Audio
+filter-wave = Wave
+filter-csv = CSV
+filter-json = JSON
+filter-spreadsheet = Tabelle
+filter-cue = CUE-Datei
+filter-lrc = Liedtext
+
+## Song list
+
+files-heading = Dateien
+files-add = hinzufügen
+files-add-folder = Ordner
+files-add-folder-hint = Alle mp3-Dateien eines Ordners hinzufügen
+files-not-mp3 = Die Datei '{ $file }' ist keine mp3-Datei!
+files-song-list = Titelliste:
+files-remove = Entfernen
+files-none-selected = Keine Datei ausgewählt
+files-empty = Datei hinzufügen oder hierher ziehen!
+
+## Menus
+
+menu-recent = Zuletzt
+menu-tools = Werkzeuge
+menu-language = Sprache
+menu-export = Tags exportieren...
+menu-import = Tags importieren...
+menu-cue = CUE-Datei importieren...
+menu-musicbrainz = Bei MusicBrainz suchen...
+menu-identify = Unbenannte Dateien erkennen...
+menu-duplicates = Duplikate finden...
+menu-lint = Bibliothek prüfen...
+menu-mojibake = Zeichenkodierung reparieren...
+menu-genres = Genres zusammenführen...
+menu-versions = ID3-Versionen...
+menu-tag-blocks = ID3v1- / APEv2-Tags...
+menu-log = Protokoll...
+menu-saving = Speichern...
+menu-appearance = Darstellung...
+menu-shortcuts = Tastenkürzel...
+menu-palette = Befehlspalette...
+
+## Artwork
+
+art-heading = Cover:
+art-edit = ändern
+art-find-cover = Cover suchen
+art-save = Bild speichern
+art-loading = Wird geladen...
+art-none = Kein Cover
+art-none-found = Kein Cover gefunden
+art-set-failed = Cover konnte nicht gesetzt werden: { $error }
+art-save-failed = Cover konnte nicht gespeichert werden: { $error }
+art-load-failed = Cover konnte nicht geladen werden: { $error }
+
+## Tags tab
+
+tab-tags = Tags
+tab-lyrics = Liedtext
+tab-chapters = Kapitel
+tags-more-fields = Weitere Felder
+info-bitrate = Bitrate:
+info-kbps = { $kbps } kbit/s
+info-unknown-bitrate = Bitrate unbekannt
+info-sample-rate = Abtastrate:
+info-khz = { $khz } kHz
+info-hz = { $hz } Hz
+info-unknown-sample-rate = Abtastrate unbekannt
+info-duration = Dauer:
+info-seconds = { $seconds ->
+    [one] 1 Sekunde
+   *[other] { $seconds } Sekunden
+}
+info-unknown-duration = Dauer unbekannt
+
+## Playback
+
+playback-play = abspielen
+playback-pause = pausieren
+playback-render = Als wav speichern...
+playback-failed = Wiedergabe fehlgeschlagen: { $error }
+playback-no-device = Kein Ausgabegerät
+playback-no-output = Dieser Build hat keine Tonausgabe; zum Anhören als wav speichern
+audio-no-track = Keine Standardspur
+
+## Fields
+
+field-title = Titel
+field-artist = Interpret
+field-album = Album
+field-album-artist = Album-Interpret
+field-genre = Genre
+field-year = Jahr
+field-track = Titelnummer
+field-disc = CD
+field-composer = Komponist
+field-comment = Kommentar
+field-invalid-year = Ungültiges Jahr '{ $value }'
+field-invalid-number = Ungültige Zahl '{ $value }'
+
+## Tag form
+
+form-field-heading = { $field }:
+form-list = Liste
+form-list-hint = Einen Wert pro Zeile bearbeiten
+form-several-values = Hat mehrere Werte; bearbeiten mit „Liste“
+form-unsaved = { $count ->
+    [one] 1 ungespeicherte Änderung
+   *[other] { $count } ungespeicherte Änderungen
+}
+form-save = Speichern
+form-revert = Verwerfen
+form-save-failed = Tags konnten nicht gespeichert werden: { $error }
+form-no-file = Keine Datei ausgewählt
+
+## Changes made by other programs
+
+watch-failed = Dateien können nicht auf Änderungen überwacht werden: { $error }
+watch-folder-failed = { $folder } kann nicht auf Änderungen überwacht werden: { $error }
+watch-unwatch-failed = Überwachung von { $folder } ließ sich nicht beenden: { $error }
+watch-deleted = { $file } wurde gelöscht oder verschoben
+conflict-title = Datei auf der Festplatte geändert
+conflict-changed = { $file } wurde von einem anderen Programm geändert, während du sie bearbeitet hast.
+conflict-keep-warning = Wenn du deine Änderungen behältst, überschreibt das Speichern die Änderungen des anderen Programms an denselben Feldern.
+conflict-reload = Neu laden
+conflict-keep = Meine Änderungen behalten
+
+## Recent files
+
+recent-none = Keine zuletzt geöffneten Dateien
+recent-folders = Ordner
+recent-clear = Leeren
+recent-missing = { $file } existiert nicht mehr
+recent-no-mp3s = Keine mp3-Dateien in { $folder }
+recent-folder-failed = Ordner konnte nicht geöffnet werden: { $error }
+
+## Appearance
+
+theme-title = Darstellung
+theme-system = Wie das System
+theme-dark = Dunkel
+theme-light = Hell
+theme-high-contrast = Hoher Kontrast
+theme-colors-dark = Farben des dunklen Designs
+theme-colors-light = Farben des hellen Designs
+theme-colors-high-contrast = Farben des kontrastreichen Designs
+theme-reset = zurücksetzen
+theme-reset-all = Alle Farben zurücksetzen
+role-panel = Hintergrund der Leisten
+role-text = Text
+role-muted-text = Gedimmter Text
+role-item = Eintrag der Dateiliste
+role-selection = Ausgewählte Datei
+role-selected-text = Text der ausgewählten Datei
+role-changed = Ungespeichertes Feld
+role-error = Fehler
+role-warning = Warnungen
+
+## Commands
+
+command-next-file = Nächste Datei
+command-previous-file = Vorherige Datei
+command-save = Änderungen speichern
+command-revert = Änderungen verwerfen
+command-undo = Letztes Speichern rückgängig machen
+command-focus-field = Felder bearbeiten
+command-add-file = Dateien hinzufügen
+command-add-folder = Ordner hinzufügen
+command-show-tags = Tags anzeigen
+command-show-lyrics = Liedtext anzeigen
+command-show-chapters = Kapitel anzeigen
+command-play-pause = Abspielen / Pause
+command-export-tags = Tags exportieren
+command-import-tags = Tags importieren
+command-import-cue = CUE-Datei importieren
+command-music-brainz = Bei MusicBrainz suchen
+command-find-cover = Cover suchen
+command-identify = Unbenannte Dateien erkennen
+command-find-duplicates = Duplikate finden
+command-check-library = Bibliothek prüfen
+command-repair-encoding = Zeichenkodierung reparieren
+command-consolidate-genres = Genres zusammenführen
+command-id3-versions = ID3-Versionen
+command-tag-blocks = ID3v1- / APEv2-Tags
+command-save-options = Speicheroptionen
+command-appearance = Darstellung
+command-shortcuts = Tastenkürzel
+command-palette = Befehlspalette
+command-log = Protokoll anzeigen
+undo-nothing = Nichts rückgängig zu machen
+undo-failed = Rückgängig machen fehlgeschlagen: { $error }
+palette-title = Befehlspalette
+palette-hint = Befehl eingeben
+shortcuts-title = Tastenkürzel
+shortcuts-recording = Taste drücken...
+shortcuts-change = ändern
+shortcuts-clear = entfernen
+shortcuts-reset = Standard wiederherstellen
+
+## Saving
+
+save-title = Speichern
+save-explanation = Tags werden in eine Kopie geschrieben, die geprüft wird, bevor sie die Datei ersetzt.
+save-preserve-times = Änderungszeit und Berechtigungen jeder Datei beibehalten
+save-keep-backups = Vor dem Speichern eine Sicherung jeder Datei anlegen
+save-no-folder = Kein Ordner gewählt
+save-choose-folder = Ordner wählen...
+save-prune-before = Sicherungen löschen nach
+save-prune-after = Tagen (0 behält sie)
+save-prune-now = Jetzt aufräumen
+save-pruned = { $count ->
+    [one] 1 alte Sicherung gelöscht
+   *[other] { $count } alte Sicherungen gelöscht
+}
+save-prune-failed = Sicherungen konnten nicht aufgeräumt werden: { $error }
+save-read-only = { $file } ist schreibgeschützt; mach die Datei beschreibbar, um ihre Tags zu speichern
+save-no-name = Datei hat keinen Namen
+save-audio-changed = die Audiodaten haben sich beim Schreiben verändert; die Originaldatei wurde behalten
+
+## Multi-valued fields
+
+values-title = { $field } bearbeiten
+values-remove = Diesen Wert entfernen
+values-add = + hinzufügen
+values-separator = Trennzeichen für ID3v2.3:
+values-separator-hint = ID3v2.4 speichert jeden Wert einzeln; ID3v2.3 muss sie zu einem verbinden
+values-save-failed = { $field } konnte nicht gespeichert werden: { $error }
+
+## Genres
+
+genres-find = Genre suchen:
+genres-add = „{ $genre }“ als Genre hinzufügen
+genres-title = Genres zusammenführen
+genres-explanation = Wähle die Schreibweisen, die zusammengeführt werden sollen, und dann das Ziel-Genre.
+genres-merge-into = Zusammenführen zu:
+genres-merge = Zusammenführen
+genres-failed = Genres konnten nicht aktualisiert werden: { $errors }
+
+## Library check
+
+lint-title = Bibliothek prüfen
+lint-summary = { $problems ->
+    [one] 1 Problem
+   *[other] { $problems } Probleme
+} in { $files ->
+    [one] 1 Datei
+   *[other] { $files } Dateien
+}
+lint-check-again = Erneut prüfen
+lint-fix-all = Alle beheben
+lint-fix-all-hint = Cover zuschneiden geht nur über den Knopf der jeweiligen Datei
+lint-fix = beheben
+lint-largest-art = Größtes Cover:
+lint-fix-failed = Tags konnten nicht korrigiert werden: { $errors }
+lint-only-v1 = Hat nur einen ID3v1-Tag
+lint-missing = { $field } fehlt
+lint-whitespace = { $field } hat Leerzeichen am Anfang oder Ende
+lint-art-large = Cover ist { $width }x{ $height } groß
+lint-art-not-square = Cover ist nicht quadratisch ({ $width }x{ $height })
+lint-duplicate-frames = Doppelte Frames: { $frames }
+lint-differs = { $field } „{ $value }“ weicht vom Rest des Albums ab
+lint-missing-track = Titelnummer fehlt
+lint-track-gaps = Titel { $track } hinterlässt Lücken oder Wiederholungen im Album
+lint-mixed-versions = Tag ist { $version }, die anderen nicht
+fix-clear = { $field } leeren
+fix-set = { $field } auf „{ $value }“ setzen
+fix-rewrite = Tag neu schreiben
+fix-convert = als { $version } neu schreiben
+fix-convert-v1 = in einen ID3v2-Tag kopieren
+fix-shrink-art = auf höchstens { $max } px verkleinern
+fix-crop-art = quadratisch zuschneiden
+
+## Text encoding repair
+
+mojibake-title = Zeichenkodierung reparieren
+mojibake-summary = { $count ->
+    [one] 1 Datei mit Text, der falsch dekodiert aussieht
+   *[other] { $count } Dateien mit Text, der falsch dekodiert aussieht
+}
+mojibake-write-as = Schreiben als:
+mojibake-v24 = ID3v2.4 (UTF-8)
+mojibake-v23 = ID3v2.3 (UTF-16)
+mojibake-preview-invalid = (in dieser Kodierung ungültig)
+mojibake-rewrite = Neu schreiben
+mojibake-invalid = „{ $text }“ ist kein gültiges { $encoding }
+mojibake-failed = Tags konnten nicht repariert werden: { $errors }
+
+## ID3 versions
+
+versions-title = ID3-Versionen
+versions-when-saving = Beim Speichern
+versions-keep = Version jeder Datei beibehalten
+versions-always-preferred = Immer die bevorzugte Version schreiben
+versions-preferred = Bevorzugte Version:
+versions-convert-heading = Geladene Dateien umwandeln
+versions-convert-to = Umwandeln in:
+versions-no-v2-tag = kein ID3v2-Tag
+versions-no-tag = kein Tag
+versions-convert = Umwandeln
+versions-failed = Tags konnten nicht umgewandelt werden: { $errors }
+versions-merged-date = TYER/TDAT/TIME zu TDRC zusammengeführt
+versions-split-date = TDRC in TYER/TDAT/TIME aufgeteilt
+versions-moved = { $from } nach { $to } verschoben
+versions-dropped = { $frame } entfernt: nicht Teil von { $version }
+
+## ID3v1 and APEv2 tags
+
+blocks-title = Tag-Blöcke
+blocks-no-file = Wähle eine Datei, um ihre Tags zu sehen.
+blocks-present = vorhanden
+blocks-absent = nicht in dieser Datei
+blocks-copy-from = Kopieren von
+blocks-copy-to = nach
+blocks-v1-preview = ID3v1 { $field }: „{ $value }“
+blocks-sync = Abgleichen
+blocks-strip-label = Entfernen:
+blocks-strip = Entfernen
+blocks-all-files = Auf alle { $count } geladenen Dateien anwenden
+blocks-no-tag = kein { $block }-Tag
+blocks-sync-failed = Tags konnten nicht abgeglichen werden: { $errors }
+blocks-strip-failed = Tags konnten nicht entfernt werden: { $errors }
+
+## Duplicates
+
+duplicates-title = Duplikate finden
+duplicates-match-by = Vergleichen nach:
+duplicates-by-name = Interpret + Titel
+duplicates-by-hash = Audio-Prüfsumme
+duplicates-by-fingerprint = Fingerabdruck
+duplicates-same-audio = identische Audiodaten
+duplicates-similar-fingerprint = ähnlicher Fingerabdruck
+duplicates-scan = Durchsuchen
+duplicates-progress = { $done } von { $total } durchsucht
+duplicates-found = { $count ->
+    [one] 1 Gruppe gefunden
+   *[other] { $count } Gruppen gefunden
+}
+duplicates-rescan = Erneut durchsuchen, um Fingerabdrücke zu erstellen.
+duplicates-remove-others = Andere aus der Liste entfernen
+duplicates-move-others = Andere in Ordner verschieben...
+duplicates-exists = { $file } existiert bereits
+duplicates-move-failed = Dateien konnten nicht verschoben werden: { $errors }
+
+## Online lookups
+
+online-endpoint = Endpunkt:
+online-artist = Interpret:
+online-album = Album:
+online-search = Suchen
+online-searching = Suche läuft...
+online-downloading = Wird heruntergeladen...
+online-search-failed = Suche fehlgeschlagen: { $error }
+
+## Cover search
+
+cover-title = Cover suchen
+cover-mbid = Release-MBID:
+cover-found = { $count ->
+    [one] 1 Bild gefunden
+   *[other] { $count } Bilder gefunden
+}
+cover-no-preview = (keine Vorschau)
+cover-unknown-size = Größe unbekannt
+cover-use = verwenden
+
+## Identify untagged files
+
+identify-title = Unbenannte Dateien erkennen
+identify-summary = { $count ->
+    [one] 1 Datei ohne Titel oder Interpret
+   *[other] { $count } Dateien ohne Titel oder Interpret
+}
+identify-query = Fingerabdrücke bei AcoustID nachschlagen
+identify-api-key = API-Schlüssel:
+identify-start = Fingerabdruck erstellen
+identify-apply = übernehmen
+identify-queued = In der Warteschlange
+identify-fingerprinted = Fingerabdruck erstellt
+identify-failed = Fehlgeschlagen: { $error }
+identify-no-match = Kein Treffer
+identify-suggestions = { $count ->
+    [one] 1 Vorschlag
+   *[other] { $count } Vorschläge
+}
+identify-lookup-failed = Abfrage fehlgeschlagen: { $error }
+identify-tagged = Getaggt als { $artist } - { $title }
+identify-apply-failed = Vorschlag konnte nicht übernommen werden: { $error }
+identify-no-audio = Keine Audiodaten für einen Fingerabdruck
+
+## MusicBrainz
+
+mb-title = Bei MusicBrainz suchen
+mb-server = Server:
+mb-search-tags = Nach Tags suchen
+mb-search-durations = Nach Spieldauern suchen
+mb-waiting = Warte auf MusicBrainz...
+mb-dropped = Anfrage wurde abgebrochen
+mb-lookup-failed = Abfrage bei MusicBrainz fehlgeschlagen: { $error }
+mb-candidates = { $count ->
+    [one] 1 mögliche Veröffentlichung
+   *[other] { $count } mögliche Veröffentlichungen
+}
+mb-album-files = { $count ->
+    [one] 1 Datei in diesem Album
+   *[other] { $count } Dateien in diesem Album
+}
+mb-no-date = kein Datum
+mb-tracks = { $count ->
+    [one] 1 Titel
+   *[other] { $count } Titel
+}
+mb-no-track = kein Titel
+mb-count-mismatch = Die Veröffentlichung hat { $tracks } Titel für { $files } Dateien
+mb-apply = Auf Album anwenden
+mb-apply-failed = Veröffentlichung konnte nicht übernommen werden: { $errors }
+mb-no-release = Keine Veröffentlichung ausgewählt
+mb-need-terms = Für die Suche wird ein Interpret oder Album benötigt
+
+## CUE sheets
+
+cue-title = CUE-Datei importieren
+cue-open = .cue öffnen...
+cue-read-failed = CUE-Datei konnte nicht gelesen werden: { $error }
+cue-no-tracks = Keine Titel in der CUE-Datei
+cue-unknown-artist = Unbekannter Interpret
+cue-unknown-album = Unbekanntes Album
+cue-no-genre = kein Genre
+cue-match-by = Titel zuordnen nach:
+cue-by-order = Reihenfolge
+cue-by-filename = Dateiname
+cue-skip = (überspringen)
+cue-write = Tags schreiben
+cue-write-failed = CUE-Tags konnten nicht geschrieben werden: { $errors }
+
+## Spreadsheets
+
+sheet-export-title = Tags exportieren
+sheet-export-summary = { $count ->
+    [one] 1 Datei, je eine Zeile. Spalten:
+   *[other] { $count } Dateien, je eine Zeile. Spalten:
+}
+sheet-export = Exportieren...
+sheet-export-failed = Tags konnten nicht exportiert werden: { $error }
+sheet-export-skipped = Ohne diese Dateien exportiert: { $errors }
+sheet-no-columns = Keine Spalten ausgewählt
+sheet-import-title = Tags importieren
+sheet-open = Tabelle öffnen...
+sheet-read-failed = Tabelle konnte nicht gelesen werden: { $error }
+sheet-not-array = Erwartet wurde ein JSON-Array aus Objekten
+sheet-match-by = Zeilen zuordnen nach:
+sheet-by-path = Pfad
+sheet-by-key = Schlüssel
+sheet-import-skipped = Nicht in der Vorschau: { $errors }
+sheet-import-summary = { $files ->
+    [one] 1 Datei mit Änderungen
+   *[other] { $files } Dateien mit Änderungen
+}, { $unmatched ->
+    [one] 1 Zeile ohne passende Datei
+   *[other] { $unmatched } Zeilen ohne passende Datei
+}
+sheet-empty = (leer)
+sheet-apply = Übernehmen
+sheet-import-failed = Tags konnten nicht importiert werden: { $errors }
+
+## Lyrics
+
+lyrics-heading = Liedtext:
+lyrics-synced-heading = Synchronisierter Liedtext:
+lyrics-add = hinzufügen
+lyrics-remove = entfernen
+lyrics-language = Sprache:
+lyrics-description = Beschreibung:
+lyrics-import-lrc = .lrc importieren
+lyrics-export-lrc = .lrc exportieren
+lyrics-now = jetzt
+lyrics-add-line = Zeile hinzufügen
+lyrics-save = Liedtext speichern
+lyrics-save-failed = Liedtext konnte nicht gespeichert werden: { $error }
+lyrics-import-failed = lrc konnte nicht importiert werden: { $error }
+lyrics-export-failed = lrc konnte nicht exportiert werden: { $error }
+lyrics-invalid-time = Ungültiger Zeitstempel '{ $time }'
+
+## Chapters
+
+chapters-heading = Kapitel:
+chapters-add = hinzufügen
+chapters-import = importieren
+chapters-export = exportieren
+chapters-format-podlove = Podlove-JSON
+chapters-format-text = Text (HH:MM:SS Titel)
+chapters-start = Beginn
+chapters-end = Ende
+chapters-title = Titel
+chapters-url = URL
+chapters-image = Bild
+chapters-toc = Inhalt
+chapters-image-size = { $kb } KB
+chapters-set-image = setzen
+chapters-remove = entfernen
+chapters-toc-heading = Inhaltsverzeichnis:
+chapters-toc-title = Titel:
+chapters-toc-ordered = geordnet
+chapters-nested-tocs = { $count ->
+    [one] 1 verschachteltes Inhaltsverzeichnis bleibt unverändert
+   *[other] { $count } verschachtelte Inhaltsverzeichnisse bleiben unverändert
+}
+chapters-save = Kapitel speichern
+chapters-save-failed = Kapitel konnten nicht gespeichert werden: { $error }
+chapters-image-failed = Kapitelbild konnte nicht gesetzt werden: { $error }
+chapters-import-failed = Kapitel konnten nicht importiert werden: { $error }
+chapters-export-failed = Kapitel konnten nicht exportiert werden: { $error }
+chapters-none-found = Keine Kapitel gefunden
+chapters-no-array = Kein Kapitel-Array
+chapters-not-array = Erwartet wurde ein JSON-Array aus Kapiteln
+chapters-no-start = Kapitel ohne gültige Anfangszeit
+chapters-invalid-start = Ungültige Anfangszeit '{ $time }'
+chapters-invalid-end = Ungültige Endzeit '{ $time }'
+chapters-ends-early = Kapitel '{ $title }' endet, bevor es beginnt
+
+## Errors and the log
+
+error-unsupported = Nicht unterstütztes Format: { $error }
+error-corrupt-tag = Beschädigter Tag: { $error }
+error-permission = Zugriff verweigert: { $error }
+error-permission-file = Zugriff auf { $file } verweigert: { $error }
+error-decode = Dekodieren fehlgeschlagen: { $error }
+log-title = Protokoll
+log-link = Protokoll ({ $count })
+log-info = Info
+log-warning = Warnung
+log-error = Fehler
+log-show-info = Details und Zeitmessungen anzeigen
+log-selected-only = Nur die ausgewählte Datei
+log-copy = In die Zwischenablage kopieren
+log-clear = Leeren
+log-empty = Noch keine Einträge
+log-art-decoded = Dekodieren des Covers dauerte { $time }
+log-art-resized = Skalieren des Covers dauerte { $time }
+log-art-uploaded = Hochladen des Covers dauerte { $time }
@@ -0,0 +1,541 @@
+# English, also the fallback for keys missing from other languages.
+
+## File dialogs
+
+filter-image = Image
+filter-audio = Audio
+filter-wave = Wave
+filter-csv = CSV
+filter-json = JSON
+filter-spreadsheet = Spreadsheet
+filter-cue = CUE sheet
+filter-lrc = Lyrics
+
+## Song list
+
+files-heading = Files
+files-add = add
+files-add-folder = folder
+files-add-folder-hint = Add every mp3 in a folder
+files-not-mp3 = File '{ $file }' is not an mp3!
+files-song-list = Song list:
+files-remove = Remove
+files-none-selected = No File Selected
+files-empty = Add or drag a file!
+
+## Menus
+
+menu-recent = Recent
+menu-tools = Tools
+menu-language = Language
+menu-export = Export tags...
+menu-import = Import tags...
+menu-cue = Import CUE sheet...
+menu-musicbrainz = MusicBrainz lookup...
+menu-identify = Identify untagged files...
+menu-duplicates = Find duplicates...
+menu-lint = Check library...
+menu-mojibake = Repair text encoding...
+menu-genres = Consolidate genres...
+menu-versions = ID3 versions...
+menu-tag-blocks = ID3v1 / APEv2 tags...
+menu-log = Log...
+menu-saving = Saving...
+menu-appearance = Appearance...
+menu-shortcuts = Keyboard shortcuts...
+menu-palette = Command palette...
+
+## Artwork
+
+art-heading = Artwork:
+art-edit = edit
+art-find-cover = find cover
+art-save = save image
+art-loading = Loading...
+art-none = No album art
+art-none-found = No album art found
+art-set-failed = Failed to pick/set album art: { $error }
+art-save-failed = Failed to save album art: { $error }
+art-load-failed = Failed to load album art: { $error }
+
+## Tags tab
+
+tab-tags = Tags
+tab-lyrics = Lyrics
+tab-chapters = Chapters
+tags-more-fields = More fields
+info-bitrate = Bitrate:
+info-kbps = { $kbps } kbps
+info-unknown-bitrate = Unknown bitrate
+info-sample-rate = Sample Rate:
+info-khz = { $khz } kHz
+info-hz = { $hz } Hz
+info-unknown-sample-rate = Unknown sample rate
+info-duration = Duration:
+info-seconds = { $seconds } seconds
+info-unknown-duration = Unknown duration
+
+## Playback
+
+playback-play = play
+playback-pause = pause
+playback-render = Render to wav...
+playback-failed = Playback failed: { $error }
+playback-no-device = No output device
+playback-no-output = This build has no sound output; render to wav to listen
+audio-no-track = No default track
+
+## Fields
+
+field-title = Title
+field-artist = Artist
+field-album = Album
+field-album-artist = Album Artist
+field-genre = Genre
+field-year = Year
+field-track = Track
+field-disc = Disc
+field-composer = Composer
+field-comment = Comment
+field-invalid-year = Invalid year '{ $value }'
+field-invalid-number = Invalid number '{ $value }'
+
+## Tag form
+
+form-field-heading = { $field }:
+form-list = list
+form-list-hint = Edit one value per row
+form-several-values = Has several values; edit them with "list"
+form-unsaved = { $count ->
+    [one] 1 unsaved change
+   *[other] { $count } unsaved changes
+}
+form-save = Save
+form-revert = Revert
+form-save-failed = Failed to save tags: { $error }
+form-no-file = No file selected
+
+## Changes made by other programs
+
+watch-failed = Can't watch files for changes: { $error }
+watch-folder-failed = Can't watch { $folder } for changes: { $error }
+watch-unwatch-failed = Couldn't stop watching { $folder }: { $error }
+watch-deleted = { $file } was deleted or moved away
+conflict-title = File changed on disk
+conflict-changed = { $file } was changed by another program while you were editing it.
+conflict-keep-warning = Keeping your edits will overwrite the other program's changes to the same fields when you save.
+conflict-reload = Reload from disk
+conflict-keep = Keep my edits
+
+## Recent files
+
+recent-none = No recent files
+recent-folders = Folders
+recent-clear = Clear
+recent-missing = { $file } no longer exists
+recent-no-mp3s = No mp3 files in { $folder }
+recent-folder-failed = Failed to open folder: { $error }
+
+## Appearance
+
+theme-title = Appearance
+theme-system = Follow system
+theme-dark = Dark
+theme-light = Light
+theme-high-contrast = High contrast
+theme-colors-dark = Colors for the dark theme
+theme-colors-light = Colors for the light theme
+theme-colors-high-contrast = Colors for the high contrast theme
+theme-reset = reset
+theme-reset-all = Reset all colors
+role-panel = Panel background
+role-text = Text
+role-muted-text = Dimmed text
+role-item = File list entry
+role-selection = Selected file
+role-selected-text = Selected file text
+role-changed = Unsaved field
+role-error = Errors
+role-warning = Warnings
+
+## Commands
+
+command-next-file = Next file
+command-previous-file = Previous file
+command-save = Save changes
+command-revert = Revert changes
+command-undo = Undo last save
+command-focus-field = Edit fields
+command-add-file = Add files
+command-add-folder = Add folder
+command-show-tags = Show tags
+command-show-lyrics = Show lyrics
+command-show-chapters = Show chapters
+command-play-pause = Play / pause
+command-export-tags = Export tags
+command-import-tags = Import tags
+command-import-cue = Import CUE sheet
+command-music-brainz = MusicBrainz lookup
+command-find-cover = Find cover
+command-identify = Identify untagged files
+command-find-duplicates = Find duplicates
+command-check-library = Check library
+command-repair-encoding = Repair text encoding
+command-consolidate-genres = Consolidate genres
+command-id3-versions = ID3 versions
+command-tag-blocks = ID3v1 / APEv2 tags
+command-save-options = Saving options
+command-appearance = Appearance
+command-shortcuts = Keyboard shortcuts
+command-palette = Command palette
+command-log = Show log
+undo-nothing = Nothing to undo
+undo-failed = Failed to undo: { $error }
+palette-title = Command palette
+palette-hint = Type a command
+shortcuts-title = Keyboard shortcuts
+shortcuts-recording = Press a key...
+shortcuts-change = change
+shortcuts-clear = clear
+shortcuts-reset = Reset to defaults
+
+## Saving
+
+save-title = Saving
+save-explanation = Tags are written to a copy that's checked before it replaces the file.
+save-preserve-times = Keep each file's modification time and permissions
+save-keep-backups = Keep a backup of each file before saving
+save-no-folder = No folder chosen
+save-choose-folder = Choose folder...
+save-prune-before = Delete backups after
+save-prune-after = days (0 keeps them)
+save-prune-now = Clean up now
+save-pruned = { $count ->
+    [one] Deleted 1 old backup
+   *[other] Deleted { $count } old backups
+}
+save-prune-failed = Failed to clean up backups: { $error }
+save-read-only = { $file } is read-only; make it writable to save its tags
+save-no-name = File has no name
+save-audio-changed = the audio data changed while writing; the original file was kept
+
+## Multi-valued fields
+
+values-title = Edit { $field }
+values-remove = Remove this value
+values-add = + add
+values-separator = ID3v2.3 separator:
+values-separator-hint = ID3v2.4 keeps each value separate; ID3v2.3 has to join them into one
+values-save-failed = Failed to save { $field }: { $error }
+
+## Genres
+
+genres-find = Find genre:
+genres-add = Add "{ $genre }" as a genre
+genres-title = Consolidate genres
+genres-explanation = Pick the spellings to merge, then the genre to merge them into.
+genres-merge-into = Merge into:
+genres-merge = Merge
+genres-failed = Failed to update genres: { $errors }
+
+## Library check
+
+lint-title = Check library
+lint-summary = { $problems ->
+    [one] 1 problem
+   *[other] { $problems } problems
+} in { $files ->
+    [one] 1 file
+   *[other] { $files } files
+}
+lint-check-again = Check again
+lint-fix-all = Fix all
+lint-fix-all-hint = Cropping artwork is left to the fix button of each file
+lint-fix = fix
+lint-largest-art = Largest artwork:
+lint-fix-failed = Failed to fix tags: { $errors }
+lint-only-v1 = Only has an ID3v1 tag
+lint-missing = Missing { $field }
+lint-whitespace = { $field } has leading or trailing whitespace
+lint-art-large = Artwork is { $width }x{ $height }
+lint-art-not-square = Artwork is not square ({ $width }x{ $height })
+lint-duplicate-frames = Duplicate frames: { $frames }
+lint-differs = { $field } "{ $value }" differs from the rest of the album
+lint-missing-track = Missing track number
+lint-track-gaps = Track { $track } leaves gaps or repeats in the album
+lint-mixed-versions = Tag is { $version } while others differ
+fix-clear = clear { $field }
+fix-set = set { $field } to "{ $value }"
+fix-rewrite = rewrite the tag
+fix-convert = rewrite as { $version }
+fix-convert-v1 = copy to an ID3v2 tag
+fix-shrink-art = scale down to at most { $max }px
+fix-crop-art = crop to a square
+
+## Text encoding repair
+
+mojibake-title = Repair text encoding
+mojibake-summary = { $count ->
+    [one] 1 file with text that looks mis-decoded
+   *[other] { $count } files with text that looks mis-decoded
+}
+mojibake-write-as = Write as:
+mojibake-v24 = ID3v2.4 (UTF-8)
+mojibake-v23 = ID3v2.3 (UTF-16)
+mojibake-preview-invalid = (not valid in this encoding)
+mojibake-rewrite = Rewrite
+mojibake-invalid = "{ $text }" is not valid { $encoding }
+mojibake-failed = Failed to repair tags: { $errors }
+
+## ID3 versions
+
+versions-title = ID3 versions
+versions-when-saving = When saving
+versions-keep = Keep each file's version
+versions-always-preferred = Always write the preferred version
+versions-preferred = Preferred version:
+versions-convert-heading = Convert loaded files
+versions-convert-to = Convert to:
+versions-no-v2-tag = no ID3v2 tag
+versions-no-tag = no tag
+versions-convert = Convert
+versions-failed = Failed to convert tags: { $errors }
+versions-merged-date = TYER/TDAT/TIME merged into TDRC
+versions-split-date = TDRC split into TYER/TDAT/TIME
+versions-moved = { $from } moved to { $to }
+versions-dropped = { $frame } dropped: not part of { $version }
+
+## ID3v1 and APEv2 tags
+
+blocks-title = Tag blocks
+blocks-no-file = Select a file to see its tags.
+blocks-present = present
+blocks-absent = not in this file
+blocks-copy-from = Copy from
+blocks-copy-to = to
+blocks-v1-preview = ID3v1 { $field }: "{ $value }"
+blocks-sync = Sync
+blocks-strip-label = Strip
+blocks-strip = Strip
+blocks-all-files = Apply to all { $count } loaded files
+blocks-no-tag = no { $block } tag
+blocks-sync-failed = Failed to sync tags: { $errors }
+blocks-strip-failed = Failed to strip tags: { $errors }
+
+## Duplicates
+
+duplicates-title = Find duplicates
+duplicates-match-by = Match by:
+duplicates-by-name = artist + title
+duplicates-by-hash = audio hash
+duplicates-by-fingerprint = fingerprint
+duplicates-same-audio = identical audio
+duplicates-similar-fingerprint = similar fingerprint
+duplicates-scan = Scan
+duplicates-progress = Scanned { $done } of { $total }
+duplicates-found = { $count ->
+    [one] 1 group found
+   *[other] { $count } groups found
+}
+duplicates-rescan = Scan again to fingerprint the files.
+duplicates-remove-others = Remove others from list
+duplicates-move-others = Move others to folder...
+duplicates-exists = { $file } already exists
+duplicates-move-failed = Failed to move files: { $errors }
+
+## Online lookups
+
+online-endpoint = Endpoint:
+online-artist = Artist:
+online-album = Album:
+online-search = Search
+online-searching = Searching...
+online-downloading = Downloading...
+online-search-failed = Search failed: { $error }
+
+## Cover search
+
+cover-title = Find cover
+cover-mbid = Release MBID:
+cover-found = { $count ->
+    [one] 1 image found
+   *[other] { $count } images found
+}
+cover-no-preview = (no preview)
+cover-unknown-size = unknown size
+cover-use = use
+
+## Identify untagged files
+
+identify-title = Identify untagged files
+identify-summary = { $count ->
+    [one] 1 file with an empty title or artist
+   *[other] { $count } files with an empty title or artist
+}
+identify-query = Look up fingerprints on AcoustID
+identify-api-key = API key:
+identify-start = Fingerprint
+identify-apply = apply
+identify-queued = Queued
+identify-fingerprinted = Fingerprinted
+identify-failed = Failed: { $error }
+identify-no-match = No match
+identify-suggestions = { $count ->
+    [one] 1 suggestion
+   *[other] { $count } suggestions
+}
+identify-lookup-failed = Lookup failed: { $error }
+identify-tagged = Tagged as { $artist } - { $title }
+identify-apply-failed = Failed to apply suggestion: { $error }
+identify-no-audio = No audio to fingerprint
+
+## MusicBrainz
+
+mb-title = MusicBrainz lookup
+mb-server = Server:
+mb-search-tags = Search by tags
+mb-search-durations = Search by durations
+mb-waiting = Waiting for MusicBrainz...
+mb-dropped = Request was dropped
+mb-lookup-failed = MusicBrainz lookup failed: { $error }
+mb-candidates = { $count ->
+    [one] 1 candidate release
+   *[other] { $count } candidate releases
+}
+mb-album-files = { $count ->
+    [one] 1 file in this album
+   *[other] { $count } files in this album
+}
+mb-no-date = no date
+mb-tracks = { $count ->
+    [one] 1 track
+   *[other] { $count } tracks
+}
+mb-no-track = no track
+mb-count-mismatch = Release has { $tracks } tracks for { $files } files
+mb-apply = Apply to album
+mb-apply-failed = Failed to apply release: { $errors }
+mb-no-release = No release selected
+mb-need-terms = Need an artist or album to search for
+
+## CUE sheets
+
+cue-title = Import CUE sheet
+cue-open = Open .cue...
+cue-read-failed = Failed to read CUE sheet: { $error }
+cue-no-tracks = No tracks in CUE sheet
+cue-unknown-artist = Unknown artist
+cue-unknown-album = Unknown album
+cue-no-genre = no genre
+cue-match-by = Match tracks by:
+cue-by-order = order
+cue-by-filename = filename
+cue-skip = (skip)
+cue-write = Write tags
+cue-write-failed = Failed to write CUE tags: { $errors }
+
+## Spreadsheets
+
+sheet-export-title = Export tags
+sheet-export-summary = { $count ->
+    [one] 1 file, one row each. Columns:
+   *[other] { $count } files, one row each. Columns:
+}
+sheet-export = Export...
+sheet-export-failed = Failed to export tags: { $error }
+sheet-export-skipped = Exported without these files: { $errors }
+sheet-no-columns = No columns selected
+sheet-import-title = Import tags
+sheet-open = Open sheet...
+sheet-read-failed = Failed to read sheet: { $error }
+sheet-not-array = Expected a JSON array of objects
+sheet-match-by = Match rows by:
+sheet-by-path = path
+sheet-by-key = key
+sheet-import-skipped = Left out of the preview: { $errors }
+sheet-import-summary = { $files ->
+    [one] 1 file with changes
+   *[other] { $files } files with changes
+}, { $unmatched ->
+    [one] 1 row matched no loaded file
+   *[other] { $unmatched } rows matched no loaded file
+}
+sheet-empty = (empty)
+sheet-apply = Apply
+sheet-import-failed = Failed to import tags: { $errors }
+
+## Lyrics
+
+lyrics-heading = Lyrics:
+lyrics-synced-heading = Synced lyrics:
+lyrics-add = add
+lyrics-remove = remove
+lyrics-language = Language:
+lyrics-description = Description:
+lyrics-import-lrc = import .lrc
+lyrics-export-lrc = export .lrc
+lyrics-now = now
+lyrics-add-line = add line
+lyrics-save = Save lyrics
+lyrics-save-failed = Failed to save lyrics: { $error }
+lyrics-import-failed = Failed to import lrc: { $error }
+lyrics-export-failed = Failed to export lrc: { $error }
+lyrics-invalid-time = Invalid timestamp '{ $time }'
+
+## Chapters
+
+chapters-heading = Chapters:
+chapters-add = add
+chapters-import = import
+chapters-export = export
+chapters-format-podlove = Podlove JSON
+chapters-format-text = Text (HH:MM:SS Title)
+chapters-start = Start
+chapters-end = End
+chapters-title = Title
+chapters-url = URL
+chapters-image = Image
+chapters-toc = TOC
+chapters-image-size = { $kb } KB
+chapters-set-image = set
+chapters-remove = remove
+chapters-toc-heading = Table of contents:
+chapters-toc-title = Title:
+chapters-toc-ordered = ordered
+chapters-nested-tocs = { $count ->
+    [one] 1 nested table of contents kept as-is
+   *[other] { $count } nested tables of contents kept as-is
+}
+chapters-save = Save chapters
+chapters-save-failed = Failed to save chapters: { $error }
+chapters-image-failed = Failed to set chapter image: { $error }
+chapters-import-failed = Failed to import chapters: { $error }
+chapters-export-failed = Failed to export chapters: { $error }
+chapters-none-found = No chapters found
+chapters-no-array = No chapters array
+chapters-not-array = Expected a JSON array of chapters
+chapters-no-start = Chapter without a valid start time
+chapters-invalid-start = Invalid start time '{ $time }'
+chapters-invalid-end = Invalid end time '{ $time }'
+chapters-ends-early = Chapter '{ $title }' ends before it starts
+
+## Errors and the log
+
+error-unsupported = Unsupported format: { $error }
+error-corrupt-tag = Corrupt tag: { $error }
+error-permission = Permission denied: { $error }
+error-permission-file = Permission denied for { $file }: { $error }
+error-decode = Couldn't decode: { $error }
+log-title = Log
+log-link = Log ({ $count })
+log-info = info
+log-warning = warning
+log-error = error
+log-show-info = Show details and timings
+log-selected-only = Only the selected file
+log-copy = Copy to clipboard
+log-clear = Clear
+log-empty = Nothing logged yet
+log-art-decoded = Decoding album art took { $time }
+log-art-resized = Resizing album art took { $time }
+log-art-uploaded = Uploading album art took { $time }
@@ -0,0 +1,255 @@
+use std::io::Read;
+use std::ops::Range;
+use std::path::Path;
+
+/// Size of an ID3v2 tag starting at `bytes[0]`, header and footer included.
+fn id3v2_len(bytes: &[u8]) -> Option<usize> {
+    if bytes.len() < 10 || &bytes[..3] != b"ID3" {
+        return None;
+    }
+    // sizes are "syncsafe": 7 bits per byte
+    let size = bytes[6..10].iter().fold(0usize, |acc, b| (acc << 7) | (*b & 0x7f) as usize);
+    let footer = if bytes[5] & 0x10 != 0 { 10 } else { 0 };
+    Some(10 + size + footer)
+}
+
+/// Size of an APEv2 tag whose footer ends at `bytes[end]`, header included.
+fn ape_len(bytes: &[u8], end: usize) -> Option<usize> {
+    if end < 32 || &bytes[end - 32..end - 24] != b"APETAGEX" {
+        return None;
+    }
+    let footer = &bytes[end - 32..end];
+    let size = u32::from_le_bytes(footer[12..16].try_into().ok()?) as usize;
+    let flags = u32::from_le_bytes(footer[20..24].try_into().ok()?);
+    let header = if flags & 0x8000_0000 != 0 { 32 } else { 0 };
+    Some(size + header)
+}
+
+/// The part of an mp3 file that is audio: everything between leading ID3v2
+/// tags and trailing APEv2/ID3v1 tags.
+pub fn stream_range(bytes: &[u8]) -> Range<usize> {
+    let mut start = 0;
+    while let Some(len) = id3v2_len(&bytes[start..]) {
+        start = (start + len).min(bytes.len());
+    }
+
+    let mut end = bytes.len();
+    if end >= start + 128 && &bytes[end - 128..end - 125] == b"TAG" {
+        end -= 128;
+    }
+    if let Some(len) = ape_len(bytes, end)
+        && len <= end - start
+    {
+        end -= len;
+    }
+
+    start..end
+}
+
+/// The bytes before and after the audio, the only part a tag edit changes.
+pub fn tag_regions(bytes: &[u8]) -> (Vec<u8>, Vec<u8>) {
+    let range = stream_range(bytes);
+    (bytes[..range.start].to_vec(), bytes[range.end..].to_vec())
+}
+
+/// FNV-1a, which is stable across builds so hashes can be stored and compared later.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for b in bytes {
+        hash ^= *b as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash
+}
+
+/// Hash of the audio data alone, so it survives any tag edit.
+pub fn stream_hash<P: AsRef<Path>>(path: P) -> crate::errors::Result<String> {
+    let bytes = std::fs::read(path)?;
+    let range = stream_range(&bytes);
+    Ok(format!("{:016x}", fnv1a(&bytes[range])))
+}
+
+/// Ids and body ranges of the frames in the ID3v2 tag at the start of `bytes`,
+/// in file order and including repeats, which the id3 crate folds together on read.
+pub fn id3v2_frames(bytes: &[u8]) -> Vec<(String, Range<usize>)> {
+    let mut frames = Vec::new();
+    let Some(len) = id3v2_len(bytes) else {
+        return frames;
+    };
+    let (major, flags) = (bytes[3], bytes[5]);
+    // a whole-tag unsynchronised v2.2/v2.3 tag would have to be decoded first
+    if flags & 0x80 != 0 && major < 4 {
+        return frames;
+    }
+    let end = len.min(bytes.len());
+    let (id_len, header_len) = if major == 2 { (3, 6) } else { (4, 10) };
+
+    let mut pos = 10;
+    if flags & 0x40 != 0 && major > 2 && pos + 4 <= end {
+        let size = &bytes[pos..pos + 4];
+        pos += if major == 4 {
+            size.iter().fold(0usize, |acc, b| (acc << 7) | (*b & 0x7f) as usize)
+        } else {
+            4 + u32::from_be_bytes(size.try_into().unwrap_or_default()) as usize
+        };
+    }
+
+    while pos + header_len <= end {
+        let id = &bytes[pos..pos + id_len];
+        // padding, or garbage we can't walk past
+        if !id.iter().all(|b| b.is_ascii_uppercase() || b.is_ascii_digit()) {
+            break;
+        }
+        let size_bytes = &bytes[pos + id_len..pos + header_len - if major == 2 { 0 } else { 2 }];
+        let size = match major {
+            2 | 3 => size_bytes.iter().fold(0usize, |acc, b| (acc << 8) | *b as usize),
+            _ => size_bytes.iter().fold(0usize, |acc, b| (acc << 7) | (*b & 0x7f) as usize),
+        };
+        let body = pos + header_len..(pos + header_len + size).min(end);
+        frames.push((String::from_utf8_lossy(id).into_owned(), body.clone()));
+        pos = body.end;
+    }
+    frames
+}
+
+/// The ID3v2 tag at the start of the file, read without the audio after it; empty if there is none.
+pub fn read_id3v2<P: AsRef<Path>>(path: P) -> std::io::Result<Vec<u8>> {
+    let mut file = std::fs::File::open(path)?;
+    let mut bytes = vec![0u8; 10];
+    if file.read_exact(&mut bytes).is_err() {
+        return Ok(Vec::new());
+    }
+    let Some(len) = id3v2_len(&bytes) else {
+        return Ok(Vec::new());
+    };
+    file.take((len - 10) as u64).read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// The version of the ID3v2 tag at the start of the file, from its header alone.
+pub fn id3v2_version<P: AsRef<Path>>(path: P) -> Option<id3::Version> {
+    let mut header = [0u8; 10];
+    std::fs::File::open(path).ok()?.read_exact(&mut header).ok()?;
+    if &header[..3] != b"ID3" {
+        return None;
+    }
+    match header[3] {
+        2 => Some(id3::Version::Id3v22),
+        3 => Some(id3::Version::Id3v23),
+        4 => Some(id3::Version::Id3v24),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn id3v2(body_len: usize) -> Vec<u8> {
+        let mut tag = b"ID3\x04\x00\x00".to_vec();
+        tag.extend((0..4).rev().map(|i| ((body_len >> (7 * i)) & 0x7f) as u8));
+        tag.extend(vec![0; body_len]);
+        tag
+    }
+
+    fn ape(items_len: usize) -> Vec<u8> {
+        let footer = |flags: u32| {
+            let mut f = b"APETAGEX".to_vec();
+            f.extend(2000u32.to_le_bytes());
+            f.extend(((items_len + 32) as u32).to_le_bytes());
+            f.extend(0u32.to_le_bytes());
+            f.extend(flags.to_le_bytes());
+            f.extend([0; 8]);
+            f
+        };
+        let mut tag = footer(0xa000_0000);
+        tag.extend(vec![b'i'; items_len]);
+        tag.extend(footer(0x8000_0000));
+        tag
+    }
+
+    fn id3v1() -> Vec<u8> {
+        let mut tag = b"TAG".to_vec();
+        tag.resize(128, 0);
+        tag
+    }
+
+    #[test]
+    fn bare_audio() {
+        let audio = vec![0xff; 300];
+        assert_eq!(stream_range(&audio), 0..300);
+    }
+
+    #[test]
+    fn every_tag_around_the_audio() {
+        let audio = vec![0xff; 300];
+        let mut file = id3v2(20);
+        file.extend(id3v2(5));
+        let start = file.len();
+        file.extend(&audio);
+        let end = file.len();
+        file.extend(ape(40));
+        file.extend(id3v1());
+        assert_eq!(stream_range(&file), start..end);
+        assert_eq!(&file[stream_range(&file)], &audio[..]);
+    }
+
+    #[test]
+    fn tag_regions_put_back_the_original() {
+        let audio = vec![0xff; 300];
+        let mut original = id3v2(20);
+        original.extend(&audio);
+        original.extend(ape(40));
+        let (head, tail) = tag_regions(&original);
+
+        // retagged with a bigger ID3v2 tag and an ID3v1 tag instead of APE
+        let mut edited = id3v2(500);
+        edited.extend(&audio);
+        edited.extend(id3v1());
+        let restored = [head.as_slice(), &edited[stream_range(&edited)], tail.as_slice()].concat();
+        assert_eq!(restored, original);
+    }
+
+    #[test]
+    fn version_from_header() {
+        let dir = std::env::temp_dir().join(format!("metadata-version-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let version_of = |bytes: &[u8]| {
+            let path = dir.join("file.mp3");
+            std::fs::write(&path, bytes).unwrap();
+            id3v2_version(&path)
+        };
+        assert_eq!(version_of(b"ID3\x02\x00\x00\x00\x00\x00\x00"), Some(id3::Version::Id3v22));
+        assert_eq!(version_of(b"ID3\x03\x00\x00\x00\x00\x00\x00"), Some(id3::Version::Id3v23));
+        assert_eq!(version_of(&id3v2(10)), Some(id3::Version::Id3v24));
+        assert_eq!(version_of(b"ID3\x05\x00\x00\x00\x00\x00\x00"), None);
+        assert_eq!(version_of(&[0xff; 300]), None);
+        // too short for a header
+        assert_eq!(version_of(b"ID3\x04"), None);
+        assert_eq!(id3v2_version(dir.join("missing.mp3")), None);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn trailers_without_id3v2() {
+        let audio = vec![0xff; 300];
+        let mut with_ape = audio.clone();
+        with_ape.extend(ape(10));
+        assert_eq!(stream_range(&with_ape), 0..300);
+
+        let mut with_v1 = audio.clone();
+        with_v1.extend(id3v1());
+        assert_eq!(stream_range(&with_v1), 0..300);
+    }
+
+    #[test]
+    fn hash_ignores_tags() {
+        let audio = vec![0x55; 300];
+        let mut tagged = id3v2(50);
+        tagged.extend(&audio);
+        tagged.extend(ape(10));
+        tagged.extend(id3v1());
+        let tagged_range = stream_range(&tagged);
+        assert_eq!(fnv1a(&tagged[tagged_range]), fnv1a(&audio));
+    }
+}
@@ -0,0 +1,710 @@
+use eframe::egui;
+use id3::frame::{Chapter, ExtendedLink, Picture, PictureType, TableOfContents};
+use id3::{Content, Frame, Tag, TagLike};
+use serde_json::{Value, json};
+
+use crate::errors;
+use crate::i18n::t;
+use crate::lyrics::parse_ms;
+use crate::{MyApp, cue};
+use crate::theme::Role;
+
+/// CHAP byte offsets are unused when set to this.
+const NO_OFFSET: u32 = 0xffff_ffff;
+/// The id a new top-level table of contents gets, numbered if a chapter already uses it.
+const TOC_ID: &str = "toc";
+
+/// `hh:mm:ss.mmm`, used for chapter times and Podlove exports.
+pub fn format_hms(ms: u32) -> String {
+    format!(
+        "{:02}:{:02}:{:02}.{:03}",
+        ms / 3_600_000,
+        (ms / 60_000) % 60,
+        (ms / 1000) % 60,
+        ms % 1000
+    )
+}
+
+/// A chapter as read from or written to one of the exchange formats.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ChapterMark {
+    pub start_ms: u32,
+    pub title: String,
+    pub url: String,
+}
+
+/// One line per chapter, `HH:MM:SS Title`. Fractions of a second and `MM:SS` are accepted too.
+pub fn parse_text(input: &str) -> Vec<ChapterMark> {
+    input
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim();
+            let (time, title) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+            Some(ChapterMark {
+                start_ms: parse_ms(time)?,
+                title: title.trim().to_string(),
+                url: String::new(),
+            })
+        })
+        .collect()
+}
+
+pub fn to_text(marks: &[ChapterMark]) -> String {
+    marks
+        .iter()
+        .map(|m| {
+            let seconds = m.start_ms / 1000;
+            format!("{:02}:{:02}:{:02} {}\n", seconds / 3600, (seconds / 60) % 60, seconds % 60, m.title)
+        })
+        .collect()
+}
+
+/// Podlove Simple Chapters as JSON: an array of `{start, title, href, image}`.
+/// The podcast namespace layout (`{"chapters": [{startTime, title, url}]}`) is read as well.
+pub fn parse_podlove_json(input: &str) -> errors::Result<Vec<ChapterMark>> {
+    let value: Value = serde_json::from_str(input)?;
+    let entries = match &value {
+        Value::Array(entries) => entries,
+        Value::Object(map) => map
+            .get("chapters")
+            .and_then(|c| c.as_array())
+            .ok_or_else(|| t!("chapters-no-array"))?,
+        _ => return Err(t!("chapters-not-array").into()),
+    };
+
+    let text = |entry: &Value, keys: &[&str]| {
+        keys.iter()
+            .find_map(|k| entry.get(k).and_then(|v| v.as_str()))
+            .unwrap_or("")
+            .to_string()
+    };
+
+    let mut marks = Vec::new();
+    for entry in entries {
+        let start_ms = match entry.get("start").or_else(|| entry.get("startTime")) {
+            Some(Value::String(s)) => parse_ms(s),
+            Some(Value::Number(n)) => n.as_f64().map(|secs| (secs * 1000.0).round() as u32),
+            _ => None,
+        }
+        .ok_or_else(|| t!("chapters-no-start"))?;
+
+        marks.push(ChapterMark {
+            start_ms,
+            title: text(entry, &["title"]),
+            url: text(entry, &["href", "url"]),
+        });
+    }
+    Ok(marks)
+}
+
+pub fn to_podlove_json(marks: &[ChapterMark]) -> String {
+    let entries: Vec<Value> = marks
+        .iter()
+        .map(|m| {
+            json!({
+                "start": format_hms(m.start_ms),
+                "title": m.title,
+                "href": m.url,
+                "image": "",
+            })
+        })
+        .collect();
+    serde_json::to_string_pretty(&entries).unwrap_or_default()
+}
+
+pub fn from_cue(sheet: &cue::CueSheet) -> Vec<ChapterMark> {
+    sheet
+        .tracks
+        .iter()
+        .map(|t| ChapterMark {
+            start_ms: t.start_ms.unwrap_or(0),
+            title: t.title.clone().unwrap_or_default(),
+            url: String::new(),
+        })
+        .collect()
+}
+
+pub fn to_cue(marks: &[ChapterMark], file: &str, title: Option<String>, performer: Option<String>) -> String {
+    let sheet = cue::CueSheet {
+        performer,
+        title,
+        file: Some(file.to_string()),
+        tracks: marks
+            .iter()
+            .enumerate()
+            .map(|(i, m)| cue::CueTrack {
+                number: i as u32 + 1,
+                title: Some(m.title.clone()),
+                performer: None,
+                file: None,
+                start_ms: Some(m.start_ms),
+            })
+            .collect(),
+        ..Default::default()
+    };
+    cue::write(&sheet)
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum ChapterFormat {
+    Cue,
+    Podlove,
+    Text,
+}
+
+impl ChapterFormat {
+    const ALL: [ChapterFormat; 3] = [ChapterFormat::Cue, ChapterFormat::Podlove, ChapterFormat::Text];
+
+    fn label(self) -> String {
+        t!(match self {
+            ChapterFormat::Cue => "filter-cue",
+            ChapterFormat::Podlove => "chapters-format-podlove",
+            ChapterFormat::Text => "chapters-format-text",
+        })
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            ChapterFormat::Cue => "cue",
+            ChapterFormat::Podlove => "json",
+            ChapterFormat::Text => "txt",
+        }
+    }
+}
+
+/// One editable CHAP frame.
+#[derive(Clone, PartialEq)]
+struct ChapterDraft {
+    element_id: String,
+    start: String,
+    end: String,
+    title: String,
+    url: String,
+    image: Option<Picture>,
+    in_toc: bool,
+    /// Frames we don't edit (descriptions, other links) are carried over untouched.
+    other_frames: Vec<Frame>,
+}
+
+impl ChapterDraft {
+    fn from_chapter(chapter: &Chapter, in_toc: bool) -> Self {
+        let mut draft = Self {
+            element_id: chapter.element_id.clone(),
+            start: format_hms(chapter.start_time),
+            end: format_hms(chapter.end_time),
+            title: String::new(),
+            url: String::new(),
+            image: None,
+            in_toc,
+            other_frames: Vec::new(),
+        };
+        for frame in &chapter.frames {
+            match (frame.id(), frame.content()) {
+                ("TIT2", Content::Text(title)) => draft.title = title.clone(),
+                ("WXXX", Content::ExtendedLink(link)) if draft.url.is_empty() => draft.url = link.link.clone(),
+                ("APIC", Content::Picture(picture)) if draft.image.is_none() => draft.image = Some(picture.clone()),
+                _ => draft.other_frames.push(frame.clone()),
+            }
+        }
+        draft
+    }
+
+    fn to_chapter(&self) -> Result<Chapter, String> {
+        let start_time = parse_ms(&self.start).ok_or_else(|| t!("chapters-invalid-start", time = self.start.as_str()))?;
+        let end_time = parse_ms(&self.end).ok_or_else(|| t!("chapters-invalid-end", time = self.end.as_str()))?;
+        if end_time < start_time {
+            return Err(t!("chapters-ends-early", title = self.title.as_str()));
+        }
+
+        let mut frames = self.other_frames.clone();
+        if !self.title.is_empty() {
+            frames.push(Frame::text("TIT2", self.title.clone()));
+        }
+        if !self.url.is_empty() {
+            frames.push(ExtendedLink { description: String::new(), link: self.url.clone() }.into());
+        }
+        if let Some(image) = &self.image {
+            frames.push(image.clone().into());
+        }
+
+        Ok(Chapter {
+            element_id: self.element_id.clone(),
+            start_time,
+            end_time,
+            start_offset: NO_OFFSET,
+            end_offset: NO_OFFSET,
+            frames,
+        })
+    }
+
+    fn mark(&self) -> Option<ChapterMark> {
+        Some(ChapterMark {
+            start_ms: parse_ms(&self.start)?,
+            title: self.title.clone(),
+            url: self.url.clone(),
+        })
+    }
+}
+
+/// Edit buffers for the chapters tab.
+#[derive(Default)]
+pub struct ChapterEditor {
+    loaded_for: Option<String>,
+    chapters: Vec<ChapterDraft>,
+    toc_title: String,
+    toc_ordered: bool,
+    /// The loaded top-level table of contents' id, kept so it stays the same on save.
+    toc_id: Option<String>,
+    /// Nested tables of contents aren't editable here, only preserved.
+    nested_tocs: Vec<TableOfContents>,
+    duration_ms: u32,
+    /// Chapters, TOC title and ordering as last loaded or saved.
+    saved: (Vec<ChapterDraft>, String, bool),
+}
+
+impl ChapterEditor {
+    fn load(&mut self, path: &str, tag: Option<&Tag>) {
+        self.loaded_for = Some(path.to_string());
+        self.duration_ms = MyApp::get_duration_seconds(path)
+            .ok()
+            .flatten()
+            .map(|s| (s * 1000.0) as u32)
+            .unwrap_or(0);
+
+        let top = tag.and_then(|t| t.tables_of_contents().find(|toc| toc.top_level).cloned());
+        self.toc_ordered = top.as_ref().is_none_or(|toc| toc.ordered);
+        self.toc_id = top.as_ref().map(|toc| toc.element_id.clone());
+        self.toc_title = top
+            .as_ref()
+            .and_then(|toc| toc.frames.iter().find(|f| f.id() == "TIT2"))
+            .and_then(|f| f.content().text())
+            .unwrap_or_default()
+            .to_string();
+        self.nested_tocs = tag
+            .map(|t| t.tables_of_contents().filter(|toc| !toc.top_level).cloned().collect())
+            .unwrap_or_default();
+
+        let listed = |id: &str| top.as_ref().is_none_or(|toc| toc.elements.iter().any(|e| e == id));
+        self.chapters = tag
+            .map(|t| t.chapters().map(|c| ChapterDraft::from_chapter(c, listed(&c.element_id))).collect())
+            .unwrap_or_default();
+        self.chapters.sort_by_key(|c| parse_ms(&c.start).unwrap_or(0));
+        self.mark_saved();
+    }
+
+    pub fn reset(&mut self) {
+        self.loaded_for = None;
+    }
+
+    /// Whether a file's chapters are in the editor.
+    pub fn is_loaded(&self) -> bool {
+        self.loaded_for.is_some()
+    }
+
+    /// Whether the editor differs from what's in the file.
+    pub fn is_dirty(&self) -> bool {
+        let (chapters, toc_title, toc_ordered) = &self.saved;
+        self.is_loaded()
+            && (&self.chapters != chapters || &self.toc_title != toc_title || self.toc_ordered != *toc_ordered)
+    }
+
+    fn mark_saved(&mut self) {
+        self.saved = (self.chapters.clone(), self.toc_title.clone(), self.toc_ordered);
+    }
+
+    fn next_element_id(&self) -> String {
+        (0..)
+            .map(|n| format!("chp{}", n))
+            .find(|id| !self.chapters.iter().any(|c| &c.element_id == id))
+            .unwrap_or_default()
+    }
+
+    /// Replaces the chapter list. Each chapter ends where the next one starts,
+    /// the last one at the end of the file.
+    fn set_marks(&mut self, mut marks: Vec<ChapterMark>) {
+        marks.sort_by_key(|m| m.start_ms);
+        self.chapters.clear();
+        for (i, mark) in marks.iter().enumerate() {
+            let end = marks
+                .get(i + 1)
+                .map(|next| next.start_ms)
+                .unwrap_or(self.duration_ms.max(mark.start_ms));
+            let element_id = self.next_element_id();
+            self.chapters.push(ChapterDraft {
+                element_id,
+                start: format_hms(mark.start_ms),
+                end: format_hms(end),
+                title: mark.title.clone(),
+                url: mark.url.clone(),
+                image: None,
+                in_toc: true,
+                other_frames: Vec::new(),
+            });
+        }
+    }
+
+    fn marks(&self) -> Vec<ChapterMark> {
+        self.chapters.iter().filter_map(|c| c.mark()).collect()
+    }
+
+    /// An id for a new top-level table of contents that no chapter or nested table uses.
+    fn new_toc_id(&self) -> String {
+        let taken = |id: &str| {
+            self.chapters.iter().any(|c| c.element_id == id) || self.nested_tocs.iter().any(|t| t.element_id == id)
+        };
+        (0..)
+            .map(|n| if n == 0 { TOC_ID.to_string() } else { format!("{}{}", TOC_ID, n) })
+            .find(|id| !taken(id))
+            .unwrap_or_default()
+    }
+
+    /// The top-level table of contents followed by the nested ones. None without
+    /// chapters, since nested tables would have nothing to point to.
+    fn tables_of_contents(&self) -> Vec<TableOfContents> {
+        if self.chapters.is_empty() {
+            return Vec::new();
+        }
+        let mut frames = Vec::new();
+        if !self.toc_title.is_empty() {
+            frames.push(Frame::text("TIT2", self.toc_title.clone()));
+        }
+        let mut elements: Vec<String> =
+            self.chapters.iter().filter(|c| c.in_toc).map(|c| c.element_id.clone()).collect();
+        elements.extend(self.nested_tocs.iter().map(|toc| toc.element_id.clone()));
+        let top = TableOfContents {
+            element_id: self.toc_id.clone().unwrap_or_else(|| self.new_toc_id()),
+            top_level: true,
+            ordered: self.toc_ordered,
+            elements,
+            frames,
+        };
+        std::iter::once(top).chain(self.nested_tocs.iter().cloned()).collect()
+    }
+}
+
+impl MyApp {
+    fn save_chapters(&mut self, path: &str) -> errors::Result<()> {
+        let editor = &mut self.chapters;
+        editor.chapters.sort_by_key(|c| parse_ms(&c.start).unwrap_or(0));
+
+        let mut tag = id3::no_tag_ok(Tag::read_from_path(path))?.unwrap_or_default();
+        tag.remove_all_chapters();
+        tag.remove_all_tables_of_contents();
+
+        for draft in &editor.chapters {
+            tag.add_frame(draft.to_chapter()?);
+        }
+
+        let tocs = editor.tables_of_contents();
+        if let Some(top) = tocs.first() {
+            editor.toc_id = Some(top.element_id.clone());
+        }
+        for toc in tocs {
+            tag.add_frame(toc);
+        }
+
+        self.write_tag(path, tag)?;
+        self.chapters.mark_saved();
+        Ok(())
+    }
+
+    fn import_chapters(&mut self, format: ChapterFormat) -> errors::Result<()> {
+        let Some(file) = rfd::FileDialog::new()
+            .add_filter(format.label(), &[format.extension()])
+            .pick_file()
+        else {
+            return Ok(());
+        };
+        let input = std::fs::read_to_string(file)?;
+        let marks = match format {
+            ChapterFormat::Cue => from_cue(&cue::parse(&input)),
+            ChapterFormat::Podlove => parse_podlove_json(&input)?,
+            ChapterFormat::Text => parse_text(&input),
+        };
+        if marks.is_empty() {
+            return Err(t!("chapters-none-found").into());
+        }
+        self.chapters.set_marks(marks);
+        Ok(())
+    }
+
+    fn export_chapters(&mut self, path: &str, format: ChapterFormat) -> errors::Result<()> {
+        let marks = self.chapters.marks();
+        let file_name = MyApp::get_file_name(&path.to_string()).to_string();
+        let output = match format {
+            ChapterFormat::Cue => {
+                let title = self.get_album(path)?.or(self.get_title(path)?);
+                let performer = self.get_artist(path)?;
+                to_cue(&marks, &file_name, title, performer)
+            }
+            ChapterFormat::Podlove => to_podlove_json(&marks),
+            ChapterFormat::Text => to_text(&marks),
+        };
+
+        let stem = std::path::Path::new(path)
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("chapters");
+        if let Some(save_path) = rfd::FileDialog::new()
+            .add_filter(format.label(), &[format.extension()])
+            .set_file_name(format!("{}.{}", stem, format.extension()))
+            .save_file()
+        {
+            std::fs::write(save_path, output)?;
+        }
+        Ok(())
+    }
+
+    fn pick_chapter_image(&mut self, index: usize) -> errors::Result<()> {
+        if let Some(file) = rfd::FileDialog::new()
+            .add_filter(t!("filter-image"), &["png", "jpg", "jpeg"])
+            .pick_file()
+        {
+            let mime_type = match file.extension().and_then(|e| e.to_str()) {
+                Some("png") => "image/png",
+                _ => "image/jpeg",
+            };
+            let data = std::fs::read(&file)?;
+            if let Some(chapter) = self.chapters.chapters.get_mut(index) {
+                chapter.image = Some(Picture {
+                    mime_type: mime_type.to_string(),
+                    picture_type: PictureType::Other,
+                    description: String::new(),
+                    data,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn chapters_tab(&mut self, ui: &mut egui::Ui) {
+        let Some(path) = self.selected_file.clone() else {
+            return;
+        };
+        if self.chapters.loaded_for.as_deref() != Some(path.as_str()) {
+            let tag = self.get_tag(&path).ok().cloned();
+            self.chapters.load(&path, tag.as_ref());
+        }
+
+        let mut import = None;
+        let mut export = None;
+        ui.horizontal(|ui| {
+            ui.heading(t!("chapters-heading"));
+            if ui.button(t!("chapters-add")).clicked() {
+                let last_end = self.chapters.chapters.last().and_then(|c| parse_ms(&c.end)).unwrap_or(0);
+                let element_id = self.chapters.next_element_id();
+                let end = self.chapters.duration_ms.max(last_end);
+                self.chapters.chapters.push(ChapterDraft {
+                    element_id,
+                    start: format_hms(last_end),
+                    end: format_hms(end),
+                    title: String::new(),
+                    url: String::new(),
+                    image: None,
+                    in_toc: true,
+                    other_frames: Vec::new(),
+                });
+            }
+            ui.menu_button(t!("chapters-import"), |ui| {
+                for format in ChapterFormat::ALL {
+                    if ui.button(format.label()).clicked() {
+                        import = Some(format);
+                        ui.close_menu();
+                    }
+                }
+            });
+            ui.menu_button(t!("chapters-export"), |ui| {
+                for format in ChapterFormat::ALL {
+                    if ui.button(format.label()).clicked() {
+                        export = Some(format);
+                        ui.close_menu();
+                    }
+                }
+            });
+        });
+
+        let mut to_remove = None;
+        let mut pick_image = None;
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            egui::Grid::new("chapters").num_columns(6).striped(true).show(ui, |ui| {
+                ui.label(t!("chapters-start"));
+                ui.label(t!("chapters-end"));
+                ui.label(t!("chapters-title"));
+                ui.label(t!("chapters-url"));
+                ui.label(t!("chapters-image"));
+                ui.label(t!("chapters-toc"));
+                ui.end_row();
+
+                for (i, chapter) in self.chapters.chapters.iter_mut().enumerate() {
+                    for time in [&mut chapter.start, &mut chapter.end] {
+                        let valid = parse_ms(time).is_some();
+                        ui.add(
+                            egui::TextEdit::singleline(time)
+                                .desired_width(90.0)
+                                .text_color_opt((!valid).then_some(self.theme.color(Role::Error))),
+                        );
+                    }
+                    ui.add(egui::TextEdit::singleline(&mut chapter.title).desired_width(150.0));
+                    ui.add(egui::TextEdit::singleline(&mut chapter.url).desired_width(100.0));
+                    ui.horizontal(|ui| {
+                        if let Some(image) = &chapter.image {
+                            ui.label(t!("chapters-image-size", kb = image.data.len() / 1024));
+                            if ui.button("x").clicked() {
+                                chapter.image = None;
+                            }
+                        } else if ui.button(t!("chapters-set-image")).clicked() {
+                            pick_image = Some(i);
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.checkbox(&mut chapter.in_toc, "");
+                        if ui.button(t!("chapters-remove")).clicked() {
+                            to_remove = Some(i);
+                        }
+                    });
+                    ui.end_row();
+                }
+            });
+
+            ui.add_space(10.0);
+            ui.heading(t!("chapters-toc-heading"));
+            ui.horizontal(|ui| {
+                ui.label(t!("chapters-toc-title"));
+                ui.text_edit_singleline(&mut self.chapters.toc_title);
+                ui.checkbox(&mut self.chapters.toc_ordered, t!("chapters-toc-ordered"));
+            });
+            if !self.chapters.nested_tocs.is_empty() {
+                ui.label(t!("chapters-nested-tocs", count = self.chapters.nested_tocs.len()));
+            }
+
+            ui.add_space(10.0);
+            ui.horizontal(|ui| {
+                if ui.button(t!("chapters-save")).clicked() {
+                    if let Err(e) = self.save_chapters(&path) {
+                        self.report_error(Some(&path), t!("chapters-save-failed", error = e.to_string()));
+                    } else {
+                        self.alert_message.clear();
+                    }
+                }
+                if ui.button(t!("form-revert")).clicked() {
+                    self.chapters.reset();
+                }
+            });
+        });
+
+        if let Some(i) = to_remove {
+            self.chapters.chapters.remove(i);
+        }
+        if let Some(i) = pick_image
+            && let Err(e) = self.pick_chapter_image(i)
+        {
+            self.report_error(Some(&path), t!("chapters-image-failed", error = e.to_string()));
+        }
+        if let Some(format) = import
+            && let Err(e) = self.import_chapters(format)
+        {
+            self.report_error(Some(&path), t!("chapters-import-failed", error = e.to_string()));
+        }
+        if let Some(format) = export
+            && let Err(e) = self.export_chapters(&path, format)
+        {
+            self.report_error(Some(&path), t!("chapters-export-failed", error = e.to_string()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chapter(element_id: &str) -> ChapterDraft {
+        ChapterDraft {
+            element_id: element_id.to_string(),
+            start: "00:00:00.000".to_string(),
+            end: "00:00:01.000".to_string(),
+            title: String::new(),
+            url: String::new(),
+            image: None,
+            in_toc: true,
+            other_frames: Vec::new(),
+        }
+    }
+
+    fn nested(element_id: &str) -> TableOfContents {
+        TableOfContents {
+            element_id: element_id.to_string(),
+            top_level: false,
+            ordered: true,
+            elements: Vec::new(),
+            frames: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn no_tables_without_chapters() {
+        let editor = ChapterEditor { nested_tocs: vec![nested("part1")], ..Default::default() };
+        assert!(editor.tables_of_contents().is_empty());
+    }
+
+    #[test]
+    fn loaded_toc_id_is_kept() {
+        let editor = ChapterEditor {
+            chapters: vec![chapter("chp0")],
+            toc_id: Some("contents".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(editor.tables_of_contents()[0].element_id, "contents");
+    }
+
+    #[test]
+    fn new_toc_id_is_unique() {
+        let editor = ChapterEditor {
+            chapters: vec![chapter("toc"), chapter("chp1")],
+            nested_tocs: vec![nested("toc1")],
+            ..Default::default()
+        };
+        let tocs = editor.tables_of_contents();
+        assert_eq!(tocs[0].element_id, "toc2");
+        assert_eq!(tocs[0].elements, ["toc", "chp1", "toc1"]);
+        assert_eq!(tocs[1].element_id, "toc1");
+    }
+
+    fn mark(start_ms: u32, title: &str) -> ChapterMark {
+        ChapterMark { start_ms, title: title.to_string(), url: String::new() }
+    }
+
+    #[test]
+    fn text_lines() {
+        let marks = parse_text("00:00:00 Intro\n\n05:30.5 Second part\nnot a chapter\n101:00:00 Very late\n");
+        assert_eq!(marks, [mark(0, "Intro"), mark(330_500, "Second part"), mark(363_600_000, "Very late")]);
+        assert_eq!(to_text(&marks), "00:00:00 Intro\n00:05:30 Second part\n101:00:00 Very late\n");
+    }
+
+    #[test]
+    fn podlove_and_podcast_json() {
+        let podlove = r#"[
+            {"start": "00:01:02.500", "title": "One", "href": "https://example.com"},
+            {"start": 90, "title": "Two"}
+        ]"#;
+        let marks = parse_podlove_json(podlove).unwrap();
+        assert_eq!(marks[0], ChapterMark { url: "https://example.com".to_string(), ..mark(62_500, "One") });
+        assert_eq!(marks[1], mark(90_000, "Two"));
+        assert_eq!(parse_podlove_json(&to_podlove_json(&marks)).unwrap(), marks);
+
+        let podcast = r#"{"version": "1.2.0", "chapters": [{"startTime": 1.25, "title": "Start", "url": "u"}]}"#;
+        let marks = parse_podlove_json(podcast).unwrap();
+        assert_eq!(marks, [ChapterMark { url: "u".to_string(), ..mark(1_250, "Start") }]);
+
+        assert!(parse_podlove_json(r#"[{"title": "No start"}]"#).is_err());
+        assert!(parse_podlove_json(r#""chapters""#).is_err());
+    }
+
+    #[test]
+    fn cue_export_reads_back() {
+        let marks = [mark(0, "Intro"), mark(75_000, "Main")];
+        let sheet = to_cue(&marks, "show.mp3", Some("Episode".to_string()), None);
+        assert!(sheet.contains("TITLE \"Episode\"\nFILE \"show.mp3\" MP3\n"));
+        assert!(sheet.contains("  TRACK 02 AUDIO\n    TITLE \"Main\"\n    INDEX 01 01:15:00\n"));
+        assert_eq!(from_cue(&cue::parse(&sheet)), marks);
+    }
+}
@@ -0,0 +1,440 @@
+use std::collections::HashMap;
+
+use eframe::egui::{self, Key, KeyboardShortcut, Modifiers};
+use serde::{Deserialize, Serialize};
+
+use crate::audio;
+use crate::errors;
+use crate::i18n::t;
+use crate::{MyApp, Tab, form};
+
+/// Everything that can be bound to a shortcut or run from the command palette.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Command {
+    NextFile,
+    PreviousFile,
+    Save,
+    Revert,
+    Undo,
+    FocusField,
+    AddFile,
+    AddFolder,
+    ShowTags,
+    ShowLyrics,
+    ShowChapters,
+    PlayPause,
+    ExportTags,
+    ImportTags,
+    ImportCue,
+    MusicBrainz,
+    FindCover,
+    Identify,
+    FindDuplicates,
+    CheckLibrary,
+    RepairEncoding,
+    ConsolidateGenres,
+    Id3Versions,
+    TagBlocks,
+    SaveOptions,
+    Appearance,
+    Shortcuts,
+    Palette,
+    Log,
+}
+
+impl Command {
+    pub const ALL: [Command; 29] = [
+        Command::NextFile,
+        Command::PreviousFile,
+        Command::Save,
+        Command::Revert,
+        Command::Undo,
+        Command::FocusField,
+        Command::AddFile,
+        Command::AddFolder,
+        Command::ShowTags,
+        Command::ShowLyrics,
+        Command::ShowChapters,
+        Command::PlayPause,
+        Command::ExportTags,
+        Command::ImportTags,
+        Command::ImportCue,
+        Command::MusicBrainz,
+        Command::FindCover,
+        Command::Identify,
+        Command::FindDuplicates,
+        Command::CheckLibrary,
+        Command::RepairEncoding,
+        Command::ConsolidateGenres,
+        Command::Id3Versions,
+        Command::TagBlocks,
+        Command::SaveOptions,
+        Command::Appearance,
+        Command::Shortcuts,
+        Command::Palette,
+        Command::Log,
+    ];
+
+    pub fn name(self) -> String {
+        t!(match self {
+            Command::NextFile => "command-next-file",
+            Command::PreviousFile => "command-previous-file",
+            Command::Save => "command-save",
+            Command::Revert => "command-revert",
+            Command::Undo => "command-undo",
+            Command::FocusField => "command-focus-field",
+            Command::AddFile => "command-add-file",
+            Command::AddFolder => "command-add-folder",
+            Command::ShowTags => "command-show-tags",
+            Command::ShowLyrics => "command-show-lyrics",
+            Command::ShowChapters => "command-show-chapters",
+            Command::PlayPause => "command-play-pause",
+            Command::ExportTags => "command-export-tags",
+            Command::ImportTags => "command-import-tags",
+            Command::ImportCue => "command-import-cue",
+            Command::MusicBrainz => "command-music-brainz",
+            Command::FindCover => "command-find-cover",
+            Command::Identify => "command-identify",
+            Command::FindDuplicates => "command-find-duplicates",
+            Command::CheckLibrary => "command-check-library",
+            Command::RepairEncoding => "command-repair-encoding",
+            Command::ConsolidateGenres => "command-consolidate-genres",
+            Command::Id3Versions => "command-id3-versions",
+            Command::TagBlocks => "command-tag-blocks",
+            Command::SaveOptions => "command-save-options",
+            Command::Appearance => "command-appearance",
+            Command::Shortcuts => "command-shortcuts",
+            Command::Palette => "command-palette",
+            Command::Log => "command-log",
+        })
+    }
+
+    fn default_shortcut(self) -> Option<KeyboardShortcut> {
+        let (modifiers, key) = match self {
+            Command::NextFile => (Modifiers::COMMAND, Key::ArrowDown),
+            Command::PreviousFile => (Modifiers::COMMAND, Key::ArrowUp),
+            Command::Save => (Modifiers::COMMAND, Key::S),
+            Command::Revert => (Modifiers::COMMAND, Key::R),
+            Command::Undo => (Modifiers::COMMAND, Key::Z),
+            Command::FocusField => (Modifiers::COMMAND, Key::E),
+            Command::AddFile => (Modifiers::COMMAND, Key::O),
+            Command::AddFolder => (Modifiers::COMMAND | Modifiers::SHIFT, Key::O),
+            Command::ShowTags => (Modifiers::COMMAND, Key::Num1),
+            Command::ShowLyrics => (Modifiers::COMMAND, Key::Num2),
+            Command::ShowChapters => (Modifiers::COMMAND, Key::Num3),
+            Command::Palette => (Modifiers::COMMAND | Modifiers::SHIFT, Key::P),
+            _ => return None,
+        };
+        Some(KeyboardShortcut::new(modifiers, key))
+    }
+
+    /// Whether the shortcut fires while a text box has focus; Ctrl+Z and friends belong to the text box.
+    fn works_while_typing(self) -> bool {
+        matches!(self, Command::NextFile | Command::PreviousFile | Command::Save | Command::Palette)
+    }
+}
+
+/// How well `text` matches the letters of `query` in order, or `None` if it doesn't.
+/// Runs of consecutive letters and matches at the start of words score higher.
+fn fuzzy_score(query: &str, text: &str) -> Option<i32> {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut next = 0;
+    let mut last_match: Option<usize> = None;
+    for wanted in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let found = (next..text.len()).find(|&i| text[i] == wanted)?;
+        score += 1;
+        if last_match.is_some_and(|l| l + 1 == found) {
+            score += 5;
+        }
+        if found == 0 || !text[found - 1].is_alphanumeric() {
+            score += 3;
+        }
+        last_match = Some(found);
+        next = found + 1;
+    }
+    Some(score)
+}
+
+#[derive(Default)]
+pub struct CommandState {
+    /// Shortcuts the user changed; `None` removes the default.
+    pub overrides: HashMap<Command, Option<KeyboardShortcut>>,
+    palette_open: bool,
+    query: String,
+    highlighted: usize,
+    shortcuts_open: bool,
+    /// The command whose new shortcut is the next key pressed.
+    recording: Option<Command>,
+}
+
+impl CommandState {
+    pub fn shortcut(&self, command: Command) -> Option<KeyboardShortcut> {
+        self.overrides.get(&command).copied().unwrap_or_else(|| command.default_shortcut())
+    }
+}
+
+impl MyApp {
+    /// Runs the commands whose shortcuts were pressed this frame.
+    pub(crate) fn handle_shortcuts(&mut self, ctx: &egui::Context) {
+        if self.commands.recording.is_some() {
+            return;
+        }
+        let typing = ctx.wants_keyboard_input();
+        let mut bindings: Vec<(KeyboardShortcut, Command)> = Command::ALL
+            .into_iter()
+            .filter(|c| !typing || c.works_while_typing())
+            .filter_map(|c| Some((self.commands.shortcut(c)?, c)))
+            .collect();
+        // Ctrl+O would also match Ctrl+Shift+O, so the longer shortcut goes first
+        bindings.sort_by_key(|(s, _)| {
+            let m = s.modifiers;
+            std::cmp::Reverse(m.alt as u8 + m.shift as u8 + (m.ctrl || m.command) as u8)
+        });
+        for (shortcut, command) in bindings {
+            if ctx.input_mut(|i| i.consume_shortcut(&shortcut)) {
+                self.run_command(ctx, command);
+            }
+        }
+    }
+
+    pub(crate) fn run_command(&mut self, ctx: &egui::Context, command: Command) {
+        match command {
+            Command::NextFile | Command::PreviousFile => {
+                if self.dropped_files.is_empty() {
+                    return;
+                }
+                let current = self.selected_file.as_ref().and_then(|s| self.dropped_files.iter().position(|f| f == s));
+                let index = match (current, command) {
+                    (None, _) => 0,
+                    (Some(i), Command::NextFile) => (i + 1).min(self.dropped_files.len() - 1),
+                    (Some(i), _) => i.saturating_sub(1),
+                };
+                if current != Some(index) {
+                    self.select_file(ctx, self.dropped_files[index].clone());
+                }
+            }
+            Command::Save => self.save_form(),
+            Command::Revert => self.form.reset(),
+            Command::Undo => {
+                if let Err(e) = self.undo_last_write(ctx) {
+                    self.report_error(None, t!("undo-failed", error = e.to_string()));
+                }
+            }
+            Command::FocusField => {
+                self.tab = Tab::Tags;
+                ctx.memory_mut(|m| m.request_focus(form::field_id(form::MAIN_FIELDS[0])));
+            }
+            Command::AddFile => {
+                if let Some(paths) = rfd::FileDialog::new().add_filter(t!("filter-audio"), &["mp3"]).pick_files() {
+                    for path in paths {
+                        self.add_file(path.display().to_string());
+                    }
+                }
+            }
+            Command::AddFolder => {
+                if let Some(dir) = rfd::FileDialog::new().pick_folder()
+                    && let Err(e) = self.add_folder(&dir.display().to_string())
+                {
+                    self.report_error(None, t!("recent-folder-failed", error = e.to_string()));
+                }
+            }
+            Command::ShowTags => self.tab = Tab::Tags,
+            Command::ShowLyrics => self.tab = Tab::Lyrics,
+            Command::ShowChapters => self.tab = Tab::Chapters,
+            Command::PlayPause => {
+                if let Some(path) = self.selected_file.clone() {
+                    self.toggle_playback(&path);
+                }
+            }
+            Command::ExportTags => self.spreadsheet.open_export(),
+            Command::ImportTags => self.spreadsheet.open_import(),
+            Command::ImportCue => self.cue_import.open(),
+            Command::MusicBrainz => {
+                if self.selected_file.is_some() {
+                    self.open_musicbrainz();
+                }
+            }
+            Command::FindCover => {
+                if self.selected_file.is_some() {
+                    self.open_cover_search();
+                }
+            }
+            Command::Identify => self.open_identify(),
+            Command::FindDuplicates => self.open_duplicates(),
+            Command::CheckLibrary => self.open_linter(),
+            Command::RepairEncoding => self.open_mojibake(),
+            Command::ConsolidateGenres => self.open_genre_consolidation(),
+            Command::Id3Versions => self.open_version_converter(),
+            Command::TagBlocks => self.open_tag_blocks(),
+            Command::SaveOptions => self.open_write_settings(),
+            Command::Appearance => self.open_theme_settings(),
+            Command::Shortcuts => self.commands.shortcuts_open = true,
+            Command::Palette => {
+                let state = &mut self.commands;
+                state.query.clear();
+                state.highlighted = 0;
+                state.palette_open = true;
+            }
+            Command::Log => self.open_log(),
+        }
+    }
+
+    /// Puts back the bytes around the audio exactly as they were before the last write.
+    /// The write stays undoable if putting them back fails.
+    fn undo_last_write(&mut self, ctx: &egui::Context) -> errors::Result<()> {
+        let (path, (head, tail)) = self.undo.pop().ok_or_else(|| t!("undo-nothing"))?;
+        let restored = self.replace_file(&path, |temp| {
+            let bytes = std::fs::read(temp)?;
+            let audio = &bytes[audio::stream_range(&bytes)];
+            std::fs::write(temp, [head.as_slice(), audio, tail.as_slice()].concat())?;
+            Ok(())
+        });
+        if let Err(e) = restored {
+            self.undo.push((path, (head, tail)));
+            return Err(e);
+        }
+        // writing it back recorded the undone tags; that's not something to undo to
+        self.undo.pop();
+        self.form.reset();
+        self.album_art_cache.remove(&path);
+        if self.selected_file.as_deref() == Some(&path) {
+            self.reload_selected(ctx);
+        }
+        self.lyrics.reset();
+        self.chapters.reset();
+        Ok(())
+    }
+
+    pub(crate) fn command_palette_window(&mut self, ctx: &egui::Context) {
+        if !self.commands.palette_open {
+            return;
+        }
+        let mut run = None;
+        let mut close = false;
+        egui::Window::new(t!("palette-title"))
+            .title_bar(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_TOP, [0.0, 40.0])
+            .show(ctx, |ui| {
+                let state = &mut self.commands;
+                let response = ui.add(
+                    egui::TextEdit::singleline(&mut state.query)
+                        .hint_text(t!("palette-hint"))
+                        .desired_width(400.0),
+                );
+                response.request_focus();
+                if response.changed() {
+                    state.highlighted = 0;
+                }
+
+                let mut matches: Vec<(i32, Command)> = Command::ALL
+                    .into_iter()
+                    .filter(|c| *c != Command::Palette)
+                    .filter_map(|c| Some((fuzzy_score(&state.query, &c.name())?, c)))
+                    .collect();
+                matches.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+
+                let (up, down, enter, escape) = ui.input(|i| {
+                    (
+                        i.key_pressed(Key::ArrowUp),
+                        i.key_pressed(Key::ArrowDown),
+                        i.key_pressed(Key::Enter),
+                        i.key_pressed(Key::Escape),
+                    )
+                });
+                if down {
+                    state.highlighted += 1;
+                }
+                if up {
+                    state.highlighted = state.highlighted.saturating_sub(1);
+                }
+                state.highlighted = state.highlighted.min(matches.len().saturating_sub(1));
+
+                ui.separator();
+                egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                    for (index, (_, command)) in matches.iter().enumerate() {
+                        ui.horizontal(|ui| {
+                            let label = ui.selectable_label(index == state.highlighted, command.name());
+                            if index == state.highlighted && (up || down) {
+                                label.scroll_to_me(None);
+                            }
+                            if label.clicked() {
+                                run = Some(*command);
+                            }
+                            if let Some(shortcut) = state.shortcut(*command) {
+                                ui.weak(ui.ctx().format_shortcut(&shortcut));
+                            }
+                        });
+                    }
+                });
+                if enter {
+                    run = matches.get(state.highlighted).map(|(_, c)| *c);
+                }
+                close = escape;
+            });
+
+        if run.is_some() || close {
+            self.commands.palette_open = false;
+        }
+        if let Some(command) = run {
+            self.run_command(ctx, command);
+        }
+    }
+
+    pub(crate) fn shortcuts_window(&mut self, ctx: &egui::Context) {
+        let state = &mut self.commands;
+        if let Some(command) = state.recording {
+            let pressed = ctx.input(|i| {
+                i.events.iter().find_map(|e| match e {
+                    egui::Event::Key { key, pressed: true, modifiers, .. } => Some((*key, *modifiers)),
+                    _ => None,
+                })
+            });
+            if let Some((key, modifiers)) = pressed {
+                if !(key == Key::Escape && modifiers.is_none()) {
+                    let shortcut = KeyboardShortcut::new(modifiers, key);
+                    // a shortcut does one thing
+                    for other in Command::ALL {
+                        if other != command && state.shortcut(other) == Some(shortcut) {
+                            state.overrides.insert(other, None);
+                        }
+                    }
+                    state.overrides.insert(command, Some(shortcut));
+                }
+                state.recording = None;
+            }
+        }
+
+        let mut open = state.shortcuts_open;
+        egui::Window::new(t!("shortcuts-title")).open(&mut open).show(ctx, |ui| {
+            egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                egui::Grid::new("shortcuts").striped(true).show(ui, |ui| {
+                    for command in Command::ALL {
+                        ui.label(command.name());
+                        if state.recording == Some(command) {
+                            ui.label(t!("shortcuts-recording"));
+                        } else {
+                            let shortcut = state.shortcut(command).map(|s| ctx.format_shortcut(&s));
+                            ui.label(shortcut.unwrap_or_else(|| "-".to_string()));
+                        }
+                        if ui.button(t!("shortcuts-change")).clicked() {
+                            state.recording = Some(command);
+                        }
+                        if ui.button(t!("shortcuts-clear")).clicked() {
+                            state.overrides.insert(command, None);
+                        }
+                        ui.end_row();
+                    }
+                });
+            });
+            if ui.button(t!("shortcuts-reset")).clicked() {
+                state.overrides.clear();
+            }
+        });
+        state.shortcuts_open &= open;
+        if !state.shortcuts_open {
+            state.recording = None;
+        }
+    }
+}
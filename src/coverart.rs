@@ -0,0 +1,336 @@
+use std::collections::HashMap;
+use std::io::{Cursor, Read};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::time::Duration;
+
+use eframe::egui;
+use id3::{Tag, TagLike};
+use serde::Deserialize;
+
+use crate::MyApp;
+use crate::errors;
+use crate::i18n::t;
+use crate::musicbrainz::{self, USER_AGENT};
+
+pub const DEFAULT_ENDPOINT: &str = "https://coverartarchive.org";
+
+/// Releases from a MusicBrainz search whose art gets listed.
+const MAX_RELEASES: usize = 5;
+/// Enough of a jpeg or png to find its dimensions without fetching the whole image.
+const HEADER_BYTES: u64 = 64 * 1024;
+
+#[derive(Deserialize)]
+struct ImageList {
+    #[serde(default)]
+    images: Vec<ImageEntry>,
+}
+
+#[derive(Deserialize)]
+struct ImageEntry {
+    image: String,
+    #[serde(default)]
+    thumbnails: HashMap<String, String>,
+    #[serde(default)]
+    front: bool,
+    #[serde(default)]
+    types: Vec<String>,
+}
+
+struct Found {
+    release: String,
+    types: String,
+    url: String,
+    size: Option<(u32, u32)>,
+    /// `None` if the preview couldn't be fetched.
+    thumbnail: Option<Vec<u8>>,
+}
+
+enum Message {
+    Found(Found),
+    Finished(errors::Result<()>),
+    /// The image, and the file it was chosen for.
+    Downloaded(String, errors::Result<Vec<u8>>),
+}
+
+struct Candidate {
+    release: String,
+    types: String,
+    url: String,
+    size: Option<(u32, u32)>,
+    thumbnail: Option<egui::TextureHandle>,
+}
+
+fn agent() -> ureq::Agent {
+    ureq::AgentBuilder::new()
+        .user_agent(USER_AGENT)
+        .timeout(Duration::from_secs(30))
+        .build()
+}
+
+fn read_body(response: ureq::Response, limit: u64) -> errors::Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    response.into_reader().take(limit).read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// Width and height from the first few KB of an image.
+fn probe_size(agent: &ureq::Agent, url: &str) -> Option<(u32, u32)> {
+    let response = agent
+        .get(url)
+        .set("Range", &format!("bytes=0-{}", HEADER_BYTES - 1))
+        .call()
+        .ok()?;
+    let bytes = read_body(response, HEADER_BYTES).ok()?;
+    image::ImageReader::new(Cursor::new(bytes))
+        .with_guessed_format()
+        .ok()?
+        .into_dimensions()
+        .ok()
+}
+
+/// Lists the art of each release, sending candidates as they come in.
+fn list_release_art(
+    endpoint: &str,
+    releases: Vec<(String, String)>,
+    sender: &Sender<Message>,
+) -> errors::Result<()> {
+    let agent = agent();
+    for (id, label) in releases {
+        let list: ImageList = match agent.get(&format!("{}/release/{}", endpoint, id)).call() {
+            Ok(response) => serde_json::from_reader(response.into_reader())?,
+            // no art for this release
+            Err(ureq::Error::Status(404, _)) => continue,
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut images = list.images;
+        images.sort_by_key(|i| !i.front);
+        for entry in images {
+            let thumb_url = ["250", "small", "500", "large"]
+                .iter()
+                .find_map(|k| entry.thumbnails.get(*k))
+                .unwrap_or(&entry.image);
+            // a missing preview shouldn't cost the rest of the results
+            let thumbnail = agent
+                .get(thumb_url)
+                .call()
+                .ok()
+                .and_then(|r| read_body(r, 8 * 1024 * 1024).ok());
+            let found = Found {
+                release: label.clone(),
+                types: entry.types.join(", "),
+                size: probe_size(&agent, &entry.image),
+                url: entry.image,
+                thumbnail,
+            };
+            if sender.send(Message::Found(found)).is_err() {
+                return Ok(());
+            }
+        }
+    }
+    Ok(())
+}
+
+#[derive(Default)]
+pub struct CoverSearch {
+    open: bool,
+    endpoint: String,
+    mbid: String,
+    artist: String,
+    album: String,
+    candidates: Vec<Candidate>,
+    pending: Option<Receiver<Message>>,
+    status: String,
+}
+
+impl MyApp {
+    pub(crate) fn open_cover_search(&mut self) {
+        let tag = self.selected_file.as_ref().and_then(|p| Tag::read_from_path(p).ok());
+        let search = &mut self.cover_search;
+        if search.endpoint.is_empty() {
+            search.endpoint = DEFAULT_ENDPOINT.to_string();
+        }
+        search.mbid = tag
+            .as_ref()
+            .and_then(|t| {
+                t.extended_texts()
+                    .find(|e| e.description == "MusicBrainz Album Id")
+                    .map(|e| e.value.clone())
+            })
+            .unwrap_or_default();
+        search.artist = tag
+            .as_ref()
+            .and_then(|t| t.album_artist().or(t.artist()))
+            .unwrap_or_default()
+            .to_string();
+        search.album = tag.as_ref().and_then(|t| t.album()).unwrap_or_default().to_string();
+        search.candidates.clear();
+        search.status.clear();
+        search.open = true;
+    }
+
+    fn start_cover_search(&mut self) {
+        let search = &mut self.cover_search;
+        let endpoint = search.endpoint.trim_end_matches('/').to_string();
+        let mbid = search.mbid.trim().to_string();
+        let (artist, album) = (search.artist.clone(), search.album.clone());
+        let mb_url = self.musicbrainz.base_url().to_string();
+
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            let result = (|| -> errors::Result<()> {
+                let releases = if mbid.is_empty() {
+                    musicbrainz::Client::new(&mb_url)
+                        .search_releases(&artist, &album, 0)?
+                        .into_iter()
+                        .take(MAX_RELEASES)
+                        .map(|r| (r.id.clone(), r.summary()))
+                        .collect()
+                } else {
+                    vec![(mbid.clone(), mbid)]
+                };
+                list_release_art(&endpoint, releases, &sender)
+            })();
+            let _ = sender.send(Message::Finished(result));
+        });
+
+        let search = &mut self.cover_search;
+        search.candidates.clear();
+        search.pending = Some(receiver);
+        search.status = t!("online-searching");
+    }
+
+    fn download_cover(&mut self, url: String) {
+        let Some(path) = self.selected_file.clone() else {
+            return;
+        };
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            let result = agent()
+                .get(&url)
+                .call()
+                .map_err(errors::Error::from)
+                .and_then(|r| read_body(r, 64 * 1024 * 1024));
+            let _ = sender.send(Message::Downloaded(path, result));
+        });
+        self.cover_search.pending = Some(receiver);
+        self.cover_search.status = t!("online-downloading");
+    }
+
+    fn poll_cover_search(&mut self, ctx: &egui::Context) {
+        let Some(receiver) = self.cover_search.pending.take() else {
+            return;
+        };
+        let mut keep = true;
+        while let Ok(message) = receiver.try_recv() {
+            match message {
+                Message::Found(found) => {
+                    let thumbnail = found.thumbnail.and_then(|bytes| image::load_from_memory(&bytes).ok()).map(|img| {
+                        let img = img.to_rgba8();
+                        let size = [img.width() as usize, img.height() as usize];
+                        ctx.load_texture(
+                            found.url.clone(),
+                            egui::ColorImage::from_rgba_unmultiplied(size, &img.into_raw()),
+                            Default::default(),
+                        )
+                    });
+                    self.cover_search.candidates.push(Candidate {
+                        release: found.release,
+                        types: found.types,
+                        url: found.url,
+                        size: found.size,
+                        thumbnail,
+                    });
+                }
+                Message::Finished(result) => {
+                    keep = false;
+                    self.cover_search.status = match result {
+                        Ok(()) => t!("cover-found", count = self.cover_search.candidates.len()),
+                        Err(e) => t!("online-search-failed", error = e.to_string()),
+                    };
+                }
+                Message::Downloaded(path, result) => {
+                    keep = false;
+                    self.cover_search.status.clear();
+                    match result.and_then(|bytes| self.set_album_art_of(ctx, &path, bytes)) {
+                        Ok(()) => self.cover_search.open = false,
+                        Err(e) => self.report_error(Some(&path), t!("art-set-failed", error = e.to_string())),
+                    }
+                }
+            }
+        }
+        if keep {
+            self.cover_search.pending = Some(receiver);
+            ctx.request_repaint_after(Duration::from_millis(200));
+        }
+    }
+
+    pub(crate) fn cover_search_window(&mut self, ctx: &egui::Context) {
+        self.poll_cover_search(ctx);
+
+        let mut open = self.cover_search.open;
+        let mut search_clicked = false;
+        let mut chosen = None;
+        egui::Window::new(t!("cover-title")).open(&mut open).show(ctx, |ui| {
+            let search = &mut self.cover_search;
+            let busy = search.pending.is_some();
+            egui::Grid::new("cover_search_fields").num_columns(2).show(ui, |ui| {
+                ui.label(t!("online-endpoint"));
+                ui.text_edit_singleline(&mut search.endpoint);
+                ui.end_row();
+                ui.label(t!("cover-mbid"));
+                ui.text_edit_singleline(&mut search.mbid);
+                ui.end_row();
+                ui.label(t!("online-artist"));
+                ui.text_edit_singleline(&mut search.artist);
+                ui.end_row();
+                ui.label(t!("online-album"));
+                ui.text_edit_singleline(&mut search.album);
+                ui.end_row();
+            });
+            ui.horizontal(|ui| {
+                search_clicked = ui.add_enabled(!busy, egui::Button::new(t!("online-search"))).clicked();
+                ui.label(&search.status);
+            });
+
+            ui.separator();
+            egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                ui.horizontal_wrapped(|ui| {
+                    for candidate in &search.candidates {
+                        ui.vertical(|ui| {
+                            ui.set_width(130.0);
+                            match &candidate.thumbnail {
+                                Some(texture) => {
+                                    ui.image((texture.id(), egui::Vec2::splat(120.0)));
+                                }
+                                None => {
+                                    ui.label(t!("cover-no-preview"));
+                                }
+                            }
+                            let size = candidate
+                                .size
+                                .map(|(w, h)| format!("{}x{}", w, h))
+                                .unwrap_or_else(|| t!("cover-unknown-size"));
+                            ui.label(size);
+                            if !candidate.types.is_empty() {
+                                ui.label(&candidate.types);
+                            }
+                            ui.label(&candidate.release).on_hover_text(&candidate.url);
+                            if ui.add_enabled(!busy, egui::Button::new(t!("cover-use"))).clicked() {
+                                chosen = Some(candidate.url.clone());
+                            }
+                        });
+                    }
+                });
+            });
+        });
+        self.cover_search.open &= open;
+
+        if search_clicked {
+            self.start_cover_search();
+        }
+        if let Some(url) = chosen {
+            self.download_cover(url);
+        }
+    }
+}
@@ -0,0 +1,486 @@
+use std::path::Path;
+
+use eframe::egui;
+use id3::Tag;
+
+use crate::MyApp;
+use crate::errorlog::FileErrors;
+use crate::errors;
+use crate::fields::Field;
+use crate::i18n::t;
+
+/// A parsed `.cue` sheet. Only the commands the editor cares about are kept.
+#[derive(Clone, Debug, Default)]
+pub struct CueSheet {
+    pub performer: Option<String>,
+    pub title: Option<String>,
+    pub date: Option<String>,
+    pub genre: Option<String>,
+    pub file: Option<String>,
+    pub tracks: Vec<CueTrack>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct CueTrack {
+    pub number: u32,
+    pub title: Option<String>,
+    pub performer: Option<String>,
+    /// The `FILE` this track sits in, for sheets that list one file per track.
+    pub file: Option<String>,
+    /// `INDEX 01`, where the track actually starts.
+    pub start_ms: Option<u32>,
+}
+
+/// CUE frames are 1/75 of a second.
+const FRAMES_PER_SECOND: u32 = 75;
+
+/// Parses `mm:ss:ff`.
+pub fn parse_index_time(s: &str) -> Option<u32> {
+    let mut parts = s.trim().split(':');
+    let minutes: u32 = parts.next()?.parse().ok()?;
+    let seconds: u32 = parts.next()?.parse().ok()?;
+    let frames: u32 = parts.next()?.parse().ok()?;
+    if parts.next().is_some() {
+        return None;
+    }
+    let seconds = minutes.checked_mul(60)?.checked_add(seconds)?;
+    seconds.checked_mul(1000)?.checked_add(frames.checked_mul(1000)? / FRAMES_PER_SECOND)
+}
+
+/// `mm:ss:ff`, rounded to the nearest frame so a parsed time writes back unchanged.
+pub fn format_index_time(ms: u32) -> String {
+    let frames = (ms as u64 * FRAMES_PER_SECOND as u64 + 500) / 1000;
+    let seconds = frames / FRAMES_PER_SECOND as u64;
+    format!("{:02}:{:02}:{:02}", seconds / 60, seconds % 60, frames % FRAMES_PER_SECOND as u64)
+}
+
+/// Splits a command's arguments, honouring double quotes.
+fn arguments(line: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    let mut in_arg = false;
+
+    for c in line.chars() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                in_arg = true;
+            }
+            c if c.is_whitespace() && !quoted => {
+                if in_arg {
+                    args.push(std::mem::take(&mut current));
+                    in_arg = false;
+                }
+            }
+            c => {
+                current.push(c);
+                in_arg = true;
+            }
+        }
+    }
+    if in_arg {
+        args.push(current);
+    }
+    args
+}
+
+/// Everything after the command word(s), unquoted. `skip` is how many words to drop.
+fn rest(args: &[String], skip: usize) -> Option<String> {
+    let value = args.get(skip..)?.join(" ");
+    (!value.is_empty()).then_some(value)
+}
+
+pub fn parse(input: &str) -> CueSheet {
+    let mut sheet = CueSheet::default();
+    let mut current_file: Option<String> = None;
+
+    for line in input.lines() {
+        let args = arguments(line.trim_start_matches('\u{feff}'));
+        let Some(command) = args.first() else {
+            continue;
+        };
+        let in_track = !sheet.tracks.is_empty();
+
+        match command.to_ascii_uppercase().as_str() {
+            "FILE" => {
+                current_file = args.get(1).cloned();
+                if sheet.file.is_none() {
+                    sheet.file = current_file.clone();
+                }
+            }
+            "TRACK" => sheet.tracks.push(CueTrack {
+                number: args.get(1).and_then(|n| n.parse().ok()).unwrap_or(sheet.tracks.len() as u32 + 1),
+                file: current_file.clone(),
+                ..Default::default()
+            }),
+            "TITLE" => {
+                let value = rest(&args, 1);
+                match sheet.tracks.last_mut() {
+                    Some(track) => track.title = value,
+                    None => sheet.title = value,
+                }
+            }
+            "PERFORMER" => {
+                let value = rest(&args, 1);
+                match sheet.tracks.last_mut() {
+                    Some(track) => track.performer = value,
+                    None => sheet.performer = value,
+                }
+            }
+            "INDEX" if in_track => {
+                if args.get(1).map(|n| n.as_str()) == Some("01")
+                    && let Some(track) = sheet.tracks.last_mut()
+                {
+                    track.start_ms = args.get(2).and_then(|t| parse_index_time(t));
+                }
+            }
+            "REM" => match args.get(1).map(|k| k.to_ascii_uppercase()).as_deref() {
+                Some("DATE") => sheet.date = rest(&args, 2),
+                Some("GENRE") => sheet.genre = rest(&args, 2),
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+
+    sheet
+}
+
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('"', "'"))
+}
+
+pub fn write(sheet: &CueSheet) -> String {
+    let mut out = String::new();
+    if let Some(genre) = &sheet.genre {
+        out.push_str(&format!("REM GENRE {}\n", quote(genre)));
+    }
+    if let Some(date) = &sheet.date {
+        out.push_str(&format!("REM DATE {}\n", date));
+    }
+    if let Some(performer) = &sheet.performer {
+        out.push_str(&format!("PERFORMER {}\n", quote(performer)));
+    }
+    if let Some(title) = &sheet.title {
+        out.push_str(&format!("TITLE {}\n", quote(title)));
+    }
+    if let Some(file) = &sheet.file {
+        out.push_str(&format!("FILE {} MP3\n", quote(file)));
+    }
+    for track in &sheet.tracks {
+        out.push_str(&format!("  TRACK {:02} AUDIO\n", track.number));
+        if let Some(title) = &track.title {
+            out.push_str(&format!("    TITLE {}\n", quote(title)));
+        }
+        if let Some(performer) = &track.performer {
+            out.push_str(&format!("    PERFORMER {}\n", quote(performer)));
+        }
+        out.push_str(&format!("    INDEX 01 {}\n", format_index_time(track.start_ms.unwrap_or(0))));
+    }
+    out
+}
+
+fn normalize(s: &str) -> String {
+    s.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+fn stem(path: &str) -> String {
+    Path::new(path)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("")
+        .to_string()
+}
+
+/// Pairs each track with one of `files`, returning the chosen file per track.
+///
+/// By order, the nth track gets the nth file. By filename, a track's own `FILE`
+/// entry wins, then a file named after the track title (with or without a
+/// leading track number), then one that starts with the track number.
+pub fn match_tracks(sheet: &CueSheet, files: &[String], by_filename: bool) -> Vec<Option<String>> {
+    if !by_filename {
+        return (0..sheet.tracks.len()).map(|i| files.get(i).cloned()).collect();
+    }
+
+    let stems: Vec<String> = files.iter().map(|f| normalize(&stem(f))).collect();
+    // "01 - Title" names the same track as "Title"
+    let bare: Vec<String> = files
+        .iter()
+        .map(|f| normalize(stem(f).trim_start_matches(|c: char| c.is_ascii_digit() || !c.is_alphanumeric())))
+        .collect();
+    let mut taken = vec![false; files.len()];
+    let mut matches = Vec::new();
+
+    for track in &sheet.tracks {
+        let own_file = track
+            .file
+            .as_deref()
+            .filter(|f| sheet.tracks.iter().filter(|t| t.file.as_deref() == Some(*f)).count() == 1)
+            .map(|f| normalize(&stem(f)));
+        let title = track.title.as_deref().map(normalize).filter(|t| !t.is_empty());
+        let number = format!("{:02}", track.number);
+
+        let found = (0..files.len())
+            .filter(|i| !taken[*i])
+            .find(|i| own_file.as_ref() == Some(&stems[*i]))
+            .or_else(|| {
+                (0..files.len())
+                    .filter(|i| !taken[*i])
+                    .find(|i| title.as_ref().is_some_and(|t| *t == stems[*i] || *t == bare[*i]))
+            })
+            .or_else(|| {
+                (0..files.len())
+                    .filter(|i| !taken[*i])
+                    .find(|i| stem(&files[*i]).trim_start().starts_with(&number))
+            });
+
+        if let Some(i) = found {
+            taken[i] = true;
+        }
+        matches.push(found.map(|i| files[i].clone()));
+    }
+    matches
+}
+
+/// The tag values a track will get.
+pub fn track_values(sheet: &CueSheet, track: &CueTrack) -> Vec<(Field, String)> {
+    let mut values = Vec::new();
+    if let Some(title) = &track.title {
+        values.push((Field::Title, title.clone()));
+    }
+    if let Some(performer) = track.performer.as_ref().or(sheet.performer.as_ref()) {
+        values.push((Field::Artist, performer.clone()));
+    }
+    if let Some(album) = &sheet.title {
+        values.push((Field::Album, album.clone()));
+    }
+    values.push((Field::Track, format!("{}/{}", track.number, sheet.tracks.len())));
+    if let Some(date) = &sheet.date {
+        values.push((Field::Year, date.clone()));
+    }
+    if let Some(genre) = &sheet.genre {
+        values.push((Field::Genre, genre.clone()));
+    }
+    values
+}
+
+#[derive(Default)]
+pub struct CueImport {
+    open: bool,
+    source: Option<String>,
+    sheet: CueSheet,
+    by_filename: bool,
+    /// The file each track's tags go to; paths, since the song list can be re-sorted meanwhile.
+    matches: Vec<Option<String>>,
+}
+
+impl CueImport {
+    pub fn open(&mut self) {
+        self.open = true;
+    }
+}
+
+impl MyApp {
+    fn load_cue(&mut self) -> errors::Result<()> {
+        let Some(path) = rfd::FileDialog::new().add_filter(t!("filter-cue"), &["cue"]).pick_file() else {
+            return Ok(());
+        };
+        let bytes = std::fs::read(&path)?;
+        // older rippers write the sheet in the system codepage rather than UTF-8
+        let input = match String::from_utf8(bytes) {
+            Ok(text) => text,
+            Err(e) => e.into_bytes().iter().map(|b| *b as char).collect(),
+        };
+
+        let sheet = parse(&input);
+        if sheet.tracks.is_empty() {
+            return Err(t!("cue-no-tracks").into());
+        }
+        let import = &mut self.cue_import;
+        import.source = Some(path.display().to_string());
+        import.by_filename = sheet.tracks.iter().filter_map(|t| t.file.as_ref()).count() > 1;
+        import.matches = match_tracks(&sheet, &self.dropped_files, import.by_filename);
+        import.sheet = sheet;
+        Ok(())
+    }
+
+    fn apply_cue(&mut self) -> FileErrors {
+        let mut errors = Vec::new();
+        let import = &self.cue_import;
+        let writes: Vec<(String, Vec<(Field, String)>)> = import
+            .sheet
+            .tracks
+            .iter()
+            .zip(&import.matches)
+            // a file taken off the list since it was matched is left alone
+            .filter_map(|(track, m)| Some((m.clone().filter(|p| self.dropped_files.contains(p))?, track_values(&import.sheet, track))))
+            .collect();
+
+        for (path, values) in writes {
+            let result = (|| -> errors::Result<()> {
+                let mut tag = id3::no_tag_ok(Tag::read_from_path(&path))?.unwrap_or_default();
+                for (field, value) in &values {
+                    field.set(&mut tag, value)?;
+                }
+                self.write_tag(&path, tag)
+            })();
+            if let Err(e) = result {
+                errors.push((path.clone(), e.to_string()));
+            }
+        }
+        errors
+    }
+
+    pub(crate) fn cue_import_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.cue_import.open;
+        egui::Window::new(t!("cue-title")).open(&mut open).show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                if ui.button(t!("cue-open")).clicked()
+                    && let Err(e) = self.load_cue()
+                {
+                    self.report_error(None, t!("cue-read-failed", error = e.to_string()));
+                }
+                if let Some(source) = &self.cue_import.source {
+                    ui.label(MyApp::get_file_name(source).to_string());
+                }
+            });
+            if self.cue_import.source.is_none() {
+                return;
+            }
+
+            let sheet = &self.cue_import.sheet;
+            ui.label(format!(
+                "{} - {} ({}, {})",
+                sheet.performer.clone().unwrap_or_else(|| t!("cue-unknown-artist")),
+                sheet.title.clone().unwrap_or_else(|| t!("cue-unknown-album")),
+                sheet.date.clone().unwrap_or_else(|| t!("mb-no-date")),
+                sheet.genre.clone().unwrap_or_else(|| t!("cue-no-genre")),
+            ));
+
+            let before = self.cue_import.by_filename;
+            ui.horizontal(|ui| {
+                ui.label(t!("cue-match-by"));
+                ui.radio_value(&mut self.cue_import.by_filename, false, t!("cue-by-order"));
+                ui.radio_value(&mut self.cue_import.by_filename, true, t!("cue-by-filename"));
+            });
+            if before != self.cue_import.by_filename {
+                let import = &mut self.cue_import;
+                import.matches = match_tracks(&import.sheet, &self.dropped_files, import.by_filename);
+            }
+
+            egui::ScrollArea::vertical().max_height(250.0).show(ui, |ui| {
+                egui::Grid::new("cue_mapping").num_columns(3).striped(true).show(ui, |ui| {
+                    let import = &mut self.cue_import;
+                    for (i, track) in import.sheet.tracks.iter().enumerate() {
+                        ui.label(format!("{:02}", track.number));
+                        ui.label(format!(
+                            "{} - {}",
+                            track.performer.as_deref().or(import.sheet.performer.as_deref()).unwrap_or("?"),
+                            track.title.as_deref().unwrap_or("?")
+                        ));
+                        let selected = &mut import.matches[i];
+                        let label = selected
+                            .as_ref()
+                            .map(|f| MyApp::get_file_name(f).to_string())
+                            .unwrap_or_else(|| t!("cue-skip"));
+                        egui::ComboBox::from_id_salt(("cue_file", i))
+                            .selected_text(label)
+                            .width(200.0)
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(selected, None, t!("cue-skip"));
+                                for file in &self.dropped_files {
+                                    ui.selectable_value(selected, Some(file.clone()), MyApp::get_file_name(file));
+                                }
+                            });
+                        ui.end_row();
+                    }
+                });
+            });
+
+            if ui.button(t!("cue-write")).clicked() {
+                let errors = self.apply_cue();
+                if errors.is_empty() {
+                    self.alert_message.clear();
+                    self.cue_import.open = false;
+                }
+                self.report_file_errors("cue-write-failed", errors);
+            }
+        });
+        self.cue_import.open &= open;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHEET: &str = "\u{feff}REM GENRE \"Rock\"\nREM DATE 1999\nPERFORMER \"The Band\"\nTITLE \"Live at Home\"\n\
+FILE \"live.mp3\" MP3\n  TRACK 01 AUDIO\n    TITLE \"Intro\"\n    INDEX 00 00:00:00\n    INDEX 01 00:00:37\n\
+  TRACK 02 AUDIO\n    TITLE \"Big Song\"\n    PERFORMER \"Guest\"\n    INDEX 01 03:25:00\n";
+
+    #[test]
+    fn index_times() {
+        assert_eq!(parse_index_time("03:25:00"), Some(205_000));
+        assert_eq!(parse_index_time("00:01:74"), Some(1_986));
+        assert_eq!(parse_index_time("00:01"), None);
+        assert_eq!(parse_index_time("00:01:02:03"), None);
+        assert_eq!(parse_index_time("99999999:00:00"), None);
+        assert_eq!(format_index_time(205_000), "03:25:00");
+        assert_eq!(format_index_time(parse_index_time("12:34:56").unwrap()), "12:34:56");
+    }
+
+    #[test]
+    fn parses_sheet_and_tracks() {
+        let sheet = parse(SHEET);
+        assert_eq!(sheet.genre.as_deref(), Some("Rock"));
+        assert_eq!(sheet.date.as_deref(), Some("1999"));
+        assert_eq!(sheet.performer.as_deref(), Some("The Band"));
+        assert_eq!(sheet.title.as_deref(), Some("Live at Home"));
+        assert_eq!(sheet.file.as_deref(), Some("live.mp3"));
+        assert_eq!(sheet.tracks.len(), 2);
+
+        let intro = &sheet.tracks[0];
+        assert_eq!((intro.number, intro.title.as_deref(), intro.performer.as_deref()), (1, Some("Intro"), None));
+        assert_eq!(intro.start_ms, Some(493));
+        let song = &sheet.tracks[1];
+        assert_eq!(song.title.as_deref(), Some("Big Song"));
+        assert_eq!(song.performer.as_deref(), Some("Guest"));
+        assert_eq!(song.start_ms, Some(205_000));
+        assert_eq!(song.file.as_deref(), Some("live.mp3"));
+    }
+
+    #[test]
+    fn write_reads_back() {
+        let sheet = parse(SHEET);
+        let written = write(&sheet);
+        assert!(written.starts_with("REM GENRE \"Rock\"\nREM DATE 1999\nPERFORMER \"The Band\"\n"));
+        assert!(written.contains("  TRACK 01 AUDIO\n    TITLE \"Intro\"\n    INDEX 01 00:00:37\n"));
+        assert!(written.contains("    PERFORMER \"Guest\"\n    INDEX 01 03:25:00\n"));
+
+        let again = parse(&written);
+        assert_eq!(again.title, sheet.title);
+        assert_eq!(again.file, sheet.file);
+        let starts: Vec<_> = again.tracks.iter().map(|t| (t.number, t.start_ms)).collect();
+        assert_eq!(starts, [(1, Some(493)), (2, Some(205_000))]);
+    }
+
+    #[test]
+    fn matches_by_order_and_by_name() {
+        let sheet = parse(SHEET);
+        let files = ["/music/Big Song (Remix).mp3", "/music/02 Big Song.mp3", "/music/01.mp3"].map(String::from);
+
+        let by_order = match_tracks(&sheet, &files, false);
+        assert_eq!(by_order, [Some(files[0].clone()), Some(files[1].clone())]);
+
+        // the remix only contains the title, so it isn't taken for track 2
+        let by_name = match_tracks(&sheet, &files, true);
+        assert_eq!(by_name, [Some(files[2].clone()), Some(files[1].clone())]);
+
+        let unmatched = match_tracks(&sheet, &files[..1], true);
+        assert_eq!(unmatched, [None, None]);
+    }
+}
@@ -0,0 +1,398 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::time::Duration;
+
+use eframe::egui;
+use id3::{Tag, TagLike};
+
+use crate::errors;
+use crate::i18n::t;
+use crate::{MyApp, audio, fingerprint, playback};
+
+/// Fingerprints at least this similar are treated as the same recording.
+const SIMILARITY_THRESHOLD: f64 = 0.9;
+
+/// What was learned about one file during a scan.
+struct Scanned {
+    path: String,
+    name_key: Option<String>,
+    stream_hash: Option<String>,
+    fingerprint: Option<Vec<u32>>,
+    bitrate: Option<u32>,
+    sample_rate: Option<u32>,
+    duration: Option<f64>,
+}
+
+enum Message {
+    Scanned(Scanned),
+    Finished,
+}
+
+struct Group {
+    reason: String,
+    files: Vec<usize>,
+    keeper: usize,
+}
+
+#[derive(Default)]
+pub struct DuplicateFinder {
+    open: bool,
+    by_name: bool,
+    by_hash: bool,
+    by_fingerprint: bool,
+    scanned: Vec<Scanned>,
+    groups: Vec<Group>,
+    pending: Option<(Receiver<Message>, usize)>,
+}
+
+/// Lowercased words of "artist title", so punctuation and spacing differences still match.
+fn normalize(artist: &str, title: &str) -> Option<String> {
+    if artist.trim().is_empty() || title.trim().is_empty() {
+        return None;
+    }
+    let words: Vec<String> = format!("{} {}", artist, title)
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(|w| w.to_lowercase())
+        .collect();
+    Some(words.join(" "))
+}
+
+fn scan(path: &str, with_fingerprint: bool) -> Scanned {
+    let tag = id3::no_tag_ok(Tag::read_from_path(path)).ok().flatten();
+    let name_key = tag
+        .as_ref()
+        .and_then(|t| normalize(t.artist().unwrap_or_default(), t.title().unwrap_or_default()));
+    Scanned {
+        path: path.to_string(),
+        name_key,
+        stream_hash: audio::stream_hash(path).ok(),
+        fingerprint: if with_fingerprint {
+            fingerprint::compute(path).ok().map(|f| f.raw)
+        } else {
+            None
+        },
+        bitrate: MyApp::get_bitrate(path).ok().flatten(),
+        sample_rate: MyApp::get_sample_rate(path).ok().flatten(),
+        duration: MyApp::get_duration_seconds(path).ok().flatten(),
+    }
+}
+
+fn find(parent: &mut [usize], i: usize) -> usize {
+    let mut root = i;
+    while parent[root] != root {
+        root = parent[root];
+    }
+    parent[i] = root;
+    root
+}
+
+/// Groups files that match on any of the enabled criteria.
+fn group(scanned: &[Scanned], by_name: bool, by_hash: bool, by_fingerprint: bool) -> Vec<Group> {
+    let mut parent: Vec<usize> = (0..scanned.len()).collect();
+    let mut reasons: Vec<Vec<&str>> = vec![Vec::new(); scanned.len()];
+    let mut join = |parent: &mut Vec<usize>, a: usize, b: usize, reason: &'static str| {
+        let (ra, rb) = (find(parent, a), find(parent, b));
+        parent[rb] = ra;
+        for r in [a, b] {
+            if !reasons[r].contains(&reason) {
+                reasons[r].push(reason);
+            }
+        }
+    };
+
+    let mut by_key = |key: fn(&Scanned) -> Option<&String>, reason: &'static str, parent: &mut Vec<usize>| {
+        let mut first: HashMap<&String, usize> = HashMap::new();
+        for (i, file) in scanned.iter().enumerate() {
+            if let Some(k) = key(file) {
+                match first.get(k) {
+                    Some(&j) => join(parent, j, i, reason),
+                    None => {
+                        first.insert(k, i);
+                    }
+                }
+            }
+        }
+    };
+    if by_name {
+        by_key(|f| f.name_key.as_ref(), "duplicates-by-name", &mut parent);
+    }
+    if by_hash {
+        by_key(|f| f.stream_hash.as_ref(), "duplicates-same-audio", &mut parent);
+    }
+    if by_fingerprint {
+        for i in 0..scanned.len() {
+            for j in i + 1..scanned.len() {
+                if let (Some(a), Some(b)) = (&scanned[i].fingerprint, &scanned[j].fingerprint)
+                    && fingerprint::similarity(a, b) >= SIMILARITY_THRESHOLD
+                {
+                    join(&mut parent, i, j, "duplicates-similar-fingerprint");
+                }
+            }
+        }
+    }
+
+    let mut members: HashMap<usize, Vec<usize>> = HashMap::new();
+    for i in 0..scanned.len() {
+        let root = find(&mut parent, i);
+        members.entry(root).or_default().push(i);
+    }
+    let mut groups: Vec<Group> = members
+        .into_values()
+        .filter(|files| files.len() > 1)
+        .map(|files| {
+            let mut reason: Vec<&str> = Vec::new();
+            for &i in &files {
+                for r in &reasons[i] {
+                    if !reason.contains(r) {
+                        reason.push(r);
+                    }
+                }
+            }
+            // the highest bitrate is usually the one worth keeping
+            let keeper = *files.iter().max_by_key(|&&i| scanned[i].bitrate.unwrap_or(0)).unwrap_or(&files[0]);
+            let reason: Vec<String> = reason.into_iter().map(|r| t!(r)).collect();
+            Group { reason: reason.join(", "), files, keeper }
+        })
+        .collect();
+    groups.sort_by(|a, b| scanned[a.files[0]].path.cmp(&scanned[b.files[0]].path));
+    groups
+}
+
+/// Moves a file into the folder, copying when a rename can't cross devices.
+fn move_into(path: &str, folder: &Path) -> errors::Result<()> {
+    let name = Path::new(path).file_name().ok_or_else(|| t!("save-no-name"))?;
+    let target: PathBuf = folder.join(name);
+    if target.exists() {
+        return Err(t!("duplicates-exists", file = target.display().to_string()).into());
+    }
+    if std::fs::rename(path, &target).is_err() {
+        std::fs::copy(path, &target)?;
+        std::fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+impl MyApp {
+    pub(crate) fn open_duplicates(&mut self) {
+        let finder = &mut self.duplicates;
+        if !finder.open && finder.scanned.is_empty() {
+            finder.by_name = true;
+            finder.by_hash = true;
+        }
+        finder.open = true;
+    }
+
+    fn start_duplicate_scan(&mut self) {
+        let paths = self.dropped_files.clone();
+        let with_fingerprint = self.duplicates.by_fingerprint;
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            for path in paths {
+                if sender.send(Message::Scanned(scan(&path, with_fingerprint))).is_err() {
+                    return;
+                }
+            }
+            let _ = sender.send(Message::Finished);
+        });
+        self.duplicates.scanned.clear();
+        self.duplicates.groups.clear();
+        self.duplicates.pending = Some((receiver, self.dropped_files.len()));
+    }
+
+    fn poll_duplicate_scan(&mut self, ctx: &egui::Context) {
+        let Some((receiver, total)) = self.duplicates.pending.take() else {
+            return;
+        };
+        let mut finished = false;
+        while let Ok(message) = receiver.try_recv() {
+            match message {
+                Message::Scanned(scanned) => self.duplicates.scanned.push(scanned),
+                Message::Finished => finished = true,
+            }
+        }
+        if finished {
+            self.regroup_duplicates();
+        } else {
+            self.duplicates.pending = Some((receiver, total));
+            ctx.request_repaint_after(Duration::from_millis(200));
+        }
+    }
+
+    fn regroup_duplicates(&mut self) {
+        let finder = &mut self.duplicates;
+        finder.groups = group(&finder.scanned, finder.by_name, finder.by_hash, finder.by_fingerprint);
+    }
+
+    /// Drops every non-keeper of the group from the list, first moving them if a folder is given.
+    fn resolve_group(&mut self, index: usize, folder: Option<PathBuf>) {
+        let group = &self.duplicates.groups[index];
+        let others: Vec<String> = group
+            .files
+            .iter()
+            .filter(|&&i| i != group.keeper)
+            .map(|&i| self.duplicates.scanned[i].path.clone())
+            .collect();
+
+        let mut errors = Vec::new();
+        let mut removed = Vec::new();
+        for path in others {
+            if let Some(folder) = &folder
+                && let Err(e) = move_into(&path, folder)
+            {
+                errors.push((path.clone(), e.to_string()));
+                continue;
+            }
+            removed.push(path);
+        }
+
+        self.dropped_files.retain(|f| !removed.contains(f));
+        if self.selected_file.as_ref().is_some_and(|s| removed.contains(s)) {
+            self.selected_file = None;
+            self.player = None;
+        }
+        self.duplicates.scanned.retain(|s| !removed.contains(&s.path));
+        self.regroup_duplicates();
+        self.report_file_errors("duplicates-move-failed", errors);
+    }
+
+    pub(crate) fn duplicates_window(&mut self, ctx: &egui::Context) {
+        self.poll_duplicate_scan(ctx);
+
+        let mut open = self.duplicates.open;
+        let mut scan_clicked = false;
+        let mut regroup = false;
+        let mut resolve = None;
+        egui::Window::new(t!("duplicates-title")).open(&mut open).show(ctx, |ui| {
+            let finder = &mut self.duplicates;
+            let busy = finder.pending.is_some();
+            ui.horizontal(|ui| {
+                ui.label(t!("duplicates-match-by"));
+                regroup |= ui.checkbox(&mut finder.by_name, t!("duplicates-by-name")).changed();
+                regroup |= ui.checkbox(&mut finder.by_hash, t!("duplicates-by-hash")).changed();
+                regroup |= ui.checkbox(&mut finder.by_fingerprint, t!("duplicates-by-fingerprint")).changed();
+            });
+            ui.horizontal(|ui| {
+                scan_clicked = ui.add_enabled(!busy, egui::Button::new(t!("duplicates-scan"))).clicked();
+                match &finder.pending {
+                    Some((_, total)) => ui.label(t!("duplicates-progress", done = finder.scanned.len(), total = *total)),
+                    None => ui.label(t!("duplicates-found", count = finder.groups.len())),
+                };
+            });
+            if finder.by_fingerprint && !busy && finder.scanned.iter().all(|s| s.fingerprint.is_none()) {
+                ui.label(t!("duplicates-rescan"));
+            }
+
+            ui.separator();
+            egui::ScrollArea::vertical().max_height(350.0).show(ui, |ui| {
+                for (index, group) in finder.groups.iter_mut().enumerate() {
+                    ui.label(egui::RichText::new(&group.reason).strong());
+                    egui::Grid::new(("duplicate_group", index)).num_columns(4).show(ui, |ui| {
+                        for &i in &group.files {
+                            let file = &finder.scanned[i];
+                            ui.radio_value(&mut group.keeper, i, MyApp::get_file_name(&file.path))
+                                .on_hover_text(&file.path);
+                            ui.label(file.bitrate.map(|b| t!("info-kbps", kbps = b / 1000)).unwrap_or_default());
+                            ui.label(file.sample_rate.map(|r| t!("info-hz", hz = r)).unwrap_or_default());
+                            ui.label(file.duration.map(playback::format_time).unwrap_or_default());
+                            ui.end_row();
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        if ui.add_enabled(!busy, egui::Button::new(t!("duplicates-remove-others"))).clicked() {
+                            resolve = Some((index, false));
+                        }
+                        if ui.add_enabled(!busy, egui::Button::new(t!("duplicates-move-others"))).clicked() {
+                            resolve = Some((index, true));
+                        }
+                    });
+                    ui.separator();
+                }
+            });
+        });
+        self.duplicates.open &= open;
+
+        if scan_clicked {
+            self.start_duplicate_scan();
+        } else if regroup && self.duplicates.pending.is_none() {
+            self.regroup_duplicates();
+        }
+        if let Some((index, move_files)) = resolve {
+            if move_files {
+                if let Some(folder) = rfd::FileDialog::new().pick_folder() {
+                    self.resolve_group(index, Some(folder));
+                }
+            } else {
+                self.resolve_group(index, None);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scanned(
+        path: &str,
+        name: Option<&str>,
+        hash: Option<&str>,
+        fingerprint: Option<Vec<u32>>,
+        bitrate: Option<u32>,
+    ) -> Scanned {
+        Scanned {
+            path: path.to_string(),
+            name_key: name.map(str::to_string),
+            stream_hash: hash.map(str::to_string),
+            fingerprint,
+            bitrate,
+            sample_rate: None,
+            duration: None,
+        }
+    }
+
+    fn files() -> Vec<Scanned> {
+        let print: Vec<u32> = (0..100u32).map(|i| i.wrapping_mul(2_654_435_761)).collect();
+        vec![
+            scanned("a.mp3", Some("band song"), Some("h1"), None, Some(128)),
+            scanned("b.mp3", Some("band song"), Some("h2"), None, Some(320)),
+            scanned("c.mp3", None, Some("h2"), None, Some(192)),
+            scanned("d.mp3", None, None, Some(print.clone()), None),
+            scanned("e.mp3", None, None, Some(print), Some(96)),
+            scanned("f.mp3", Some("other song"), Some("h3"), None, Some(320)),
+        ]
+    }
+
+    #[test]
+    fn groups_join_across_criteria() {
+        let scanned = files();
+        let mut groups = group(&scanned, true, true, true);
+        for g in &mut groups {
+            g.files.sort();
+        }
+        assert_eq!(groups.len(), 2);
+
+        // a and b share a name, b and c the audio, so all three end up together
+        assert_eq!(groups[0].files, [0, 1, 2]);
+        assert_eq!(groups[0].reason, format!("{}, {}", t!("duplicates-by-name"), t!("duplicates-same-audio")));
+        assert_eq!(groups[0].keeper, 1);
+
+        assert_eq!(groups[1].files, [3, 4]);
+        assert_eq!(groups[1].reason, t!("duplicates-similar-fingerprint"));
+        assert_eq!(groups[1].keeper, 4);
+    }
+
+    #[test]
+    fn only_enabled_criteria_group() {
+        let scanned = files();
+        let groups = group(&scanned, true, false, false);
+        assert_eq!(groups.len(), 1);
+        let mut files = groups[0].files.clone();
+        files.sort();
+        assert_eq!(files, [0, 1]);
+        assert_eq!(groups[0].reason, t!("duplicates-by-name"));
+
+        assert!(group(&scanned, false, false, false).is_empty());
+    }
+}
@@ -0,0 +1,160 @@
+use chrono::{DateTime, Local};
+use eframe::egui;
+
+use crate::MyApp;
+use crate::i18n::t;
+use crate::theme::Role;
+
+/// Older entries are dropped past this many.
+const MAX_ENTRIES: usize = 1000;
+
+/// Each failed file with what went wrong; an empty path for errors that aren't about one file.
+pub type FileErrors = Vec<(String, String)>;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Level {
+    /// Timings and other details, hidden unless asked for.
+    Info,
+    Warning,
+    Error,
+}
+
+impl Level {
+    fn name(self) -> String {
+        t!(match self {
+            Level::Info => "log-info",
+            Level::Warning => "log-warning",
+            Level::Error => "log-error",
+        })
+    }
+}
+
+struct Entry {
+    time: DateTime<Local>,
+    level: Level,
+    file: Option<String>,
+    message: String,
+}
+
+impl Entry {
+    fn line(&self) -> String {
+        let file = self.file.as_deref().map(|f| format!("{}: ", f)).unwrap_or_default();
+        format!("{} [{}] {}{}", self.time.format("%Y-%m-%d %H:%M:%S"), self.level.name(), file, self.message)
+    }
+}
+
+#[derive(Default)]
+pub struct ErrorLog {
+    entries: Vec<Entry>,
+    show_info: bool,
+    /// Only show entries about the selected file.
+    selected_only: bool,
+    open: bool,
+}
+
+impl ErrorLog {
+    fn push(&mut self, level: Level, file: Option<&str>, message: String) {
+        self.entries.push(Entry { time: Local::now(), level, file: file.map(str::to_string), message });
+        if self.entries.len() > MAX_ENTRIES {
+            self.entries.remove(0);
+        }
+    }
+
+    fn problems(&self) -> usize {
+        self.entries.iter().filter(|e| e.level != Level::Info).count()
+    }
+}
+
+impl MyApp {
+    /// Logs an error, about `file` if there is one, and shows it in the alert bar.
+    pub(crate) fn report_error(&mut self, file: Option<&str>, message: String) {
+        self.log.push(Level::Error, file, message.clone());
+        self.alert_message = message;
+    }
+
+    /// Like `report_error`, for something that didn't stop the work.
+    pub(crate) fn report_warning(&mut self, file: Option<&str>, message: String) {
+        self.log.push(Level::Warning, file, message.clone());
+        self.alert_message = message;
+    }
+
+    /// Logs each file's error on its own and sums them up in the alert bar with the message `key`.
+    pub(crate) fn report_file_errors(&mut self, key: &str, errors: FileErrors) {
+        if errors.is_empty() {
+            return;
+        }
+        let summary: Vec<String> = errors
+            .iter()
+            .map(|(path, e)| if path.is_empty() { e.clone() } else { format!("{}: {}", MyApp::get_file_name(path), e) })
+            .collect();
+        for (path, e) in errors {
+            self.log.push(Level::Error, Some(path.as_str()).filter(|p| !p.is_empty()), e);
+        }
+        self.alert_message = t!(key, errors = summary.join("; "));
+    }
+
+    pub(crate) fn log_info(&mut self, file: Option<&str>, message: String) {
+        self.log.push(Level::Info, file, message);
+    }
+
+    pub(crate) fn open_log(&mut self) {
+        self.log.open = true;
+    }
+
+    /// The alert bar's link to the log, counting the errors and warnings in it.
+    pub(crate) fn log_link(&mut self, ui: &mut egui::Ui) {
+        if ui.link(t!("log-link", count = self.log.problems())).clicked() {
+            self.open_log();
+        }
+    }
+
+    pub(crate) fn log_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.log.open;
+        let colors = [Role::MutedText, Role::Warning, Role::Error].map(|r| self.theme.color(r));
+        egui::Window::new(t!("log-title")).open(&mut open).default_width(500.0).show(ctx, |ui| {
+            let selected = self.selected_file.as_deref();
+            let log = &mut self.log;
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut log.show_info, t!("log-show-info"));
+                ui.add_enabled(selected.is_some(), egui::Checkbox::new(&mut log.selected_only, t!("log-selected-only")));
+            });
+            let shown: Vec<&Entry> = log
+                .entries
+                .iter()
+                .filter(|e| log.show_info || e.level != Level::Info)
+                .filter(|e| !log.selected_only || selected.is_none() || e.file.as_deref() == selected)
+                .collect();
+
+            let mut clear = false;
+            ui.horizontal(|ui| {
+                if ui.add_enabled(!shown.is_empty(), egui::Button::new(t!("log-copy"))).clicked() {
+                    let text: Vec<String> = shown.iter().map(|e| e.line()).collect();
+                    ui.ctx().copy_text(text.join("\n"));
+                }
+                clear = ui.add_enabled(!log.entries.is_empty(), egui::Button::new(t!("log-clear"))).clicked();
+            });
+            ui.separator();
+
+            if shown.is_empty() {
+                ui.label(t!("log-empty"));
+            }
+            egui::ScrollArea::vertical().max_height(400.0).stick_to_bottom(true).show(ui, |ui| {
+                for entry in &shown {
+                    let color = colors[entry.level as usize];
+                    ui.horizontal_wrapped(|ui| {
+                        ui.label(egui::RichText::new(entry.time.format("%H:%M:%S").to_string()).monospace());
+                        ui.colored_label(color, entry.level.name());
+                        if let Some(file) = &entry.file {
+                            ui.label(egui::RichText::new(MyApp::get_file_name(file)).strong()).on_hover_text(file);
+                        }
+                        ui.label(&entry.message);
+                    });
+                }
+            });
+            if clear {
+                log.entries.clear();
+            }
+        });
+        self.log.open &= open;
+    }
+}
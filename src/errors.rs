@@ -0,0 +1,179 @@
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::i18n::t;
+
+/// What reading or writing a file can fail with.
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    /// A container, codec, image or tag feature we can't handle.
+    Unsupported(String),
+    /// A tag that's there but can't be parsed.
+    CorruptTag(String),
+    /// The system refused access to the file, if known.
+    Permission { path: Option<PathBuf>, source: io::Error },
+    /// The file is marked read-only, so saving won't replace it.
+    ReadOnly(PathBuf),
+    /// Audio or image data that doesn't decode.
+    Decode(String),
+    /// Anything else, already worded for the user.
+    Other(String),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let message = match self {
+            Error::Io(e) => e.to_string(),
+            Error::Unsupported(e) => t!("error-unsupported", error = e.as_str()),
+            Error::CorruptTag(e) => t!("error-corrupt-tag", error = e.as_str()),
+            Error::Permission { path: Some(path), source } => {
+                t!("error-permission-file", file = path.display().to_string(), error = source.to_string())
+            }
+            Error::Permission { path: None, source } => t!("error-permission", error = source.to_string()),
+            Error::ReadOnly(path) => {
+                let name = path.file_name().map(|n| n.to_string_lossy()).unwrap_or_default();
+                t!("save-read-only", file = name)
+            }
+            Error::Decode(e) => t!("error-decode", error = e.as_str()),
+            Error::Other(e) => e.clone(),
+        };
+        f.write_str(&message)
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) | Error::Permission { source: e, .. } => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl Error {
+    /// Names `path` as the file a permission error is about, if it doesn't name one yet.
+    pub fn at(self, path: &Path) -> Self {
+        match self {
+            Error::Permission { path: None, source } => Error::Permission { path: Some(path.to_path_buf()), source },
+            e => e,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        match e.kind() {
+            io::ErrorKind::PermissionDenied | io::ErrorKind::ReadOnlyFilesystem => Error::Permission { path: None, source: e },
+            _ => Error::Io(e),
+        }
+    }
+}
+
+impl From<id3::Error> for Error {
+    fn from(e: id3::Error) -> Self {
+        match e.kind {
+            id3::ErrorKind::Io(io) => io.into(),
+            id3::ErrorKind::UnsupportedFeature => Error::Unsupported(e.description),
+            _ => Error::CorruptTag(e.description),
+        }
+    }
+}
+
+impl From<ape::Error> for Error {
+    fn from(e: ape::Error) -> Self {
+        match e {
+            ape::Error::Io(io) => io.into(),
+            e => Error::CorruptTag(e.to_string()),
+        }
+    }
+}
+
+impl From<image::ImageError> for Error {
+    fn from(e: image::ImageError) -> Self {
+        match e {
+            image::ImageError::IoError(io) => io.into(),
+            image::ImageError::Unsupported(e) => Error::Unsupported(e.to_string()),
+            e => Error::Decode(e.to_string()),
+        }
+    }
+}
+
+impl From<symphonia::core::errors::Error> for Error {
+    fn from(e: symphonia::core::errors::Error) -> Self {
+        use symphonia::core::errors::Error as Symphonia;
+        match e {
+            Symphonia::IoError(io) => io.into(),
+            Symphonia::Unsupported(what) => Error::Unsupported(what.to_string()),
+            e => Error::Decode(e.to_string()),
+        }
+    }
+}
+
+impl From<csv::Error> for Error {
+    fn from(e: csv::Error) -> Self {
+        if !e.is_io_error() {
+            return Error::Decode(e.to_string());
+        }
+        match e.into_kind() {
+            csv::ErrorKind::Io(io) => io.into(),
+            kind => Error::Decode(format!("{:?}", kind)),
+        }
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        match e.classify() {
+            serde_json::error::Category::Io => io::Error::from(e).into(),
+            _ => Error::Decode(e.to_string()),
+        }
+    }
+}
+
+impl From<ureq::Error> for Error {
+    fn from(e: ureq::Error) -> Self {
+        match e {
+            ureq::Error::Transport(transport) => match transport.kind() {
+                ureq::ErrorKind::InvalidUrl | ureq::ErrorKind::UnknownScheme => Error::Unsupported(transport.to_string()),
+                _ => Error::Io(io::Error::other(transport)),
+            },
+            // the server answered, just not with what was asked for
+            e @ ureq::Error::Status(..) => Error::Io(io::Error::other(e)),
+        }
+    }
+}
+
+impl From<rusty_chromaprint::ResetError> for Error {
+    fn from(e: rusty_chromaprint::ResetError) -> Self {
+        Error::Unsupported(e.to_string().trim().to_string())
+    }
+}
+
+impl From<String> for Error {
+    fn from(message: String) -> Self {
+        Error::Other(message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn permission_errors_keep_cause_and_path() {
+        let denied = || io::Error::new(io::ErrorKind::PermissionDenied, "denied by test");
+        let e = Error::from(denied()).at(Path::new("/music/song.mp3"));
+        let Error::Permission { path: Some(path), .. } = &e else {
+            panic!("{e:?}");
+        };
+        assert_eq!(path, Path::new("/music/song.mp3"));
+        assert!(std::error::Error::source(&e).is_some());
+        let message = e.to_string();
+        assert!(message.contains("/music/song.mp3") && message.contains("denied by test"), "{message}");
+        assert_ne!(message, Error::ReadOnly(PathBuf::from("/music/song.mp3")).to_string());
+    }
+}
@@ -0,0 +1,330 @@
+use id3::frame::Comment;
+use id3::{Tag, TagLike, Timestamp, Version};
+use serde::{Deserialize, Serialize};
+
+use crate::genres;
+use crate::i18n::t;
+
+/// The plain text fields the batch tools can read and write.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Field {
+    Title,
+    Artist,
+    Album,
+    AlbumArtist,
+    Genre,
+    Year,
+    Track,
+    Disc,
+    Composer,
+    Comment,
+}
+
+impl Field {
+    pub const ALL: [Field; 10] = [
+        Field::Title,
+        Field::Artist,
+        Field::Album,
+        Field::AlbumArtist,
+        Field::Genre,
+        Field::Year,
+        Field::Track,
+        Field::Disc,
+        Field::Composer,
+        Field::Comment,
+    ];
+
+    /// The name used for spreadsheet columns, which stays the same in every language.
+    pub fn name(self) -> &'static str {
+        match self {
+            Field::Title => "Title",
+            Field::Artist => "Artist",
+            Field::Album => "Album",
+            Field::AlbumArtist => "Album Artist",
+            Field::Genre => "Genre",
+            Field::Year => "Year",
+            Field::Track => "Track",
+            Field::Disc => "Disc",
+            Field::Composer => "Composer",
+            Field::Comment => "Comment",
+        }
+    }
+
+    /// The name shown in the interface, in the current language.
+    pub fn label(self) -> String {
+        t!(match self {
+            Field::Title => "field-title",
+            Field::Artist => "field-artist",
+            Field::Album => "field-album",
+            Field::AlbumArtist => "field-album-artist",
+            Field::Genre => "field-genre",
+            Field::Year => "field-year",
+            Field::Track => "field-track",
+            Field::Disc => "field-disc",
+            Field::Composer => "field-composer",
+            Field::Comment => "field-comment",
+        })
+    }
+
+    /// Matches column headers loosely: case, spaces and underscores are ignored.
+    pub fn from_name(name: &str) -> Option<Field> {
+        let normalize = |s: &str| s.to_lowercase().replace([' ', '_', '-'], "");
+        let wanted = normalize(name);
+        Field::ALL.into_iter().find(|f| normalize(f.name()) == wanted)
+    }
+
+    pub fn get(self, tag: &Tag) -> Option<String> {
+        match self {
+            Field::Title => tag.title().map(str::to_string),
+            Field::Artist => tag.artist().map(str::to_string),
+            Field::Album => tag.album().map(str::to_string),
+            Field::AlbumArtist => tag.album_artist().map(str::to_string),
+            Field::Genre => tag.genre().map(str::to_string),
+            Field::Year => tag
+                .date_recorded()
+                .map(|d| d.year)
+                .or_else(|| tag.year())
+                .map(|y| y.to_string()),
+            Field::Track => tag.track().map(|n| match tag.total_tracks() {
+                Some(total) => format!("{}/{}", n, total),
+                None => n.to_string(),
+            }),
+            Field::Disc => tag.disc().map(|n| match tag.total_discs() {
+                Some(total) => format!("{}/{}", n, total),
+                None => n.to_string(),
+            }),
+            Field::Composer => tag.get("TCOM").and_then(|f| f.content().text()).map(str::to_string),
+            Field::Comment => tag
+                .comments()
+                .find(|c| c.description.is_empty())
+                .map(|c| c.text.clone()),
+        }
+    }
+
+    /// Sets the field from its text form. An empty value removes it.
+    pub fn set(self, tag: &mut Tag, value: &str) -> Result<(), String> {
+        let value = value.trim();
+        match self {
+            Field::Title if value.is_empty() => tag.remove_title(),
+            Field::Title => tag.set_title(value),
+            Field::Artist if value.is_empty() => tag.remove_artist(),
+            Field::Artist => tag.set_artist(value),
+            Field::Album if value.is_empty() => tag.remove_album(),
+            Field::Album => tag.set_album(value),
+            Field::AlbumArtist if value.is_empty() => tag.remove_album_artist(),
+            Field::AlbumArtist => tag.set_album_artist(value),
+            Field::Genre if value.is_empty() => tag.remove_genre(),
+            Field::Genre => tag.set_text_values("TCON", normalize_genres([value])),
+            Field::Year => {
+                tag.remove_year();
+                tag.remove_date_recorded();
+                if !value.is_empty() {
+                    let timestamp: Timestamp = value.parse().map_err(|_| t!("field-invalid-year", value = value))?;
+                    tag.set_date_recorded(timestamp);
+                }
+            }
+            Field::Track => {
+                let (number, total) = parse_pair(value)?;
+                match number {
+                    Some(n) => tag.set_track(n),
+                    None => tag.remove_track(),
+                }
+                match total {
+                    Some(n) => tag.set_total_tracks(n),
+                    None => tag.remove_total_tracks(),
+                }
+            }
+            Field::Disc => {
+                let (number, total) = parse_pair(value)?;
+                match number {
+                    Some(n) => tag.set_disc(n),
+                    None => tag.remove_disc(),
+                }
+                match total {
+                    Some(n) => tag.set_total_discs(n),
+                    None => tag.remove_total_discs(),
+                }
+            }
+            Field::Composer if value.is_empty() => {
+                tag.remove("TCOM");
+            }
+            Field::Composer => tag.set_text("TCOM", value),
+            Field::Comment => {
+                tag.remove_comment(Some(""), None);
+                if !value.is_empty() {
+                    tag.add_frame(Comment {
+                        lang: "eng".to_string(),
+                        description: String::new(),
+                        text: value.to_string(),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The text frame of fields that can hold a list of values.
+    pub fn multi_value_frame(self) -> Option<&'static str> {
+        match self {
+            Field::Artist => Some("TPE1"),
+            Field::AlbumArtist => Some("TPE2"),
+            Field::Genre => Some("TCON"),
+            Field::Composer => Some("TCOM"),
+            _ => None,
+        }
+    }
+
+    /// Each value of a multi-valued field, as separated by nulls in ID3v2.4.
+    /// Older tags joined them into one string, so those are split on `separator`.
+    pub fn values(self, tag: &Tag, separator: Separator) -> Vec<String> {
+        let Some(id) = self.multi_value_frame() else {
+            return self.get(tag).into_iter().collect();
+        };
+        let Some(text) = tag.get(id).and_then(|f| f.content().text()) else {
+            return Vec::new();
+        };
+        let values: Vec<String> = match tag.version() {
+            Version::Id3v24 => text.split('\0').filter(|v| !v.is_empty()).map(str::to_string).collect(),
+            // the id3 crate reads every '/' in these as a null
+            _ => separator.split(&text.replace('\0', "/")),
+        };
+        if self == Field::Genre {
+            values.iter().flat_map(|v| genres::resolve(v)).collect()
+        } else {
+            values
+        }
+    }
+
+    /// Stores the values null-separated; `Separator` decides how they're joined for ID3v2.3.
+    pub fn set_values(self, tag: &mut Tag, values: &[String]) -> Result<(), String> {
+        let mut values: Vec<String> = values.iter().map(|v| v.trim().to_string()).filter(|v| !v.is_empty()).collect();
+        if self == Field::Genre {
+            values = normalize_genres(values.iter().map(String::as_str));
+        }
+        match self.multi_value_frame() {
+            Some(id) if values.is_empty() => {
+                tag.remove(id);
+            }
+            Some(id) => tag.set_text_values(id, values),
+            None => self.set(tag, values.first().map(String::as_str).unwrap_or(""))?,
+        }
+        Ok(())
+    }
+}
+
+/// How multiple values are joined in ID3v2.3, which has no list separator of its own.
+/// Semicolons by default, since slashes turn up in names like "AC/DC".
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Separator {
+    Slash,
+    #[default]
+    Semicolon,
+}
+
+impl Separator {
+    pub const ALL: [Separator; 2] = [Separator::Slash, Separator::Semicolon];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Separator::Slash => "/",
+            Separator::Semicolon => "; ",
+        }
+    }
+
+    /// The values in text joined with this separator, trimmed.
+    pub fn split(self, text: &str) -> Vec<String> {
+        text.split(self.as_str().trim())
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+            .collect()
+    }
+
+    /// Joins the null-separated values of every multi-valued frame for writing as ID3v2.3.
+    pub fn apply(self, tag: &mut Tag) {
+        for field in Field::ALL {
+            if let Some(id) = field.multi_value_frame()
+                && let Some(text) = tag.get(id).and_then(|f| f.content().text())
+                && text.contains('\0')
+            {
+                let joined = text.split('\0').collect::<Vec<_>>().join(self.as_str());
+                tag.set_text(id, joined);
+            }
+        }
+    }
+}
+
+/// Genre names for writing: numeric references resolved and table genres in their usual spelling.
+fn normalize_genres<'a>(values: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    let mut genres: Vec<String> = Vec::new();
+    for genre in values.into_iter().flat_map(genres::resolve) {
+        let genre = genres::canonical(&genre, &[]);
+        if !genres.contains(&genre) {
+            genres.push(genre);
+        }
+    }
+    genres
+}
+
+/// `"3"`, `"3/12"` or `""`.
+fn parse_pair(value: &str) -> Result<(Option<u32>, Option<u32>), String> {
+    let parse = |s: &str| -> Result<Option<u32>, String> {
+        let s = s.trim();
+        if s.is_empty() {
+            Ok(None)
+        } else {
+            s.parse().map(Some).map_err(|_| t!("field-invalid-number", value = s))
+        }
+    };
+    match value.split_once('/') {
+        Some((number, total)) => Ok((parse(number)?, parse(total)?)),
+        None => Ok((parse(value)?, None)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `tag` written as `version` and read back.
+    fn round_trip(tag: &Tag, version: Version) -> Tag {
+        let mut bytes = Vec::new();
+        tag.write_to(&mut bytes, version).unwrap();
+        Tag::read_from2(std::io::Cursor::new(bytes)).unwrap()
+    }
+
+    #[test]
+    fn values_survive_every_version() {
+        let artists = vec!["Simon".to_string(), "Garfunkel".to_string()];
+        for separator in Separator::ALL {
+            let mut tag = Tag::new();
+            Field::Artist.set_values(&mut tag, &artists).unwrap();
+            assert_eq!(Field::Artist.values(&round_trip(&tag, Version::Id3v24), separator), artists);
+
+            separator.apply(&mut tag);
+            let v23 = round_trip(&tag, Version::Id3v23);
+            assert_eq!(Field::Artist.values(&v23, separator), artists);
+        }
+    }
+
+    #[test]
+    fn slashes_stay_in_names_by_default() {
+        let mut tag = Tag::new();
+        tag.set_artist("AC/DC; Motörhead");
+        let v23 = round_trip(&tag, Version::Id3v23);
+        assert_eq!(Field::Artist.values(&v23, Separator::default()), ["AC/DC", "Motörhead"]);
+
+        // v2.4 keeps values apart with nulls, so slashes never split there
+        let mut tag = Tag::new();
+        Field::Artist.set_values(&mut tag, &["AC/DC".to_string()]).unwrap();
+        let v24 = round_trip(&tag, Version::Id3v24);
+        for separator in Separator::ALL {
+            assert_eq!(Field::Artist.values(&v24, separator), ["AC/DC"]);
+        }
+    }
+
+    #[test]
+    fn split_trims_and_drops_empty_values() {
+        assert_eq!(Separator::Semicolon.split(" a ;b;; "), ["a", "b"]);
+        assert_eq!(Separator::Slash.split("a / b"), ["a", "b"]);
+    }
+}
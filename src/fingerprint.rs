@@ -0,0 +1,444 @@
+use std::fs::File;
+use std::sync::mpsc::{self, Receiver};
+use std::time::Duration;
+
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use eframe::egui;
+use id3::frame::{ExtendedText, UniqueFileIdentifier};
+use id3::{Tag, TagLike};
+use rusty_chromaprint::{Configuration, FingerprintCompressor, Fingerprinter};
+use serde::Deserialize;
+use symphonia::core::{
+    audio::SampleBuffer,
+    codecs::DecoderOptions,
+    errors::Error as SymphoniaError,
+    formats::FormatOptions,
+    io::MediaSourceStream,
+    meta::MetadataOptions,
+    probe::Hint,
+};
+use symphonia::default::{get_codecs, get_probe};
+
+use crate::MyApp;
+use crate::errors;
+use crate::fields::Field;
+use crate::i18n::t;
+
+pub const DEFAULT_ENDPOINT: &str = "https://api.acoustid.org/v2";
+/// TXXX description Picard and other taggers use for the fingerprint.
+pub const FINGERPRINT_DESCRIPTION: &str = "Acoustid Fingerprint";
+const ACOUSTID_DESCRIPTION: &str = "Acoustid Id";
+/// Like `fpcalc`, only the start of the file is fingerprinted.
+const MAX_SECONDS: u32 = 120;
+
+/// Chromaprint's default algorithm, the one AcoustID expects.
+pub fn configuration() -> Configuration {
+    Configuration::preset_test2()
+}
+
+pub struct Fingerprint {
+    pub raw: Vec<u32>,
+    pub duration_secs: u32,
+}
+
+impl Fingerprint {
+    /// The compressed, base64 form AcoustID and `fpcalc` use.
+    pub fn encoded(&self) -> String {
+        let config = configuration();
+        URL_SAFE_NO_PAD.encode(FingerprintCompressor::from(&config).compress(&self.raw))
+    }
+}
+
+/// Decodes the start of the file and runs it through Chromaprint.
+pub fn compute(path: &str) -> errors::Result<Fingerprint> {
+    let file = File::open(path)?;
+    let mss = MediaSourceStream::new(Box::new(file), Default::default());
+    let probed = get_probe().format(
+        &Hint::new(),
+        mss,
+        &FormatOptions::default(),
+        &MetadataOptions::default(),
+    )?;
+    let mut format = probed.format;
+    let track = format.default_track().ok_or_else(|| t!("audio-no-track"))?;
+    let track_id = track.id;
+    let mut decoder = get_codecs().make(&track.codec_params, &DecoderOptions::default())?;
+    let total_secs = match (track.codec_params.sample_rate, track.codec_params.n_frames) {
+        (Some(rate), Some(frames)) => (frames / rate as u64) as u32,
+        _ => 0,
+    };
+
+    let config = configuration();
+    let mut printer = Fingerprinter::new(&config);
+    let mut started = false;
+    let mut consumed: u64 = 0;
+    let mut limit: u64 = u64::MAX;
+    let mut samples: Option<SampleBuffer<i16>> = None;
+
+    while consumed < limit {
+        let packet = match format.next_packet() {
+            Ok(packet) => packet,
+            Err(SymphoniaError::IoError(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => break,
+            Err(e) => return Err(e.into()),
+        };
+        if packet.track_id() != track_id {
+            continue;
+        }
+        let decoded = match decoder.decode(&packet) {
+            Ok(decoded) => decoded,
+            Err(SymphoniaError::DecodeError(_)) => continue,
+            Err(e) => return Err(e.into()),
+        };
+
+        let spec = *decoded.spec();
+        let channels = spec.channels.count();
+        if !started {
+            printer.start(spec.rate, channels as u32)?;
+            limit = spec.rate as u64 * channels as u64 * MAX_SECONDS as u64;
+            started = true;
+        }
+
+        let buffer = match &mut samples {
+            Some(buffer) if buffer.capacity() >= decoded.capacity() * channels => buffer,
+            _ => samples.insert(SampleBuffer::new(decoded.capacity() as u64, spec)),
+        };
+        buffer.copy_interleaved_ref(decoded);
+        let data = buffer.samples();
+        let take = data.len().min((limit - consumed) as usize);
+        printer.consume(&data[..take]);
+        consumed += take as u64;
+    }
+
+    if !started {
+        return Err(t!("identify-no-audio").into());
+    }
+    printer.finish();
+    Ok(Fingerprint { raw: printer.fingerprint().to_vec(), duration_secs: total_secs })
+}
+
+/// Share of matching bits at the best alignment of two fingerprints, from 0 to 1.
+pub fn similarity(a: &[u32], b: &[u32]) -> f64 {
+    // items are about 0.12 s apart, so this allows a few seconds of extra lead-in
+    const MAX_SHIFT: isize = 40;
+    let mut best = 0.0;
+    for shift in -MAX_SHIFT..=MAX_SHIFT {
+        let (a, b) = if shift >= 0 {
+            (a.get(shift as usize..).unwrap_or_default(), b)
+        } else {
+            (a, b.get((-shift) as usize..).unwrap_or_default())
+        };
+        let len = a.len().min(b.len());
+        if len == 0 {
+            continue;
+        }
+        let errors: u32 = a.iter().zip(b).map(|(x, y)| (x ^ y).count_ones()).sum();
+        let score = 1.0 - errors as f64 / (len as f64 * 32.0);
+        if score > best {
+            best = score;
+        }
+    }
+    best
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct Artist {
+    name: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct Recording {
+    id: String,
+    title: Option<String>,
+    #[serde(default)]
+    artists: Vec<Artist>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct LookupResult {
+    id: String,
+    score: f64,
+    #[serde(default)]
+    recordings: Vec<Recording>,
+}
+
+#[derive(Deserialize)]
+struct LookupResponse {
+    status: String,
+    #[serde(default)]
+    results: Vec<LookupResult>,
+    error: Option<LookupError>,
+}
+
+#[derive(Deserialize)]
+struct LookupError {
+    message: String,
+}
+
+/// A recording AcoustID thinks a file might be.
+#[derive(Clone, Debug)]
+pub struct Suggestion {
+    pub acoustid: String,
+    pub score: f64,
+    pub recording_id: String,
+    pub title: String,
+    pub artist: String,
+}
+
+pub fn lookup(
+    endpoint: &str,
+    api_key: &str,
+    fingerprint: &str,
+    duration_secs: u32,
+) -> errors::Result<Vec<Suggestion>> {
+    let agent = ureq::AgentBuilder::new()
+        .user_agent(crate::musicbrainz::USER_AGENT)
+        .timeout(Duration::from_secs(20))
+        .build();
+    let duration = duration_secs.to_string();
+    let response = agent
+        .post(&format!("{}/lookup", endpoint.trim_end_matches('/')))
+        .send_form(&[
+            ("client", api_key),
+            ("meta", "recordings"),
+            ("format", "json"),
+            ("duration", &duration),
+            ("fingerprint", fingerprint),
+        ]);
+    // AcoustID reports bad keys and the like as JSON with an error status
+    let response = match response {
+        Ok(response) | Err(ureq::Error::Status(_, response)) => response,
+        Err(e) => return Err(e.into()),
+    };
+    let parsed: LookupResponse = serde_json::from_reader(response.into_reader())?;
+    if parsed.status != "ok" {
+        return Err(parsed.error.map(|e| e.message).unwrap_or(parsed.status).into());
+    }
+
+    let mut suggestions = Vec::new();
+    for result in parsed.results {
+        for recording in result.recordings {
+            suggestions.push(Suggestion {
+                acoustid: result.id.clone(),
+                score: result.score,
+                recording_id: recording.id,
+                title: recording.title.unwrap_or_default(),
+                artist: recording.artists.iter().map(|a| a.name.as_str()).collect::<Vec<_>>().join(", "),
+            });
+        }
+    }
+    Ok(suggestions)
+}
+
+enum Message {
+    Fingerprinted(String, errors::Result<String>),
+    Suggestions(String, errors::Result<Vec<Suggestion>>),
+    Finished,
+}
+
+struct Untagged {
+    path: String,
+    status: String,
+    suggestions: Vec<Suggestion>,
+}
+
+#[derive(Default)]
+pub struct Identify {
+    open: bool,
+    endpoint: String,
+    pub api_key: String,
+    query: bool,
+    files: Vec<Untagged>,
+    pending: Option<Receiver<Message>>,
+}
+
+impl MyApp {
+    pub(crate) fn open_identify(&mut self) {
+        let identify = &mut self.identify;
+        if identify.endpoint.is_empty() {
+            identify.endpoint = DEFAULT_ENDPOINT.to_string();
+        }
+        identify.files = self
+            .dropped_files
+            .iter()
+            .filter(|path| {
+                let tag = id3::no_tag_ok(Tag::read_from_path(path)).ok().flatten();
+                let empty = |field: Field| tag.as_ref().and_then(|t| field.get(t)).is_none_or(|v| v.trim().is_empty());
+                empty(Field::Title) || empty(Field::Artist)
+            })
+            .map(|path| Untagged { path: path.clone(), status: String::new(), suggestions: Vec::new() })
+            .collect();
+        identify.open = true;
+    }
+
+    fn start_identify(&mut self) {
+        let identify = &mut self.identify;
+        let paths: Vec<String> = identify.files.iter().map(|f| f.path.clone()).collect();
+        let query = identify.query && !identify.api_key.trim().is_empty();
+        let (endpoint, api_key) = (identify.endpoint.clone(), identify.api_key.trim().to_string());
+
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            for path in paths {
+                let fingerprint = match compute(&path) {
+                    Ok(fingerprint) => fingerprint,
+                    Err(e) => {
+                        let _ = sender.send(Message::Fingerprinted(path, Err(e)));
+                        continue;
+                    }
+                };
+                let encoded = fingerprint.encoded();
+                let _ = sender.send(Message::Fingerprinted(path.clone(), Ok(encoded.clone())));
+
+                if query {
+                    let suggestions = lookup(&endpoint, &api_key, &encoded, fingerprint.duration_secs);
+                    let _ = sender.send(Message::Suggestions(path, suggestions));
+                    // AcoustID allows three requests a second
+                    std::thread::sleep(Duration::from_millis(350));
+                }
+            }
+            let _ = sender.send(Message::Finished);
+        });
+
+        for file in &mut identify.files {
+            file.status = t!("identify-queued");
+            file.suggestions.clear();
+        }
+        identify.pending = Some(receiver);
+    }
+
+    fn store_fingerprint(&mut self, path: &str, encoded: &str) -> errors::Result<()> {
+        let mut tag = id3::no_tag_ok(Tag::read_from_path(path))?.unwrap_or_default();
+        tag.remove_extended_text(Some(FINGERPRINT_DESCRIPTION), None);
+        tag.add_frame(ExtendedText {
+            description: FINGERPRINT_DESCRIPTION.to_string(),
+            value: encoded.to_string(),
+        });
+        self.write_tag(path, tag)
+    }
+
+    fn apply_suggestion(&mut self, path: &str, suggestion: &Suggestion) -> errors::Result<()> {
+        let mut tag = id3::no_tag_ok(Tag::read_from_path(path))?.unwrap_or_default();
+        Field::Title.set(&mut tag, &suggestion.title)?;
+        Field::Artist.set(&mut tag, &suggestion.artist)?;
+        tag.remove_extended_text(Some(ACOUSTID_DESCRIPTION), None);
+        tag.add_frame(ExtendedText {
+            description: ACOUSTID_DESCRIPTION.to_string(),
+            value: suggestion.acoustid.clone(),
+        });
+        tag.add_frame(UniqueFileIdentifier {
+            owner_identifier: "http://musicbrainz.org".to_string(),
+            identifier: suggestion.recording_id.clone().into_bytes(),
+        });
+        self.write_tag(path, tag)
+    }
+
+    fn poll_identify(&mut self, ctx: &egui::Context) {
+        let Some(receiver) = self.identify.pending.take() else {
+            return;
+        };
+        let mut finished = false;
+        while let Ok(message) = receiver.try_recv() {
+            match message {
+                Message::Fingerprinted(path, result) => {
+                    let status = match result.and_then(|fp| self.store_fingerprint(&path, &fp)) {
+                        Ok(()) => t!("identify-fingerprinted"),
+                        Err(e) => t!("identify-failed", error = e.to_string()),
+                    };
+                    if let Some(file) = self.identify.files.iter_mut().find(|f| f.path == path) {
+                        file.status = status;
+                    }
+                }
+                Message::Suggestions(path, result) => {
+                    if let Some(file) = self.identify.files.iter_mut().find(|f| f.path == path) {
+                        match result {
+                            Ok(suggestions) if suggestions.is_empty() => file.status = t!("identify-no-match"),
+                            Ok(suggestions) => {
+                                file.status = t!("identify-suggestions", count = suggestions.len());
+                                file.suggestions = suggestions;
+                            }
+                            Err(e) => file.status = t!("identify-lookup-failed", error = e.to_string()),
+                        }
+                    }
+                }
+                Message::Finished => finished = true,
+            }
+        }
+        if !finished {
+            self.identify.pending = Some(receiver);
+            ctx.request_repaint_after(Duration::from_millis(200));
+        }
+    }
+
+    pub(crate) fn identify_window(&mut self, ctx: &egui::Context) {
+        self.poll_identify(ctx);
+
+        let mut open = self.identify.open;
+        let mut start = false;
+        let mut apply = None;
+        egui::Window::new(t!("identify-title")).open(&mut open).show(ctx, |ui| {
+            let identify = &mut self.identify;
+            let busy = identify.pending.is_some();
+            ui.label(t!("identify-summary", count = identify.files.len()));
+            ui.checkbox(&mut identify.query, t!("identify-query"));
+            if identify.query {
+                egui::Grid::new("acoustid_settings").num_columns(2).show(ui, |ui| {
+                    ui.label(t!("online-endpoint"));
+                    ui.text_edit_singleline(&mut identify.endpoint);
+                    ui.end_row();
+                    ui.label(t!("identify-api-key"));
+                    ui.text_edit_singleline(&mut identify.api_key);
+                    ui.end_row();
+                });
+            }
+            start = ui
+                .add_enabled(!busy && !identify.files.is_empty(), egui::Button::new(t!("identify-start")))
+                .clicked();
+
+            ui.separator();
+            egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                for file in &identify.files {
+                    ui.horizontal(|ui| {
+                        ui.label(MyApp::get_file_name(&file.path));
+                        ui.label(&file.status);
+                    });
+                    for suggestion in &file.suggestions {
+                        ui.horizontal(|ui| {
+                            ui.add_space(15.0);
+                            ui.label(format!(
+                                "{:.0}%  {} - {}",
+                                suggestion.score * 100.0,
+                                suggestion.artist,
+                                suggestion.title
+                            ))
+                            .on_hover_text(&suggestion.recording_id);
+                            if ui.button(t!("identify-apply")).clicked() {
+                                apply = Some((file.path.clone(), suggestion.clone()));
+                            }
+                        });
+                    }
+                }
+            });
+        });
+        self.identify.open &= open;
+
+        if start {
+            self.start_identify();
+        }
+        if let Some((path, suggestion)) = apply {
+            match self.apply_suggestion(&path, &suggestion) {
+                Ok(()) => {
+                    if let Some(file) = self.identify.files.iter_mut().find(|f| f.path == path) {
+                        file.status = t!(
+                            "identify-tagged",
+                            artist = suggestion.artist.as_str(),
+                            title = suggestion.title.as_str()
+                        );
+                        file.suggestions.clear();
+                    }
+                }
+                Err(e) => self.report_error(Some(&path), t!("identify-apply-failed", error = e.to_string())),
+            }
+        }
+    }
+}
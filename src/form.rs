@@ -0,0 +1,154 @@
+use std::collections::HashMap;
+
+use eframe::egui;
+use id3::Tag;
+
+use crate::MyApp;
+use crate::errors;
+use crate::fields::{Field, Separator};
+use crate::genres;
+use crate::i18n::t;
+use crate::theme::Role;
+
+/// The fields in the main column of the Tags tab, in tab order.
+pub const MAIN_FIELDS: [Field; 6] =
+    [Field::Artist, Field::Title, Field::Album, Field::Genre, Field::AlbumArtist, Field::Composer];
+/// The fields shown beside them when there's room.
+pub const MORE_FIELDS: [Field; 4] = [Field::Year, Field::Track, Field::Disc, Field::Comment];
+
+/// Edits to the selected file's tag that haven't been written yet.
+#[derive(Default)]
+pub struct TagForm {
+    /// The typed text of each field that differs from the tag.
+    edits: HashMap<Field, String>,
+}
+
+impl TagForm {
+    pub fn reset(&mut self) {
+        self.edits.clear();
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        !self.edits.is_empty()
+    }
+}
+
+/// The id of a field's text box, for moving focus to it.
+pub fn field_id(field: Field) -> egui::Id {
+    egui::Id::new(("tag_form", field))
+}
+
+/// A field's values as the form shows them. Several values are only shown joined;
+/// they are edited in the list editor, since no separator is safe to split names on.
+fn saved_values(tag: &Tag, field: Field, separator: Separator) -> Vec<String> {
+    if field.multi_value_frame().is_some() {
+        field.values(tag, separator)
+    } else {
+        field.get(tag).into_iter().collect()
+    }
+}
+
+impl MyApp {
+    /// One labelled text box. Enter saves every changed field, Escape puts this one back.
+    pub(crate) fn form_field(&mut self, ui: &mut egui::Ui, field: Field) {
+        let Some(path) = self.selected_file.clone() else {
+            return;
+        };
+        let separator = self.separator;
+        let values = self.get_tag(&path).map(|tag| saved_values(tag, field, separator)).unwrap_or_default();
+        let saved = values.join("; ");
+        let several = values.len() > 1;
+        let multi = field.multi_value_frame().is_some();
+
+        ui.horizontal(|ui| {
+            ui.heading(t!("form-field-heading", field = field.label()));
+            // not focusable, so Tab goes straight to the next field
+            if multi
+                && ui
+                    .add(egui::Button::new(t!("form-list")).sense(egui::Sense::CLICK))
+                    .on_hover_text(t!("form-list-hint"))
+                    .clicked()
+            {
+                self.open_value_editor(field);
+            }
+        });
+
+        let mut text = self.form.edits.get(&field).cloned().unwrap_or_else(|| saved.clone());
+        let mut edit = egui::TextEdit::singleline(&mut text)
+            .id(field_id(field))
+            .interactive(!several)
+            .font(egui::FontId::proportional(16.0))
+            .text_color(self.theme.color(Role::Text))
+            .desired_width(f32::INFINITY);
+        if self.form.edits.contains_key(&field) {
+            edit = edit.background_color(self.theme.color(Role::Changed));
+        }
+        let mut response = ui.add(edit);
+        if several {
+            response = response.on_hover_text(t!("form-several-values"));
+        }
+
+        if response.changed() {
+            if text == saved {
+                self.form.edits.remove(&field);
+            } else {
+                self.form.edits.insert(field, text);
+            }
+        }
+        if response.lost_focus() {
+            if ui.input(|i| i.key_pressed(egui::Key::Escape)) {
+                self.form.edits.remove(&field);
+            } else if ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                self.save_form();
+            }
+        }
+        ui.add_space(10.0);
+    }
+
+    /// Save and revert buttons, while there's something to save.
+    pub(crate) fn form_buttons(&mut self, ui: &mut egui::Ui) {
+        if !self.form.is_dirty() {
+            return;
+        }
+        ui.horizontal(|ui| {
+            ui.label(t!("form-unsaved", count = self.form.edits.len()));
+            if ui.button(t!("form-save")).clicked() {
+                self.save_form();
+            }
+            if ui.button(t!("form-revert")).clicked() {
+                self.form.reset();
+            }
+        });
+        ui.add_space(10.0);
+    }
+
+    pub(crate) fn save_form(&mut self) {
+        if let Err(e) = self.commit_form() {
+            let file = self.selected_file.clone();
+            self.report_error(file.as_deref(), t!("form-save-failed", error = e.to_string()));
+        }
+    }
+
+    fn commit_form(&mut self) -> errors::Result<()> {
+        if self.form.edits.is_empty() {
+            return Ok(());
+        }
+        let path = self.selected_file.clone().ok_or_else(|| t!("form-no-file"))?;
+        let mut tag = id3::no_tag_ok(Tag::read_from_path(&path))?.unwrap_or_default();
+        for (field, text) in &self.form.edits {
+            if field.multi_value_frame().is_some() {
+                // the whole text is one value; several are added in the list editor
+                let value = match field {
+                    Field::Genre => genres::canonical(text.trim(), &self.genres.custom),
+                    _ => text.clone(),
+                };
+                field.set_values(&mut tag, &[value])?;
+            } else {
+                field.set(&mut tag, text)?;
+            }
+        }
+        self.write_tag(&path, tag)?;
+        self.form.reset();
+        Ok(())
+    }
+}
@@ -0,0 +1,268 @@
+use std::collections::BTreeMap;
+
+use eframe::egui;
+use id3::Tag;
+
+use crate::MyApp;
+use crate::errorlog::FileErrors;
+use crate::errors;
+use crate::fields::Field;
+use crate::i18n::t;
+
+/// The ID3v1 genres with Winamp's extensions, indexed by their numeric code.
+pub const GENRES: [&str; 192] = [
+    "Blues", "Classic Rock", "Country", "Dance", "Disco", "Funk", "Grunge", "Hip-Hop", "Jazz",
+    "Metal", "New Age", "Oldies", "Other", "Pop", "R&B", "Rap", "Reggae", "Rock", "Techno",
+    "Industrial", "Alternative", "Ska", "Death Metal", "Pranks", "Soundtrack", "Euro-Techno",
+    "Ambient", "Trip-Hop", "Vocal", "Jazz+Funk", "Fusion", "Trance", "Classical", "Instrumental",
+    "Acid", "House", "Game", "Sound Clip", "Gospel", "Noise", "Alternative Rock", "Bass", "Soul",
+    "Punk", "Space", "Meditative", "Instrumental Pop", "Instrumental Rock", "Ethnic", "Gothic",
+    "Darkwave", "Techno-Industrial", "Electronic", "Pop-Folk", "Eurodance", "Dream",
+    "Southern Rock", "Comedy", "Cult", "Gangsta", "Top 40", "Christian Rap", "Pop/Funk", "Jungle",
+    "Native US", "Cabaret", "New Wave", "Psychadelic", "Rave", "Showtunes", "Trailer", "Lo-Fi",
+    "Tribal", "Acid Punk", "Acid Jazz", "Polka", "Retro", "Musical", "Rock & Roll", "Hard Rock",
+    "Folk", "Folk-Rock", "National Folk", "Swing", "Fast Fusion", "Bebob", "Latin", "Revival",
+    "Celtic", "Bluegrass", "Avantgarde", "Gothic Rock", "Progressive Rock", "Psychedelic Rock",
+    "Symphonic Rock", "Slow Rock", "Big Band", "Chorus", "Easy Listening", "Acoustic", "Humour",
+    "Speech", "Chanson", "Opera", "Chamber Music", "Sonata", "Symphony", "Booty Bass", "Primus",
+    "Porn Groove", "Satire", "Slow Jam", "Club", "Tango", "Samba", "Folklore", "Ballad",
+    "Power Ballad", "Rhytmic Soul", "Freestyle", "Duet", "Punk Rock", "Drum Solo", "Acapella",
+    "Euro-House", "Dance Hall", "Goa", "Drum & Bass", "Club-House", "Hardcore", "Terror", "Indie",
+    "BritPop", "Afro-Punk", "Polsk Punk", "Beat", "Christian Gangsta Rap", "Heavy Metal",
+    "Black Metal", "Crossover", "Contemporary Christian", "Christian Rock", "Merengue", "Salsa",
+    "Thrash Metal", "Anime", "JPop", "SynthPop", "Abstract", "Art Rock", "Baroque", "Bhangra",
+    "Big Beat", "Breakbeat", "Chillout", "Downtempo", "Dub", "EBM", "Eclectic", "Electro",
+    "Electroclash", "Emo", "Experimental", "Garage", "Global", "IDM", "Illbient", "Industro-Goth",
+    "Jam Band", "Krautrock", "Leftfield", "Lounge", "Math Rock", "New Romantic", "Nu-Breakz",
+    "Post-Punk", "Post-Rock", "Psytrance", "Shoegaze", "Space Rock", "Trop Rock", "World Music",
+    "Neoclassical", "Audiobook", "Audio Theatre", "Neue Deutsche Welle", "Podcast", "Indie Rock",
+    "G-Funk", "Dubstep", "Garage Rock", "Psybient",
+];
+
+fn reference(code: &str) -> Option<String> {
+    match code {
+        "RX" => Some("Remix".to_string()),
+        "CR" => Some("Cover".to_string()),
+        _ => GENRES.get(code.parse::<usize>().ok()?).map(|g| g.to_string()),
+    }
+}
+
+/// Genre names from one TCON value. Numeric references like `(17)` or `17`
+/// become their names, and text after references (`(13)Pop`) refines the last one.
+pub fn resolve(value: &str) -> Vec<String> {
+    let value = value.trim();
+    if let Some(name) = reference(value) {
+        return vec![name];
+    }
+
+    let mut genres = Vec::new();
+    let mut rest = value;
+    while rest.starts_with('(') && !rest.starts_with("((") {
+        let Some(end) = rest.find(')') else {
+            break;
+        };
+        let Some(name) = reference(&rest[1..end]) else {
+            break;
+        };
+        genres.push(name);
+        rest = &rest[end + 1..];
+    }
+
+    // "((" escapes a literal parenthesis
+    let refinement = rest.strip_prefix('(').filter(|r| r.starts_with('(')).unwrap_or(rest).trim();
+    if !refinement.is_empty() {
+        genres.pop();
+        genres.push(refinement.to_string());
+    }
+    genres
+}
+
+/// A genre in the spelling of the table or the user's list, when it matches one ignoring case.
+pub fn canonical(name: &str, custom: &[String]) -> String {
+    GENRES
+        .iter()
+        .copied()
+        .chain(custom.iter().map(String::as_str))
+        .find(|g| g.eq_ignore_ascii_case(name))
+        .unwrap_or(name)
+        .to_string()
+}
+
+#[derive(Default)]
+pub struct GenreTool {
+    /// Genres the user added on top of the table.
+    pub custom: Vec<String>,
+    search: String,
+    consolidate_open: bool,
+    counts: BTreeMap<String, usize>,
+    selected: Vec<String>,
+    target: String,
+}
+
+impl MyApp {
+    /// A searchable list of known genres; returns the one clicked.
+    pub(crate) fn genre_picker(&mut self, ui: &mut egui::Ui) -> Option<String> {
+        let tool = &mut self.genres;
+        let mut picked = None;
+        ui.horizontal(|ui| {
+            ui.label(t!("genres-find"));
+            ui.text_edit_singleline(&mut tool.search);
+        });
+
+        let search = tool.search.trim().to_lowercase();
+        let known = tool.custom.iter().map(String::as_str).chain(GENRES);
+        egui::ScrollArea::vertical().id_salt("genre_picker").max_height(150.0).show(ui, |ui| {
+            for genre in known.filter(|g| g.to_lowercase().contains(&search)) {
+                if ui.selectable_label(false, genre).clicked() {
+                    picked = Some(genre.to_string());
+                }
+            }
+        });
+
+        let new = tool.search.trim().to_string();
+        if !new.is_empty()
+            && canonical(&new, &tool.custom) == new
+            && !GENRES.contains(&new.as_str())
+            && !tool.custom.contains(&new)
+            && ui.button(t!("genres-add", genre = new.as_str())).clicked()
+        {
+            tool.custom.push(new.clone());
+            tool.custom.sort();
+            picked = Some(new);
+        }
+        if picked.is_some() {
+            tool.search.clear();
+        }
+        picked
+    }
+
+    pub(crate) fn open_genre_consolidation(&mut self) {
+        let mut counts = BTreeMap::new();
+        for path in &self.dropped_files {
+            if let Ok(Some(tag)) = id3::no_tag_ok(Tag::read_from_path(path)) {
+                for genre in Field::Genre.values(&tag, self.separator) {
+                    *counts.entry(genre).or_default() += 1;
+                }
+            }
+        }
+        let tool = &mut self.genres;
+        tool.counts = counts;
+        tool.selected.clear();
+        tool.target.clear();
+        tool.consolidate_open = true;
+    }
+
+    /// Rewrites every selected genre spelling as the target, file by file.
+    fn consolidate_genres(&mut self) -> FileErrors {
+        let target = canonical(self.genres.target.trim(), &self.genres.custom);
+        let selected = self.genres.selected.clone();
+        let mut errors = Vec::new();
+        for path in self.dropped_files.clone() {
+            let result = (|| -> errors::Result<()> {
+                let Some(mut tag) = id3::no_tag_ok(Tag::read_from_path(&path))? else {
+                    return Ok(());
+                };
+                let before = Field::Genre.values(&tag, self.separator);
+                let mut after: Vec<String> = Vec::new();
+                for genre in &before {
+                    let genre = if selected.contains(genre) { &target } else { genre };
+                    if !after.contains(genre) {
+                        after.push(genre.clone());
+                    }
+                }
+                if after != before {
+                    Field::Genre.set_values(&mut tag, &after)?;
+                    self.write_tag(&path, tag)?;
+                }
+                Ok(())
+            })();
+            if let Err(e) = result {
+                errors.push((path.clone(), e.to_string()));
+            }
+        }
+        errors
+    }
+
+    pub(crate) fn genre_consolidation_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.genres.consolidate_open;
+        let mut merge = false;
+        egui::Window::new(t!("genres-title")).open(&mut open).show(ctx, |ui| {
+            ui.label(t!("genres-explanation"));
+            egui::ScrollArea::vertical().id_salt("genre_counts").max_height(250.0).show(ui, |ui| {
+                let tool = &mut self.genres;
+                for (genre, count) in &tool.counts {
+                    let mut checked = tool.selected.contains(genre);
+                    if ui.checkbox(&mut checked, format!("{} ({})", genre, count)).changed() {
+                        if checked {
+                            tool.selected.push(genre.clone());
+                            if tool.target.is_empty() {
+                                tool.target = canonical(genre, &tool.custom);
+                            }
+                        } else {
+                            tool.selected.retain(|g| g != genre);
+                        }
+                    }
+                }
+            });
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.label(t!("genres-merge-into"));
+                ui.text_edit_singleline(&mut self.genres.target);
+            });
+            if let Some(genre) = self.genre_picker(ui) {
+                self.genres.target = genre;
+            }
+            let ready = !self.genres.selected.is_empty() && !self.genres.target.trim().is_empty();
+            merge = ui.add_enabled(ready, egui::Button::new(t!("genres-merge"))).clicked();
+        });
+        self.genres.consolidate_open &= open;
+
+        if merge {
+            let errors = self.consolidate_genres();
+            self.report_file_errors("genres-failed", errors);
+            self.open_genre_consolidation();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numeric_references() {
+        assert_eq!(resolve("17"), ["Rock"]);
+        assert_eq!(resolve("(17)"), ["Rock"]);
+        assert_eq!(resolve("(17)(13)"), ["Rock", "Pop"]);
+        assert_eq!(resolve(" (191) "), ["Psybient"]);
+        assert_eq!(resolve("(RX)"), ["Remix"]);
+        assert_eq!(resolve("CR"), ["Cover"]);
+        assert!(resolve("").is_empty());
+    }
+
+    #[test]
+    fn text_refines_the_last_reference() {
+        assert_eq!(resolve("(13)Pop"), ["Pop"]);
+        assert_eq!(resolve("(17)(13)Synthpop"), ["Rock", "Synthpop"]);
+        assert_eq!(resolve("Shoegaze"), ["Shoegaze"]);
+        // a doubled parenthesis is a literal one
+        assert_eq!(resolve("((Live)"), ["(Live)"]);
+        assert_eq!(resolve("(17)((Live)"), ["(Live)"]);
+    }
+
+    #[test]
+    fn unknown_numbers_stay_as_text() {
+        assert_eq!(resolve("192"), ["192"]);
+        assert_eq!(resolve("(300)"), ["(300)"]);
+        assert_eq!(resolve("(-1)"), ["(-1)"]);
+    }
+
+    #[test]
+    fn canonical_spelling() {
+        let custom = ["Synthwave".to_string()];
+        assert_eq!(canonical("hip-hop", &custom), "Hip-Hop");
+        assert_eq!(canonical("SYNTHWAVE", &custom), "Synthwave");
+        assert_eq!(canonical("Vaporwave", &custom), "Vaporwave");
+    }
+}
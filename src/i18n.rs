@@ -0,0 +1,112 @@
+use std::sync::{LazyLock, RwLock};
+
+use eframe::egui;
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_bundle::{FluentArgs, FluentResource};
+use unic_langid::LanguageIdentifier;
+
+/// The shipped translations: language code, the language's own name, and its catalog.
+pub const LANGUAGES: [(&str, &str, &str); 2] = [
+    ("en", "English", include_str!("../locales/en.ftl")),
+    ("de", "Deutsch", include_str!("../locales/de.ftl")),
+];
+
+/// Where keys missing from a translation are looked up.
+const FALLBACK: &str = "en";
+
+struct Catalogs {
+    code: &'static str,
+    /// Whether the user picked `code` rather than it following the system.
+    chosen: bool,
+    current: FluentBundle<FluentResource>,
+    fallback: FluentBundle<FluentResource>,
+}
+
+static CATALOGS: LazyLock<RwLock<Catalogs>> = LazyLock::new(|| {
+    RwLock::new(Catalogs { code: FALLBACK, chosen: false, current: bundle(FALLBACK), fallback: bundle(FALLBACK) })
+});
+
+fn bundle(code: &str) -> FluentBundle<FluentResource> {
+    let (code, _, source) = LANGUAGES.iter().find(|(c, _, _)| *c == code).unwrap_or(&LANGUAGES[0]);
+    let id: LanguageIdentifier = code.parse().unwrap_or_default();
+    let mut bundle = FluentBundle::new_concurrent(vec![id]);
+    // egui doesn't understand the bidi isolation marks Fluent puts around arguments
+    bundle.set_use_isolating(false);
+    // a broken entry only loses that entry, which then falls back like a missing one
+    let resource = FluentResource::try_new(source.to_string()).unwrap_or_else(|(resource, _)| resource);
+    let _ = bundle.add_resource(resource);
+    bundle
+}
+
+/// Switches every string to the language with this code, or English if there's no such catalog.
+pub fn set_language(code: &str) {
+    switch(code, false);
+}
+
+/// Like `set_language`, but remembered as the user's choice over the system's language.
+pub fn choose_language(code: &str) {
+    switch(code, true);
+}
+
+fn switch(code: &str, chosen: bool) {
+    let code = LANGUAGES.iter().find(|(c, _, _)| *c == code).map_or(FALLBACK, |(c, _, _)| *c);
+    let mut catalogs = CATALOGS.write().unwrap_or_else(|e| e.into_inner());
+    catalogs.chosen = chosen;
+    if catalogs.code != code {
+        catalogs.code = code;
+        catalogs.current = bundle(code);
+    }
+}
+
+pub fn language() -> &'static str {
+    CATALOGS.read().unwrap_or_else(|e| e.into_inner()).code
+}
+
+/// The language the user picked, if they did.
+pub fn chosen_language() -> Option<&'static str> {
+    let catalogs = CATALOGS.read().unwrap_or_else(|e| e.into_inner());
+    catalogs.chosen.then_some(catalogs.code)
+}
+
+/// The operating system's language, if there's a catalog for it.
+pub fn system_language() -> &'static str {
+    let locale = sys_locale::get_locale().unwrap_or_default();
+    let lang = locale.split(['-', '_']).next().unwrap_or_default();
+    LANGUAGES.iter().find(|(c, _, _)| c.eq_ignore_ascii_case(lang)).map_or(FALLBACK, |(c, _, _)| *c)
+}
+
+/// Looks `key` up in the current language, then in English, and shows the key itself as a last resort.
+pub fn translate(key: &str, args: Option<&FluentArgs>) -> String {
+    let catalogs = CATALOGS.read().unwrap_or_else(|e| e.into_inner());
+    for bundle in [&catalogs.current, &catalogs.fallback] {
+        if let Some(pattern) = bundle.get_message(key).and_then(|m| m.value()) {
+            let mut errors = Vec::new();
+            return bundle.format_pattern(pattern, args, &mut errors).into_owned();
+        }
+    }
+    key.to_string()
+}
+
+/// The language menu: every shipped language, each under its own name.
+pub fn language_menu(ui: &mut egui::Ui) {
+    let current = language();
+    for (code, name, _) in LANGUAGES {
+        if ui.radio(current == code, name).clicked() {
+            choose_language(code);
+            ui.close_menu();
+        }
+    }
+}
+
+/// `t!("key")` or `t!("key", name = value, ...)`: the translated string for `key`.
+macro_rules! t {
+    ($key:expr) => {
+        $crate::i18n::translate($key, None)
+    };
+    ($key:expr, $($name:ident = $value:expr),+ $(,)?) => {{
+        let mut args = fluent_bundle::FluentArgs::new();
+        $(args.set(stringify!($name), $value);)+
+        $crate::i18n::translate($key, Some(&args))
+    }};
+}
+pub(crate) use t;
@@ -0,0 +1,458 @@
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::path::Path;
+
+use eframe::egui;
+use id3::frame::Picture;
+use id3::{Tag, TagLike, Version};
+
+use crate::MyApp;
+use crate::audio;
+use crate::errors;
+use crate::fields::Field;
+use crate::i18n::t;
+
+/// Artwork wider or taller than this is flagged and scaled down to it, unless the user picked another size.
+const DEFAULT_MAX_ART_SIZE: u32 = 1000;
+
+#[derive(Clone)]
+enum Fix {
+    SetField(Field, String),
+    /// Reading and writing the tag again folds repeated frames.
+    Rewrite,
+    ConvertVersion(Version),
+    ConvertV1,
+    /// Scale down to fit the given size, keeping the aspect ratio and image format.
+    ShrinkArt(u32),
+    /// Crop to the centre square.
+    CropArt,
+}
+
+impl Fix {
+    fn describe(&self) -> String {
+        match self {
+            Fix::SetField(field, value) if value.is_empty() => t!("fix-clear", field = field.label()),
+            Fix::SetField(field, value) => t!("fix-set", field = field.label(), value = value.as_str()),
+            Fix::Rewrite => t!("fix-rewrite"),
+            Fix::ConvertVersion(version) => t!("fix-convert", version = version.to_string()),
+            Fix::ConvertV1 => t!("fix-convert-v1"),
+            Fix::ShrinkArt(max) => t!("fix-shrink-art", max = *max),
+            Fix::CropArt => t!("fix-crop-art"),
+        }
+    }
+
+    /// Whether "Fix all" applies it. Cropping cuts away part of the picture, so it's only done when asked for.
+    fn automatic(&self) -> bool {
+        !matches!(self, Fix::CropArt)
+    }
+}
+
+struct Finding {
+    path: String,
+    problem: String,
+    fix: Option<Fix>,
+}
+
+#[derive(Default)]
+pub struct Linter {
+    open: bool,
+    findings: Vec<Finding>,
+    /// Largest artwork side in pixels; 0 means `DEFAULT_MAX_ART_SIZE`.
+    pub max_art_size: u32,
+}
+
+impl Linter {
+    pub fn max_art_size(&self) -> u32 {
+        if self.max_art_size == 0 { DEFAULT_MAX_ART_SIZE } else { self.max_art_size }
+    }
+}
+
+struct FileInfo {
+    path: String,
+    tag: Option<Tag>,
+    has_v1: bool,
+}
+
+/// The value most files agree on, if any file has one.
+fn majority<'a>(values: impl Iterator<Item = &'a str>) -> Option<String> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for value in values {
+        *counts.entry(value).or_default() += 1;
+    }
+    counts
+        .into_iter()
+        .max_by(|a, b| a.1.cmp(&b.1).then(b.0.cmp(a.0)))
+        .map(|(v, _)| v.to_string())
+}
+
+fn field(info: &FileInfo, field: Field) -> Option<String> {
+    info.tag.as_ref().and_then(|t| field.get(t)).filter(|v| !v.trim().is_empty())
+}
+
+fn folder(path: &str) -> &Path {
+    Path::new(path).parent().unwrap_or(Path::new(""))
+}
+
+fn check_file(info: &FileInfo, siblings: &[&FileInfo], max_art: u32, findings: &mut Vec<Finding>) {
+    let mut push = |problem: String, fix: Option<Fix>| {
+        findings.push(Finding { path: info.path.clone(), problem, fix });
+    };
+
+    let Some(tag) = &info.tag else {
+        if info.has_v1 {
+            push(t!("lint-only-v1"), Some(Fix::ConvertV1));
+        }
+        return;
+    };
+
+    if field(info, Field::Title).is_none() {
+        let stem = Path::new(&info.path).file_stem().and_then(|s| s.to_str()).unwrap_or_default();
+        push(t!("lint-missing", field = Field::Title.label()), Some(Fix::SetField(Field::Title, stem.to_string())));
+    }
+    for missing in [Field::Artist, Field::Album] {
+        if field(info, missing).is_none() {
+            let known: Vec<String> = siblings.iter().filter_map(|s| field(s, missing)).collect();
+            let guess = majority(known.iter().map(String::as_str))
+                .or_else(|| {
+                    (missing == Field::Album)
+                        .then(|| folder(&info.path).file_name()?.to_str().map(str::to_string))
+                        .flatten()
+                });
+            push(
+                t!("lint-missing", field = missing.label()),
+                guess.map(|g| Fix::SetField(missing, g)),
+            );
+        }
+    }
+
+    for f in Field::ALL {
+        if let Some(value) = f.get(tag)
+            && value.trim() != value
+        {
+            push(
+                t!("lint-whitespace", field = f.label()),
+                Some(Fix::SetField(f, value.trim().to_string())),
+            );
+        }
+    }
+
+    for picture in tag.pictures() {
+        let Ok((w, h)) = image::ImageReader::new(Cursor::new(&picture.data))
+            .with_guessed_format()
+            .map_err(|_| ())
+            .and_then(|r| r.into_dimensions().map_err(|_| ()))
+        else {
+            continue;
+        };
+        if w > max_art || h > max_art {
+            push(t!("lint-art-large", width = w, height = h), Some(Fix::ShrinkArt(max_art)));
+        }
+        if w != h {
+            push(t!("lint-art-not-square", width = w, height = h), Some(Fix::CropArt));
+        }
+    }
+
+    if let Ok(bytes) = audio::read_id3v2(&info.path) {
+        let frames = audio::id3v2_frames(&bytes);
+        let mut seen: Vec<(&str, &[u8])> = Vec::new();
+        let mut repeated: Vec<&str> = Vec::new();
+        for (id, body) in &frames {
+            let body = &bytes[body.clone()];
+            // text frames may only appear once; anything else only if it differs
+            let text = id.starts_with('T') && id != "TXXX" && id != "TXX";
+            if seen.iter().any(|(i, b)| i == id && (text || *b == body)) && !repeated.contains(&id.as_str()) {
+                repeated.push(id);
+            }
+            seen.push((id, body));
+        }
+        if !repeated.is_empty() {
+            push(t!("lint-duplicate-frames", frames = repeated.join(", ")), Some(Fix::Rewrite));
+        }
+    }
+}
+
+/// Checks that the files of one album agree with each other.
+fn check_album(files: &[&FileInfo], findings: &mut Vec<Finding>) {
+    for shared in [Field::AlbumArtist, Field::Year] {
+        let values: Vec<Option<String>> = files.iter().map(|f| field(f, shared)).collect();
+        if values.iter().all(|v| *v == values[0]) {
+            continue;
+        }
+        let Some(common) = majority(values.iter().flatten().map(String::as_str)) else {
+            continue;
+        };
+        for (file, value) in files.iter().zip(&values) {
+            if value.as_deref() != Some(common.as_str()) {
+                findings.push(Finding {
+                    path: file.path.clone(),
+                    problem: t!(
+                        "lint-differs",
+                        field = shared.label(),
+                        value = value.as_deref().unwrap_or("")
+                    ),
+                    fix: Some(Fix::SetField(shared, common.clone())),
+                });
+            }
+        }
+    }
+
+    let numbers: Vec<Option<u32>> = files.iter().map(|f| f.tag.as_ref().and_then(|t| t.track())).collect();
+    let mut sorted: Vec<u32> = numbers.iter().flatten().copied().collect();
+    sorted.sort();
+    let gaps_or_repeats = sorted.iter().enumerate().any(|(i, n)| *n != i as u32 + 1);
+    for (index, (file, number)) in files.iter().zip(&numbers).enumerate() {
+        let expected = format!("{}/{}", index + 1, files.len());
+        let problem = match number {
+            None => t!("lint-missing-track"),
+            Some(n) if gaps_or_repeats && *n != index as u32 + 1 => t!("lint-track-gaps", track = *n),
+            _ => continue,
+        };
+        findings.push(Finding {
+            path: file.path.clone(),
+            problem,
+            fix: Some(Fix::SetField(Field::Track, expected)),
+        });
+    }
+}
+
+fn check(paths: &[String], preferred: Version, max_art: u32) -> Vec<Finding> {
+    let infos: Vec<FileInfo> = paths
+        .iter()
+        .map(|path| FileInfo {
+            path: path.clone(),
+            tag: id3::no_tag_ok(Tag::read_from_path(path)).ok().flatten(),
+            has_v1: id3::v1::Tag::read_from_path(path).is_ok(),
+        })
+        .collect();
+
+    let mut findings = Vec::new();
+    for info in &infos {
+        let siblings: Vec<&FileInfo> = infos
+            .iter()
+            .filter(|s| s.path != info.path && folder(&s.path) == folder(&info.path))
+            .collect();
+        check_file(info, &siblings, max_art, &mut findings);
+    }
+
+    // an album is the files of one folder sharing an album title, in file name order
+    let mut albums: HashMap<(&Path, String), Vec<&FileInfo>> = HashMap::new();
+    for info in &infos {
+        if let Some(album) = field(info, Field::Album) {
+            albums.entry((folder(&info.path), album)).or_default().push(info);
+        }
+    }
+    let mut albums: Vec<_> = albums.into_values().filter(|files| files.len() > 1).collect();
+    albums.sort_by(|a, b| a[0].path.cmp(&b[0].path));
+    for files in &mut albums {
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        check_album(files, &mut findings);
+    }
+
+    let versions: Vec<Version> = infos.iter().filter_map(|i| Some(i.tag.as_ref()?.version())).collect();
+    if versions.iter().any(|v| *v != versions[0]) {
+        for info in &infos {
+            if let Some(tag) = &info.tag
+                && tag.version() != preferred
+            {
+                findings.push(Finding {
+                    path: info.path.clone(),
+                    problem: t!("lint-mixed-versions", version = tag.version().to_string()),
+                    fix: Some(Fix::ConvertVersion(preferred)),
+                });
+            }
+        }
+    }
+
+    findings.sort_by(|a, b| a.path.cmp(&b.path));
+    findings
+}
+
+/// Encodes `img` in `format`, dropping the alpha channel for formats that can't hold one.
+fn encode(img: &image::DynamicImage, format: image::ImageFormat) -> errors::Result<Vec<u8>> {
+    let mut out = Cursor::new(Vec::new());
+    if format == image::ImageFormat::Jpeg {
+        img.to_rgb8().write_to(&mut out, format)?;
+    } else {
+        img.write_to(&mut out, format)?;
+    }
+    Ok(out.into_inner())
+}
+
+/// Scales artwork down to fit within `max` on both sides, in its own format.
+/// Artwork that already fits is returned as it was.
+fn shrink_art(data: &[u8], max: u32) -> errors::Result<Vec<u8>> {
+    let format = image::guess_format(data)?;
+    let img = image::load_from_memory_with_format(data, format)?;
+    if img.width() <= max && img.height() <= max {
+        return Ok(data.to_vec());
+    }
+    encode(&img.resize(max, max, image::imageops::FilterType::Lanczos3), format)
+}
+
+/// Crops artwork to its centre square, in its own format.
+fn crop_art(data: &[u8]) -> errors::Result<Vec<u8>> {
+    let format = image::guess_format(data)?;
+    let img = image::load_from_memory_with_format(data, format)?;
+    if img.width() == img.height() {
+        return Ok(data.to_vec());
+    }
+    let side = img.width().min(img.height());
+    encode(&img.crop_imm((img.width() - side) / 2, (img.height() - side) / 2, side, side), format)
+}
+
+impl MyApp {
+    pub(crate) fn open_linter(&mut self) {
+        self.linter.findings = check(&self.dropped_files, self.version_settings.preferred(), self.linter.max_art_size());
+        self.linter.open = true;
+    }
+
+    fn apply_fix(&mut self, ctx: &egui::Context, path: &str, fix: &Fix) -> errors::Result<()> {
+        match fix {
+            Fix::SetField(Field::Title, value) => self.set_title(path, value.clone()),
+            Fix::SetField(Field::Artist, value) => self.set_artist(path, value.clone()),
+            Fix::SetField(Field::Album, value) => self.set_album(path, value.clone()),
+            Fix::SetField(Field::Genre, value) => self.set_genre(path, value.clone()),
+            Fix::SetField(field, value) => {
+                let mut tag = id3::no_tag_ok(Tag::read_from_path(path))?.unwrap_or_default();
+                field.set(&mut tag, value)?;
+                self.write_tag(path, tag)
+            }
+            Fix::Rewrite => {
+                let tag = Tag::read_from_path(path)?;
+                self.write_tag(path, tag)
+            }
+            Fix::ConvertVersion(version) => {
+                let tag = Tag::read_from_path(path)?;
+                self.write_tag_as(path, tag, *version)
+            }
+            Fix::ConvertV1 => {
+                let tag = Tag::from(id3::v1::Tag::read_from_path(path)?);
+                self.write_tag(path, tag)
+            }
+            Fix::ShrinkArt(_) | Fix::CropArt => {
+                let mut tag = Tag::read_from_path(path)?;
+                let pictures: Vec<Picture> = tag.pictures().cloned().collect();
+                tag.remove_all_pictures();
+                for picture in pictures {
+                    let data = match fix {
+                        Fix::ShrinkArt(max) => shrink_art(&picture.data, *max)?,
+                        _ => crop_art(&picture.data)?,
+                    };
+                    tag.add_frame(Picture { data, ..picture });
+                }
+                self.write_tag(path, tag)?;
+                self.album_art_cache.remove(path);
+                if self.selected_file.as_deref() == Some(path) {
+                    self.load_album_art_texture(ctx, path)?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    pub(crate) fn linter_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.linter.open;
+        let mut recheck = false;
+        let mut fixes: Vec<(String, Fix)> = Vec::new();
+        let mut max_art = self.linter.max_art_size();
+        egui::Window::new(t!("lint-title")).open(&mut open).show(ctx, |ui| {
+            let findings = &self.linter.findings;
+            ui.horizontal(|ui| {
+                ui.label(t!("lint-summary", problems = findings.len(), files = self.dropped_files.len()));
+                recheck = ui.button(t!("lint-check-again")).clicked();
+                let automatic = |f: &Finding| f.fix.as_ref().is_some_and(Fix::automatic);
+                if ui
+                    .add_enabled(findings.iter().any(automatic), egui::Button::new(t!("lint-fix-all")))
+                    .on_hover_text(t!("lint-fix-all-hint"))
+                    .clicked()
+                {
+                    fixes = findings
+                        .iter()
+                        .filter(|f| automatic(f))
+                        .filter_map(|f| Some((f.path.clone(), f.fix.clone()?)))
+                        .collect();
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.label(t!("lint-largest-art"));
+                ui.add(egui::DragValue::new(&mut max_art).range(100..=4000).suffix(" px"));
+            });
+
+            ui.separator();
+            egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                let mut last_path = None;
+                for finding in findings {
+                    if last_path != Some(&finding.path) {
+                        ui.label(egui::RichText::new(MyApp::get_file_name(&finding.path)).strong())
+                            .on_hover_text(&finding.path);
+                        last_path = Some(&finding.path);
+                    }
+                    ui.horizontal(|ui| {
+                        ui.add_space(15.0);
+                        ui.label(&finding.problem);
+                        if let Some(fix) = &finding.fix
+                            && ui.button(t!("lint-fix")).on_hover_text(fix.describe()).clicked()
+                        {
+                            fixes.push((finding.path.clone(), fix.clone()));
+                        }
+                    });
+                }
+            });
+        });
+        self.linter.open &= open;
+        self.linter.max_art_size = max_art;
+
+        if !fixes.is_empty() {
+            let mut errors = Vec::new();
+            for (path, fix) in &fixes {
+                if let Err(e) = self.apply_fix(ctx, path, fix) {
+                    errors.push((path.clone(), e.to_string()));
+                }
+            }
+            self.report_file_errors("lint-fix-failed", errors);
+            self.lyrics.reset();
+            self.chapters.reset();
+            recheck = true;
+        }
+        if recheck {
+            self.linter.findings = check(&self.dropped_files, self.version_settings.preferred(), self.linter.max_art_size());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn picture(width: u32, height: u32, format: image::ImageFormat) -> Vec<u8> {
+        encode(&image::DynamicImage::new_rgb8(width, height), format).unwrap()
+    }
+
+    fn size_and_format(data: &[u8]) -> (u32, u32, image::ImageFormat) {
+        let img = image::load_from_memory(data).unwrap();
+        (img.width(), img.height(), image::guess_format(data).unwrap())
+    }
+
+    #[test]
+    fn shrinking_keeps_aspect_ratio_and_format() {
+        let png = picture(400, 200, image::ImageFormat::Png);
+        assert_eq!(size_and_format(&shrink_art(&png, 100).unwrap()), (100, 50, image::ImageFormat::Png));
+
+        let jpeg = picture(150, 300, image::ImageFormat::Jpeg);
+        assert_eq!(size_and_format(&shrink_art(&jpeg, 100).unwrap()), (50, 100, image::ImageFormat::Jpeg));
+    }
+
+    #[test]
+    fn art_that_fits_is_untouched() {
+        let png = picture(80, 40, image::ImageFormat::Png);
+        assert_eq!(shrink_art(&png, 100).unwrap(), png);
+        let square = picture(60, 60, image::ImageFormat::Png);
+        assert_eq!(crop_art(&square).unwrap(), square);
+    }
+
+    #[test]
+    fn cropping_keeps_format() {
+        let png = picture(300, 200, image::ImageFormat::Png);
+        assert_eq!(size_and_format(&crop_art(&png).unwrap()), (200, 200, image::ImageFormat::Png));
+    }
+}
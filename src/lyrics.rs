@@ -0,0 +1,541 @@
+use eframe::egui;
+use id3::frame::{Lyrics, SynchronisedLyrics, SynchronisedLyricsType, TimestampFormat};
+use id3::{Tag, TagLike};
+
+use crate::MyApp;
+use crate::errors;
+use crate::i18n::t;
+use crate::theme::Role;
+
+const CONTENT_TYPES: [SynchronisedLyricsType; 7] = [
+    SynchronisedLyricsType::Other,
+    SynchronisedLyricsType::Lyrics,
+    SynchronisedLyricsType::Transcription,
+    SynchronisedLyricsType::PartName,
+    SynchronisedLyricsType::Event,
+    SynchronisedLyricsType::Chord,
+    SynchronisedLyricsType::Trivia,
+];
+
+/// Length of one MPEG audio frame in milliseconds. Layer III frames hold 1152
+/// samples for MPEG-1 and 576 for the lower MPEG-2/2.5 sample rates.
+pub fn mpeg_frame_ms(sample_rate: u32) -> f64 {
+    let samples = if sample_rate >= 32_000 { 1152.0 } else { 576.0 };
+    samples * 1000.0 / sample_rate.max(1) as f64
+}
+
+pub fn frames_to_ms(frames: u32, frame_ms: f64) -> u32 {
+    (frames as f64 * frame_ms).round() as u32
+}
+
+pub fn ms_to_frames(ms: u32, frame_ms: f64) -> u32 {
+    (ms as f64 / frame_ms).round() as u32
+}
+
+/// `mm:ss.xxx`, the way the SYLT table shows millisecond timestamps.
+pub fn format_ms(ms: u32) -> String {
+    format!("{:02}:{:02}.{:03}", ms / 60_000, (ms % 60_000) / 1000, ms % 1000)
+}
+
+/// Parses `mm:ss`, `mm:ss.x`, `mm:ss.xx`, `mm:ss.xxx` and `hh:mm:ss.xxx`.
+pub fn parse_ms(s: &str) -> Option<u32> {
+    let s = s.trim();
+    let (clock, fraction) = match s.split_once(['.', ',']) {
+        Some((clock, fraction)) => (clock, fraction),
+        None => (s, ""),
+    };
+
+    let mut seconds: u32 = 0;
+    for part in clock.split(':') {
+        seconds = seconds.checked_mul(60)?.checked_add(part.trim().parse().ok()?)?;
+    }
+
+    let ms = if fraction.is_empty() {
+        0
+    } else {
+        // ".5" is half a second, ".05" fifty milliseconds
+        let digits: String = fraction.chars().take(3).collect();
+        let value: u32 = digits.parse().ok()?;
+        value * 10u32.pow(3 - digits.len() as u32)
+    };
+
+    seconds.checked_mul(1000)?.checked_add(ms)
+}
+
+/// Reads an `.lrc` file into millisecond timestamps, sorted by time.
+///
+/// Lines may carry several timestamps (`[00:12.00][01:30.00]Chorus`), and an
+/// `[offset:+/-ms]` tag shifts everything as the format describes. Other ID
+/// tags like `[ar:...]` are skipped.
+pub fn parse_lrc(input: &str) -> Vec<(u32, String)> {
+    let mut offset: i64 = 0;
+    let mut lines = Vec::new();
+
+    for raw in input.lines() {
+        let mut rest = raw.trim();
+        let mut stamps = Vec::new();
+
+        while let Some(inner) = rest.strip_prefix('[') {
+            let Some(end) = inner.find(']') else {
+                break;
+            };
+            let tag = &inner[..end];
+            rest = &inner[end + 1..];
+
+            if let Some(value) = tag.strip_prefix("offset:") {
+                offset = value.trim().parse().unwrap_or(0);
+            } else if let Some(ms) = parse_ms(tag) {
+                stamps.push(ms);
+            }
+        }
+
+        for ms in stamps {
+            lines.push((ms, rest.trim().to_string()));
+        }
+    }
+
+    // a positive offset makes lyrics show up sooner
+    for (ms, _) in &mut lines {
+        *ms = (*ms as i64 - offset).max(0) as u32;
+    }
+    lines.sort_by_key(|(ms, _)| *ms);
+    lines
+}
+
+/// Writes millisecond timestamps as `.lrc`, with optional `[ti:]`/`[ar:]` headers.
+pub fn to_lrc(content: &[(u32, String)], title: Option<&str>, artist: Option<&str>) -> String {
+    let mut out = String::new();
+    if let Some(title) = title {
+        out.push_str(&format!("[ti:{}]\n", title));
+    }
+    if let Some(artist) = artist {
+        out.push_str(&format!("[ar:{}]\n", artist));
+    }
+    for (ms, text) in content {
+        let centis = (ms % 1000) / 10;
+        out.push_str(&format!("[{:02}:{:02}.{:02}]{}\n", ms / 60_000, (ms % 60_000) / 1000, centis, text));
+    }
+    out
+}
+
+/// One editable SYLT frame. Timestamps are kept as text while editing so a
+/// half-typed value doesn't get thrown away.
+#[derive(Clone, PartialEq)]
+pub struct SyltDraft {
+    pub lang: String,
+    pub description: String,
+    pub content_type: SynchronisedLyricsType,
+    pub timestamp_format: TimestampFormat,
+    pub rows: Vec<(String, String)>,
+}
+
+impl SyltDraft {
+    fn new() -> Self {
+        Self {
+            lang: "eng".to_string(),
+            description: String::new(),
+            content_type: SynchronisedLyricsType::Lyrics,
+            timestamp_format: TimestampFormat::Ms,
+            rows: Vec::new(),
+        }
+    }
+
+    fn from_frame(sylt: &SynchronisedLyrics) -> Self {
+        Self {
+            lang: sylt.lang.clone(),
+            description: sylt.description.clone(),
+            content_type: sylt.content_type,
+            timestamp_format: sylt.timestamp_format,
+            rows: sylt
+                .content
+                .iter()
+                .map(|(t, text)| (Self::format_time(*t, sylt.timestamp_format), text.clone()))
+                .collect(),
+        }
+    }
+
+    fn format_time(value: u32, format: TimestampFormat) -> String {
+        match format {
+            TimestampFormat::Ms => format_ms(value),
+            TimestampFormat::Mpeg => value.to_string(),
+        }
+    }
+
+    fn parse_time(value: &str, format: TimestampFormat) -> Option<u32> {
+        match format {
+            TimestampFormat::Ms => parse_ms(value),
+            TimestampFormat::Mpeg => value.trim().parse().ok(),
+        }
+    }
+
+    /// Rows converted to milliseconds, skipping any whose time doesn't parse.
+    fn content_ms(&self, frame_ms: f64) -> Vec<(u32, String)> {
+        self.rows
+            .iter()
+            .filter_map(|(time, text)| {
+                let value = Self::parse_time(time, self.timestamp_format)?;
+                let ms = match self.timestamp_format {
+                    TimestampFormat::Ms => value,
+                    TimestampFormat::Mpeg => frames_to_ms(value, frame_ms),
+                };
+                Some((ms, text.clone()))
+            })
+            .collect()
+    }
+
+    fn set_content_ms(&mut self, content: Vec<(u32, String)>, frame_ms: f64) {
+        self.rows = content
+            .into_iter()
+            .map(|(ms, text)| {
+                let value = match self.timestamp_format {
+                    TimestampFormat::Ms => ms,
+                    TimestampFormat::Mpeg => ms_to_frames(ms, frame_ms),
+                };
+                (Self::format_time(value, self.timestamp_format), text)
+            })
+            .collect();
+    }
+
+    /// Switches the timestamp unit, rewriting every row that parses.
+    fn convert_to(&mut self, format: TimestampFormat, frame_ms: f64) {
+        if format == self.timestamp_format {
+            return;
+        }
+        for (time, _) in &mut self.rows {
+            if let Some(value) = Self::parse_time(time, self.timestamp_format) {
+                let converted = match format {
+                    TimestampFormat::Ms => frames_to_ms(value, frame_ms),
+                    TimestampFormat::Mpeg => ms_to_frames(value, frame_ms),
+                };
+                *time = Self::format_time(converted, format);
+            }
+        }
+        self.timestamp_format = format;
+    }
+
+    fn to_frame(&self) -> Result<SynchronisedLyrics, String> {
+        let mut content = Vec::new();
+        for (time, text) in &self.rows {
+            let value = Self::parse_time(time, self.timestamp_format)
+                .ok_or_else(|| t!("lyrics-invalid-time", time = time.as_str()))?;
+            content.push((value, text.clone()));
+        }
+        content.sort_by_key(|(t, _)| *t);
+        Ok(SynchronisedLyrics {
+            lang: self.lang.clone(),
+            timestamp_format: self.timestamp_format,
+            content_type: self.content_type,
+            description: self.description.clone(),
+            content,
+        })
+    }
+}
+
+/// Edit buffers for the lyrics tab, loaded from the selected file's tag.
+#[derive(Default)]
+pub struct LyricsEditor {
+    loaded_for: Option<String>,
+    uslt: Vec<Lyrics>,
+    sylt: Vec<SyltDraft>,
+    /// The buffers as they were last loaded or saved.
+    saved: (Vec<Lyrics>, Vec<SyltDraft>),
+    frame_ms: f64,
+}
+
+impl LyricsEditor {
+    fn load(&mut self, path: &str, tag: Option<&Tag>) {
+        self.loaded_for = Some(path.to_string());
+        self.uslt = tag.map(|t| t.lyrics().cloned().collect()).unwrap_or_default();
+        self.sylt = tag
+            .map(|t| t.synchronised_lyrics().map(SyltDraft::from_frame).collect())
+            .unwrap_or_default();
+        self.mark_saved();
+        let sample_rate = MyApp::get_sample_rate(path).ok().flatten().unwrap_or(44_100);
+        self.frame_ms = mpeg_frame_ms(sample_rate);
+    }
+
+    /// Forget the buffers so the next frame reloads them from disk.
+    pub fn reset(&mut self) {
+        self.loaded_for = None;
+    }
+
+    /// Whether the buffers hold a file's contents, possibly edited.
+    pub fn is_loaded(&self) -> bool {
+        self.loaded_for.is_some()
+    }
+
+    /// Whether the buffers differ from what's in the file.
+    pub fn is_dirty(&self) -> bool {
+        self.is_loaded() && (self.uslt != self.saved.0 || self.sylt != self.saved.1)
+    }
+
+    fn mark_saved(&mut self) {
+        self.saved = (self.uslt.clone(), self.sylt.clone());
+    }
+}
+
+impl MyApp {
+    fn save_lyrics(&mut self, path: &str) -> errors::Result<()> {
+        let mut tag = id3::no_tag_ok(Tag::read_from_path(path))?.unwrap_or_default();
+        tag.remove_all_lyrics();
+        tag.remove_all_synchronised_lyrics();
+        for lyrics in &self.lyrics.uslt {
+            tag.add_frame(lyrics.clone());
+        }
+        for draft in &self.lyrics.sylt {
+            tag.add_frame(draft.to_frame()?);
+        }
+        self.write_tag(path, tag)?;
+        self.lyrics.mark_saved();
+        Ok(())
+    }
+
+    fn import_lrc(&mut self, index: usize) -> errors::Result<()> {
+        if let Some(lrc_path) = rfd::FileDialog::new().add_filter(t!("filter-lrc"), &["lrc"]).pick_file() {
+            let content = parse_lrc(&std::fs::read_to_string(lrc_path)?);
+            let frame_ms = self.lyrics.frame_ms;
+            if let Some(draft) = self.lyrics.sylt.get_mut(index) {
+                draft.set_content_ms(content, frame_ms);
+            }
+        }
+        Ok(())
+    }
+
+    fn export_lrc(&mut self, path: &str, index: usize) -> errors::Result<()> {
+        let Some(draft) = self.lyrics.sylt.get(index) else {
+            return Ok(());
+        };
+        let content = draft.content_ms(self.lyrics.frame_ms);
+        let title = self.get_title(path)?;
+        let artist = self.get_artist(path)?;
+        let stem = std::path::Path::new(path)
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("lyrics");
+
+        if let Some(save_path) = rfd::FileDialog::new()
+            .add_filter(t!("filter-lrc"), &["lrc"])
+            .set_file_name(format!("{}.lrc", stem))
+            .save_file()
+        {
+            std::fs::write(save_path, to_lrc(&content, title.as_deref(), artist.as_deref()))?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn lyrics_tab(&mut self, ui: &mut egui::Ui) {
+        let Some(path) = self.selected_file.clone() else {
+            return;
+        };
+        if self.lyrics.loaded_for.as_deref() != Some(path.as_str()) {
+            let tag = self.get_tag(&path).ok().cloned();
+            self.lyrics.load(&path, tag.as_ref());
+        }
+
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            ui.horizontal(|ui| {
+                ui.heading(t!("lyrics-heading"));
+                if ui.button(t!("lyrics-add")).clicked() {
+                    self.lyrics.uslt.push(Lyrics {
+                        lang: "eng".to_string(),
+                        description: String::new(),
+                        text: String::new(),
+                    });
+                }
+            });
+
+            let mut uslt_to_remove = None;
+            for (i, lyrics) in self.lyrics.uslt.iter_mut().enumerate() {
+                ui.horizontal(|ui| {
+                    ui.label(t!("lyrics-language"));
+                    ui.add(egui::TextEdit::singleline(&mut lyrics.lang).char_limit(3).desired_width(40.0));
+                    ui.label(t!("lyrics-description"));
+                    ui.add(egui::TextEdit::singleline(&mut lyrics.description).desired_width(120.0));
+                    if ui.button(t!("lyrics-remove")).clicked() {
+                        uslt_to_remove = Some(i);
+                    }
+                });
+                ui.add(egui::TextEdit::multiline(&mut lyrics.text).desired_rows(6).desired_width(f32::INFINITY));
+                ui.add_space(5.0);
+            }
+            if let Some(i) = uslt_to_remove {
+                self.lyrics.uslt.remove(i);
+            }
+
+            ui.add_space(10.0);
+
+            ui.horizontal(|ui| {
+                ui.heading(t!("lyrics-synced-heading"));
+                if ui.button(t!("lyrics-add")).clicked() {
+                    self.lyrics.sylt.push(SyltDraft::new());
+                }
+            });
+
+            let frame_ms = self.lyrics.frame_ms;
+            let position = self
+                .player
+                .as_ref()
+                .filter(|p| p.path() == path)
+                .map(|p| (p.position_seconds() * 1000.0) as u32);
+            let mut sylt_to_remove = None;
+            let mut lrc_import = None;
+            let mut lrc_export = None;
+
+            for (i, draft) in self.lyrics.sylt.iter_mut().enumerate() {
+                ui.horizontal(|ui| {
+                    ui.label(t!("lyrics-language"));
+                    ui.add(egui::TextEdit::singleline(&mut draft.lang).char_limit(3).desired_width(40.0));
+                    ui.label(t!("lyrics-description"));
+                    ui.add(egui::TextEdit::singleline(&mut draft.description).desired_width(120.0));
+                });
+                ui.horizontal(|ui| {
+                    egui::ComboBox::from_id_salt(("sylt_type", i))
+                        .selected_text(draft.content_type.to_string())
+                        .show_ui(ui, |ui| {
+                            for kind in CONTENT_TYPES {
+                                ui.selectable_value(&mut draft.content_type, kind, kind.to_string());
+                            }
+                        });
+
+                    let mut format = draft.timestamp_format;
+                    egui::ComboBox::from_id_salt(("sylt_format", i))
+                        .selected_text(format.to_string())
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut format, TimestampFormat::Ms, TimestampFormat::Ms.to_string());
+                            ui.selectable_value(&mut format, TimestampFormat::Mpeg, TimestampFormat::Mpeg.to_string());
+                        });
+                    draft.convert_to(format, frame_ms);
+
+                    if ui.button(t!("lyrics-import-lrc")).clicked() {
+                        lrc_import = Some(i);
+                    }
+                    if ui.button(t!("lyrics-export-lrc")).clicked() {
+                        lrc_export = Some(i);
+                    }
+                    if ui.button(t!("lyrics-remove")).clicked() {
+                        sylt_to_remove = Some(i);
+                    }
+                });
+
+                let mut row_to_remove = None;
+                egui::Grid::new(("sylt_rows", i)).num_columns(3).striped(true).show(ui, |ui| {
+                    for (row, (time, text)) in draft.rows.iter_mut().enumerate() {
+                        let valid = SyltDraft::parse_time(time, draft.timestamp_format).is_some();
+                        ui.add(
+                            egui::TextEdit::singleline(time)
+                                .desired_width(80.0)
+                                .text_color_opt((!valid).then_some(self.theme.color(Role::Error))),
+                        );
+                        ui.add(egui::TextEdit::singleline(text).desired_width(250.0));
+                        ui.horizontal(|ui| {
+                            // stamp the row with wherever the preview currently is
+                            if let Some(ms) = position
+                                && ui.button(t!("lyrics-now")).clicked()
+                            {
+                                let value = match draft.timestamp_format {
+                                    TimestampFormat::Ms => ms,
+                                    TimestampFormat::Mpeg => ms_to_frames(ms, frame_ms),
+                                };
+                                *time = SyltDraft::format_time(value, draft.timestamp_format);
+                            }
+                            if ui.button("x").clicked() {
+                                row_to_remove = Some(row);
+                            }
+                        });
+                        ui.end_row();
+                    }
+                });
+                if let Some(row) = row_to_remove {
+                    draft.rows.remove(row);
+                }
+                if ui.button(t!("lyrics-add-line")).clicked() {
+                    let last = draft
+                        .rows
+                        .last()
+                        .and_then(|(t, _)| SyltDraft::parse_time(t, draft.timestamp_format))
+                        .unwrap_or(0);
+                    draft.rows.push((SyltDraft::format_time(last, draft.timestamp_format), String::new()));
+                }
+                ui.add_space(5.0);
+            }
+
+            if let Some(i) = sylt_to_remove {
+                self.lyrics.sylt.remove(i);
+            }
+            if let Some(i) = lrc_import
+                && let Err(e) = self.import_lrc(i)
+            {
+                self.report_error(Some(&path), t!("lyrics-import-failed", error = e.to_string()));
+            }
+            if let Some(i) = lrc_export
+                && let Err(e) = self.export_lrc(&path, i)
+            {
+                self.report_error(Some(&path), t!("lyrics-export-failed", error = e.to_string()));
+            }
+
+            ui.add_space(10.0);
+            ui.horizontal(|ui| {
+                if ui.button(t!("lyrics-save")).clicked() {
+                    if let Err(e) = self.save_lyrics(&path) {
+                        self.report_error(Some(&path), t!("lyrics-save-failed", error = e.to_string()));
+                    } else {
+                        self.alert_message.clear();
+                    }
+                }
+                if ui.button(t!("form-revert")).clicked() {
+                    self.lyrics.reset();
+                }
+            });
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_every_fraction_width() {
+        assert_eq!(parse_ms("01:02"), Some(62_000));
+        assert_eq!(parse_ms("01:02.5"), Some(62_500));
+        assert_eq!(parse_ms("01:02.05"), Some(62_050));
+        assert_eq!(parse_ms("01:02.345"), Some(62_345));
+        assert_eq!(parse_ms("1:02:03.004"), Some(3_723_004));
+        assert_eq!(parse_ms("ar:Someone"), None);
+    }
+
+    #[test]
+    fn lrc_lines_with_offset_and_repeats() {
+        let input = "[ar:Someone]\n[offset:+500]\n[00:12.00][01:30.50]Chorus\n[00:00.20]Intro\n[00:05.123]Verse\n";
+        let lines = parse_lrc(input);
+        let times: Vec<u32> = lines.iter().map(|(ms, _)| *ms).collect();
+        let texts: Vec<&str> = lines.iter().map(|(_, text)| text.as_str()).collect();
+        assert_eq!(times, [0, 4_623, 11_500, 90_000]);
+        assert_eq!(texts, ["Intro", "Verse", "Chorus", "Chorus"]);
+
+        let lines = parse_lrc("[offset:-250]\n[00:01.00]Late\n");
+        assert_eq!(lines, [(1_250, "Late".to_string())]);
+    }
+
+    #[test]
+    fn lrc_round_trip() {
+        let content = vec![(1_230, "One".to_string()), (62_340, "Two".to_string())];
+        let lrc = to_lrc(&content, Some("Title"), Some("Artist"));
+        assert_eq!(lrc, "[ti:Title]\n[ar:Artist]\n[00:01.23]One\n[01:02.34]Two\n");
+        assert_eq!(parse_lrc(&lrc), content);
+    }
+
+    #[test]
+    fn ms_survive_a_trip_through_frames() {
+        for sample_rate in [44_100, 48_000, 22_050] {
+            let frame_ms = mpeg_frame_ms(sample_rate);
+            for ms in [0, 999, 61_234, 3_600_000] {
+                let back = frames_to_ms(ms_to_frames(ms, frame_ms), frame_ms);
+                assert!((back as f64 - ms as f64).abs() <= frame_ms / 2.0 + 1.0, "{ms} came back as {back}");
+            }
+            assert_eq!(ms_to_frames(frames_to_ms(1_000, frame_ms), frame_ms), 1_000);
+        }
+        assert!((mpeg_frame_ms(44_100) - 26.122).abs() < 0.001);
+        assert!((mpeg_frame_ms(22_050) - 26.122).abs() < 0.001);
+    }
+}
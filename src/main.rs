@@ -1,7 +1,38 @@
+mod audio;
+mod chapters;
+mod commands;
+mod coverart;
+mod cue;
+mod duplicates;
+mod errorlog;
+mod errors;
+mod fields;
+mod fingerprint;
+mod form;
+mod genres;
+mod i18n;
+mod lint;
+mod lyrics;
+mod mojibake;
+mod multivalue;
+mod musicbrainz;
+mod playback;
+mod safewrite;
+mod session;
+mod spreadsheet;
+mod tagblocks;
+mod theme;
+mod versions;
+mod watcher;
+
 use eframe::{egui::{self}, App, Frame, NativeOptions};
 use id3::{Tag, TagLike, Version};
 use id3::frame::{Picture, PictureType};
 use symphonia::default::{get_probe};
+use playback::Player;
+use fields::Field;
+use theme::Role;
+use i18n::t;
 use symphonia::core::{
     codecs::CodecParameters,
     formats::FormatOptions,
@@ -10,72 +41,156 @@ use symphonia::core::{
     probe::Hint,
 };
 use std::collections::HashMap;
+use std::time::Duration;
 use std::path::Path;
 use std::fs::File;
 
+/// Raw image bytes and their mime type.
+type AlbumArt = (Vec<u8>, String);
+
+/// A file's bytes before and after its audio, which is everything a write can change.
+type TagRegions = (Vec<u8>, Vec<u8>);
+
+/// How many tag writes can be undone.
+const MAX_UNDO: usize = 50;
+
+#[derive(Default)]
 struct MyApp {
     cached_tag: Option<Tag>,
     dropped_files: Vec<String>,
     selected_file: Option<String>,
     alert_message: String,
+    log: errorlog::ErrorLog,
     selected_album_art: Option<egui::TextureId>,
     album_art_cache: HashMap<String, egui::TextureId>,
     album_art_ready: bool,
-    editing_artist: bool,
-    edited_artist: String,
-    editing_title: bool,
-    edited_title: String,
-    editing_album: bool,
-    edited_album: String,
-    editing_genre: bool,
-    edited_genre: String,
+    form: form::TagForm,
+    /// What the selected file's audio stream looks like, probed once per file.
+    audio_info: Option<(String, AudioInfo)>,
+    player: Option<Player>,
+    seek_preview: Option<f64>,
+    tab: Tab,
+    lyrics: lyrics::LyricsEditor,
+    chapters: chapters::ChapterEditor,
+    spreadsheet: spreadsheet::SpreadsheetTool,
+    cue_import: cue::CueImport,
+    musicbrainz: musicbrainz::MusicBrainzLookup,
+    cover_search: coverart::CoverSearch,
+    identify: fingerprint::Identify,
+    duplicates: duplicates::DuplicateFinder,
+    linter: lint::Linter,
+    mojibake: mojibake::MojibakeRepair,
+    value_editor: multivalue::ValueListEditor,
+    genres: genres::GenreTool,
+    /// How multi-valued fields are joined when a tag is written as ID3v2.3.
+    separator: fields::Separator,
+    version_settings: versions::VersionSettings,
+    version_converter: versions::VersionConverter,
+    tag_blocks: tagblocks::TagBlocksTool,
+    write_settings: safewrite::WriteSettings,
+    watcher: watcher::FileWatcher,
+    recent: session::Recent,
+    /// Each written file with its tags as they were before, newest last.
+    undo: Vec<(String, TagRegions)>,
+    commands: commands::CommandState,
+    theme: theme::ThemeSettings,
+}
+
+/// The stream properties shown in the info column.
+#[derive(Clone, Copy, Default)]
+struct AudioInfo {
+    bitrate: Option<u32>,
+    sample_rate: Option<u32>,
+    duration: Option<f64>,
+}
+
+#[derive(Clone, Copy, Default, PartialEq)]
+enum Tab {
+    #[default]
+    Tags,
+    Lyrics,
+    Chapters,
 }
 
 impl MyApp {
-    fn pick_and_set_album_art(&mut self, ctx: &egui::Context) -> Result<(), Box<dyn std::error::Error>> {
+    fn pick_and_set_album_art(&mut self, ctx: &egui::Context) -> errors::Result<()> {
         if let Some(path) = rfd::FileDialog::new()
-            .add_filter("Image", &["png", "jpg", "jpeg"])
+            .add_filter(t!("filter-image"), &["png", "jpg", "jpeg"])
             .pick_file()
         {
-            let image = image::open(&path)?.to_rgba8();
-            let size = [image.width() as usize, image.height() as usize];
-            let pixels = image.into_raw();
+            let img_bytes = std::fs::read(&path)?;
+            self.set_album_art(ctx, img_bytes)?;
+        }
+        Ok(())
+    }
 
-            let color_image = egui::ColorImage::from_rgba_unmultiplied(size, &pixels);
+    /// Replaces the selected file's pictures with `img_bytes` as the front cover.
+    fn set_album_art(&mut self, ctx: &egui::Context, img_bytes: Vec<u8>) -> errors::Result<()> {
+        match self.selected_file.clone() {
+            Some(path) => self.set_album_art_of(ctx, &path, img_bytes),
+            None => Ok(()),
+        }
+    }
 
-            let texture_id = ctx
-                .tex_manager()
-                .write()
-                .alloc("album_art".into(), color_image.into(), Default::default());
+    /// Replaces `path`'s pictures with `img_bytes` as the front cover, showing it if `path` is still selected.
+    fn set_album_art_of(&mut self, ctx: &egui::Context, path: &str, img_bytes: Vec<u8>) -> errors::Result<()> {
+        let image = image::load_from_memory(&img_bytes)?.to_rgba8();
+        let size = [image.width() as usize, image.height() as usize];
+        let pixels = image.into_raw();
 
+        let color_image = egui::ColorImage::from_rgba_unmultiplied(size, &pixels);
 
-            self.selected_album_art = Some(texture_id);
+        let mut tag = Tag::read_from_path(path)?;
+
+        tag.remove_all_pictures();
 
-            if let Some(song_path) = &self.selected_file {
-                self.album_art_cache.insert(song_path.clone(), texture_id);
-                let mut tag = Tag::read_from_path(song_path)?;
+        let mime_type = match image::guess_format(&img_bytes) {
+            Ok(image::ImageFormat::Png) => "image/png",
+            _ => "image/jpeg",
+        };
 
-                tag.remove_all_pictures();
+        let picture = Picture {
+            mime_type: mime_type.to_string(),
+            picture_type: PictureType::CoverFront,
+            description: "".to_string(),
+            data: img_bytes,
+        };
 
-                let img_bytes = std::fs::read(&path)?;
+        let frame = id3::Frame::with_content("APIC", id3::Content::Picture(picture));
+        tag.add_frame(frame);
+        self.write_tag(path, tag)?;
 
-                let picture = Picture {
-                    mime_type: "image/png".to_string(),
-                    picture_type: PictureType::CoverFront,
-                    description: "".to_string(),
-                    data: img_bytes,
-                };
+        let texture_id = ctx
+            .tex_manager()
+            .write()
+            .alloc("album_art".into(), color_image.into(), Default::default());
+        self.album_art_cache.insert(path.to_string(), texture_id);
+        if self.selected_file.as_deref() == Some(path) {
+            self.selected_album_art = Some(texture_id);
+            self.album_art_ready = true;
+        }
 
-                let frame = id3::Frame::with_content("APIC", id3::Content::Picture(picture));
-                tag.add_frame(frame);
-                tag.write_to_path(song_path, Version::Id3v24)?;
+        Ok(())
+    }
 
-                self.cached_tag = Some(tag);
-            }
+    /// Makes `path` the selected file, dropping everything loaded for the previous one.
+    fn select_file(&mut self, ctx: &egui::Context, path: String) {
+        self.selected_file = Some(path.clone());
+        self.selected_album_art = None;
+        self.player = None;
+        self.lyrics.reset();
+        self.chapters.reset();
+        self.value_editor = Default::default();
+        self.form.reset();
+        self.cached_tag = None;
+        self.show_album_art(ctx, &path);
+    }
 
-            Ok(())
-        } else {
-            Ok(())
+    /// `load_album_art_texture`, logging what went wrong instead of returning it.
+    fn show_album_art(&mut self, ctx: &egui::Context, path: &str) {
+        if let Err(e) = self.load_album_art_texture(ctx, path) {
+            self.album_art_ready = true;
+            self.report_error(Some(path), t!("art-load-failed", error = e.to_string()));
         }
     }
 
@@ -86,63 +201,71 @@ impl MyApp {
             .unwrap_or("Unknown")
     }
 
-    fn get_tag(&mut self, path: &str) -> Result<&Tag, Box<dyn std::error::Error>> {
+    fn get_tag(&mut self, path: &str) -> errors::Result<&Tag> {
         if self.cached_tag.is_none() {
-            let tag = Tag::read_from_path(path)?;
+            let tag = id3::no_tag_ok(Tag::read_from_path(path))?.unwrap_or_default();
             self.cached_tag = Some(tag);
         }
         Ok(self.cached_tag.as_ref().unwrap())
     }
 
-    fn get_title(&mut self, path: &str) -> Result<Option<String>, Box<dyn std::error::Error>> {
+    /// Writes `tag` to `path` in the version the settings pick, refreshing the cached tag if it's the selected file. Every edit goes through here.
+    fn write_tag(&mut self, path: &str, tag: Tag) -> errors::Result<()> {
+        let version = self.version_settings.for_file(path);
+        self.write_tag_as(path, tag, version)
+    }
+
+    /// Like `write_tag`, but in the given ID3 version.
+    fn write_tag_as(&mut self, path: &str, mut tag: Tag, version: Version) -> errors::Result<()> {
+        versions::convert(&mut tag, version);
+        if version != Version::Id3v24 {
+            self.separator.apply(&mut tag);
+        }
+        self.replace_file(path, |temp| {
+            tag.write_to_path(temp, version)?;
+            Tag::read_from_path(temp)?;
+            Ok(())
+        })?;
+        if self.selected_file.as_deref() == Some(path) {
+            self.cached_tag = Some(tag);
+        }
+        Ok(())
+    }
+
+    fn get_title(&mut self, path: &str) -> errors::Result<Option<String>> {
         Ok(self.get_tag(path)?.title().map(|s| s.to_string()))
     }
 
-    fn set_title(&mut self, path: &str, title: String) -> Result<(), Box<dyn std::error::Error>> {
+    fn set_title(&mut self, path: &str, title: String) -> errors::Result<()> {
         let mut tag = Tag::read_from_path(path)?;
         tag.set_title(title);
-        tag.write_to_path(path, Version::Id3v24)?;
-        self.cached_tag = Some(tag);
-        Ok(())
+        self.write_tag(path, tag)
     }
 
-    fn get_artist(&mut self, path: &str) -> Result<Option<String>, Box<dyn std::error::Error>> {
-        if let Some(artist_str) = self.get_tag(path)?.artist() {
-            let clean_str = artist_str.replace('\0', ";").replace('\r', ";").replace('\n', ";").replace(',', ";");
-            let artists: Vec<&str> = clean_str
-                .split(';')
-                .map(|s| s.trim())
-                .filter(|s| !s.is_empty())
-                .collect();
-            let joined = artists.join(", ");
-            Ok(Some(joined))
-        } else {
-            Ok(None)
-        }
+    fn get_artist(&mut self, path: &str) -> errors::Result<Option<String>> {
+        let separator = self.separator;
+        let artists = Field::Artist.values(self.get_tag(path)?, separator);
+        Ok((!artists.is_empty()).then(|| artists.join("; ")))
     }
 
 
-    fn set_artist(&mut self, path: &str, artist: String) -> Result<(), Box<dyn std::error::Error>> {
+    fn set_artist(&mut self, path: &str, artist: String) -> errors::Result<()> {
         let mut tag = Tag::read_from_path(path)?;
         tag.set_artist(artist);
-        tag.write_to_path(path, Version::Id3v24)?;
-        self.cached_tag = Some(tag);
-        Ok(())
+        self.write_tag(path, tag)
     }
 
-    fn get_album(&mut self, path: &str) -> Result<Option<String>, Box<dyn std::error::Error>> {
+    fn get_album(&mut self, path: &str) -> errors::Result<Option<String>> {
         Ok(self.get_tag(path)?.album().map(|s| s.to_string()))
     }
 
-    fn set_album(&mut self, path: &str, album: String) -> Result<(), Box<dyn std::error::Error>> {
+    fn set_album(&mut self, path: &str, album: String) -> errors::Result<()> {
         let mut tag = Tag::read_from_path(path)?;
         tag.set_album(album);
-        tag.write_to_path(path, Version::Id3v24)?;
-        self.cached_tag = Some(tag);
-        Ok(())
+        self.write_tag(path, tag)
     }
 
-    fn get_album_art(&mut self, path: &str) -> Result<Option<(Vec<u8>, String)>, Box<dyn std::error::Error>> {
+    fn get_album_art(&mut self, path: &str) -> errors::Result<Option<AlbumArt>> {
         let tag = self.get_tag(path)?;
         if let Some(picture) = tag.pictures().next() {
             Ok(Some((picture.data.clone(), picture.mime_type.clone())))
@@ -151,7 +274,7 @@ impl MyApp {
         }
     }
 
-    fn save_album_art(&mut self, path: &str) -> Result<(), Box<dyn std::error::Error>> {
+    fn save_album_art(&mut self, path: &str) -> errors::Result<()> {
         if let Some((bytes, mime)) = self.get_album_art(path)? {
             // determine extension from mime type
             let ext = match mime.as_str() {
@@ -161,13 +284,13 @@ impl MyApp {
             };
 
             if let Some(save_path) = rfd::FileDialog::new()
-                .set_file_name(&format!("artwork.{}", ext))
+                .set_file_name(format!("artwork.{}", ext))
                 .save_file()
             {
                 std::fs::write(save_path, &bytes)?;
             }
         } else {
-            return Err("No album art found".into());
+            return Err(t!("art-none-found").into());
         }
         Ok(())
     }
@@ -176,7 +299,7 @@ impl MyApp {
         &mut self,
         ctx: &egui::Context,
         path: &str,
-    ) -> Result<(), Box<dyn std::error::Error>> {
+    ) -> errors::Result<()> {
         if let Some(tex) = self.album_art_cache.get(path) {
             self.selected_album_art = Some(*tex);
             return Ok(());
@@ -186,7 +309,7 @@ impl MyApp {
             let start_decode = std::time::Instant::now();
             let mut image = image::load_from_memory(&bytes)?;
             let decode_duration = start_decode.elapsed();
-            println!("Decoding image took: {:?}", decode_duration);
+            self.log_info(Some(path), t!("log-art-decoded", time = format!("{:?}", decode_duration)));
 
             let max_size = 512;
             let start_resize = std::time::Instant::now();
@@ -194,7 +317,7 @@ impl MyApp {
                 image = image::DynamicImage::ImageRgba8(image::imageops::thumbnail(&image, max_size, max_size));
             }
             let resize_duration = start_resize.elapsed();
-            println!("Resizing image took: {:?}", resize_duration);
+            self.log_info(Some(path), t!("log-art-resized", time = format!("{:?}", resize_duration)));
 
             let size = [image.width() as usize, image.height() as usize];
             let pixels = image.to_rgba8().into_raw();
@@ -207,7 +330,7 @@ impl MyApp {
                 .write()
                 .alloc("album_art".into(), color_image.into(), Default::default());
             let upload_duration = start_upload.elapsed();
-            println!("Uploading texture took: {:?}", upload_duration);
+            self.log_info(Some(path), t!("log-art-uploaded", time = format!("{:?}", upload_duration)));
 
             self.album_art_cache.insert(path.to_string(), texture_id);
             self.selected_album_art = Some(texture_id);
@@ -219,19 +342,13 @@ impl MyApp {
         
     }
 
-    fn get_genre(&mut self, path: &str) -> Result<Option<String>, Box<dyn std::error::Error>> {
-        Ok(self.get_tag(path)?.genre().map(|s| s.to_string()))
-    }
-
-    fn set_genre(&mut self, path: &str, genre: String) -> Result<(), Box<dyn std::error::Error>> {
+    fn set_genre(&mut self, path: &str, genre: String) -> errors::Result<()> {
         let mut tag = Tag::read_from_path(path)?;
         tag.set_genre(genre);
-        tag.write_to_path(path, Version::Id3v24)?;
-        self.cached_tag = Some(tag);
-        Ok(())
+        self.write_tag(path, tag)
     }
 
-    fn get_codec_params<P: AsRef<Path>>(path: P) -> Result<CodecParameters, Box<dyn std::error::Error>> {
+    fn get_codec_params<P: AsRef<Path>>(path: P) -> errors::Result<CodecParameters> {
         let file = File::open(path)?;
         let mss = MediaSourceStream::new(Box::new(file), Default::default());
 
@@ -244,11 +361,11 @@ impl MyApp {
         )?;
 
         let format = probed.format;
-        let track = format.default_track().ok_or("No default track")?;
+        let track = format.default_track().ok_or_else(|| t!("audio-no-track"))?;
         Ok(track.codec_params.clone())
     }
 
-    fn get_bitrate<P: AsRef<Path>>(path: P) -> Result<Option<u32>, Box<dyn std::error::Error>> {
+    fn get_bitrate<P: AsRef<Path>>(path: P) -> errors::Result<Option<u32>> {
         let params = Self::get_codec_params(&path)?;
 
         let metadata = std::fs::metadata(path)?;
@@ -260,22 +377,22 @@ impl MyApp {
             None
         };
 
-        if let Some(dur) = duration {
-            if dur > 0.0 {
-                let approx_bitrate = (file_size_bits as f64 / dur) as u32;
-                return Ok(Some(approx_bitrate));
-            }
+        if let Some(dur) = duration
+            && dur > 0.0
+        {
+            let approx_bitrate = (file_size_bits as f64 / dur) as u32;
+            return Ok(Some(approx_bitrate));
         }
 
         Ok(None)
     }
 
-    fn get_sample_rate(path: &str) -> Result<Option<u32>, Box<dyn std::error::Error>> {
+    fn get_sample_rate(path: &str) -> errors::Result<Option<u32>> {
         let params = Self::get_codec_params(path)?;
         Ok(params.sample_rate)
     }
 
-    fn get_duration_seconds(path: &str) -> Result<Option<f64>, Box<dyn std::error::Error>> {
+    fn get_duration_seconds(path: &str) -> errors::Result<Option<f64>> {
         let params = Self::get_codec_params(path)?;
         match (params.sample_rate, params.n_frames) {
             (Some(rate), Some(frames)) => Ok(Some(frames as f64 / rate as f64)),
@@ -283,6 +400,133 @@ impl MyApp {
         }
     }
 
+    /// Bitrate, sample rate and duration of `path`, probing the file only when it wasn't the last one asked about.
+    fn audio_info(&mut self, path: &str) -> AudioInfo {
+        if let Some((cached, info)) = &self.audio_info
+            && cached == path
+        {
+            return *info;
+        }
+        let params = MyApp::get_codec_params(path).ok();
+        let sample_rate = params.as_ref().and_then(|p| p.sample_rate);
+        let duration = params
+            .and_then(|p| p.n_frames)
+            .zip(sample_rate)
+            .map(|(frames, rate)| frames as f64 / rate as f64);
+        let bitrate = duration
+            .filter(|d| *d > 0.0)
+            .zip(std::fs::metadata(path).ok())
+            .map(|(d, m)| (m.len() as f64 * 8.0 / d) as u32);
+        let info = AudioInfo { bitrate, sample_rate, duration };
+        self.audio_info = Some((path.to_string(), info));
+        info
+    }
+
+    fn toggle_playback(&mut self, path: &str) {
+        match &self.player {
+            Some(player) if player.path() == path && !player.is_finished() => {
+                if player.is_paused() {
+                    player.play();
+                } else {
+                    player.pause();
+                }
+            }
+            _ => self.player = Some(Player::start(path, playback::default_sink())),
+        }
+    }
+
+    fn render_to_wav(&mut self, path: &str) {
+        if let Some(save_path) = rfd::FileDialog::new()
+            .add_filter(t!("filter-wave"), &["wav"])
+            .set_file_name("preview.wav")
+            .save_file()
+        {
+            // finishes as soon as the whole file has been decoded
+            self.player = Some(Player::start(path, playback::file_sink(save_path)));
+        }
+    }
+
+    fn transport_ui(&mut self, ui: &mut egui::Ui) {
+        let Some(path) = self.selected_file.clone() else {
+            return;
+        };
+        let duration = self.audio_info(&path).duration.unwrap_or(0.0);
+
+        if let Some(err) = self.player.as_ref().and_then(|p| p.take_error()) {
+            self.report_error(Some(&path), t!("playback-failed", error = err.to_string()));
+        }
+
+        let text_color = self.theme.color(Role::Text);
+        let playing = self
+            .player
+            .as_ref()
+            .is_some_and(|p| p.path() == path && !p.is_paused() && !p.is_finished());
+        let mut position = self
+            .seek_preview
+            .or_else(|| self.player.as_ref().filter(|p| !p.is_finished()).map(|p| p.position_seconds()))
+            .unwrap_or(0.0);
+
+        ui.horizontal(|ui| {
+            let button = egui::Button::new(if playing { t!("playback-pause") } else { t!("playback-play") });
+            let response = ui
+                .add_enabled(playback::HAS_OUTPUT, button)
+                .on_disabled_hover_text(t!("playback-no-output"));
+            if response.clicked() {
+                self.toggle_playback(&path);
+            }
+            if playback::HAS_OUTPUT {
+                response.context_menu(|ui| {
+                    if ui.button(t!("playback-render")).clicked() {
+                        self.render_to_wav(&path);
+                        ui.close_menu();
+                    }
+                });
+            } else if ui.button(t!("playback-render")).clicked() {
+                // a disabled button has no context menu to hold this
+                self.render_to_wav(&path);
+            }
+
+            let slider = ui.add_enabled(
+                duration > 0.0,
+                egui::Slider::new(&mut position, 0.0..=duration.max(0.1)).show_value(false),
+            );
+            if slider.dragged() {
+                self.seek_preview = Some(position);
+            } else if slider.drag_stopped() || slider.changed() {
+                self.seek_preview = None;
+                if self.player.as_ref().is_none_or(|p| p.path() != path || p.is_finished()) {
+                    self.player = Some(Player::start(&path, playback::default_sink()));
+                    if let Some(player) = &self.player {
+                        player.pause();
+                    }
+                }
+                if let Some(player) = &self.player {
+                    player.seek(position);
+                }
+            }
+
+            ui.label(
+                egui::RichText::new(format!(
+                    "{} / {}",
+                    playback::format_time(position),
+                    playback::format_time(duration)
+                ))
+                .color(text_color),
+            );
+        });
+
+        if playing {
+            ui.ctx().request_repaint_after(Duration::from_millis(100));
+        }
+    }
+
+    /// The less used fields, shown beside the others when there's room.
+    fn more_fields_ui(&mut self, ui: &mut egui::Ui) {
+        for field in form::MORE_FIELDS {
+            self.form_field(ui, field);
+        }
+    }
+
     fn truncate_filename_with_ext(name: &str, max_len: usize) -> String {
         if name.len() <= max_len {
             return name.to_string();
@@ -304,60 +548,39 @@ impl MyApp {
 }
 
 
-impl Default for MyApp {
-    fn default() -> Self {
-        Self {
-            cached_tag: None,
-            dropped_files: Vec::new(),
-            selected_file: None,
-            selected_album_art: None,
-            album_art_cache: HashMap::new(),
-            album_art_ready: false,
-            alert_message: String::new(),
-            edited_artist: String::new(),
-            editing_artist: false,
-            edited_title: String::new(),
-            editing_title: false,
-            editing_album: false,
-            edited_album: String::new(),
-            editing_genre: false,
-            edited_genre: String::new(),
-        }
-    }
-}
-
 impl App for MyApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut Frame) {
-        let mut app = MyApp::default();
         let input = ctx.input(|i| i.clone());
+        self.apply_theme(ctx);
+        let panel_fill = self.theme.color(Role::Panel);
+        let text_color = self.theme.color(Role::Text);
+        self.watch_files(ctx);
+        self.handle_shortcuts(ctx);
 
         egui::SidePanel::left("my_left_panel")
-        .resizable(false)
-        .frame(egui::Frame::default().fill(egui::Color32::from_rgb(20, 20, 20)).inner_margin(5.0))
+        .resizable(true)
+        .frame(egui::Frame::default().fill(panel_fill).inner_margin(5.0))
         .default_width(200.0)
+        .width_range(150.0..=400.0)
         .show(ctx, |ui| {
-            ui.set_max_width(150.0);
             egui::ScrollArea::vertical()
             .show(ui, |ui| {
             ui.horizontal(|ui| {
                 ui.label(
-                egui::RichText::new("Files")
+                egui::RichText::new(t!("files-heading"))
                     .size(25.0)
-                    .color(egui::Color32::WHITE),
+                    .color(text_color),
                 );
-                if ui.button("add").clicked() {
-                    if let Some(path) = rfd::FileDialog::new()
-                        .add_filter("Audio", &["mp3"])
+                if ui.button(t!("files-add")).clicked()
+                    && let Some(path) = rfd::FileDialog::new()
+                        .add_filter(t!("filter-audio"), &["mp3"])
                         .pick_file()
-                    {
-                        let path_str = path.display().to_string();
-
-                        if !self.dropped_files.contains(&path_str) {
-                            self.dropped_files.push(path_str);
-                            self.dropped_files.sort();
-                        }
-                    }
+                {
+                    self.add_file(path.display().to_string());
                 };
+                if ui.button(t!("files-add-folder")).on_hover_text(t!("files-add-folder-hint")).clicked() {
+                    self.run_command(ctx, commands::Command::AddFolder);
+                }
             });
             
 
@@ -366,18 +589,15 @@ impl App for MyApp {
                     let path_str = path.display().to_string();
 
                     if path.extension().and_then(|ext| ext.to_str()) == Some("mp3") {
-                        if !self.dropped_files.contains(&path_str) {
-                            self.dropped_files.push(path_str);
-                            self.dropped_files.sort();
-                        }
+                        self.add_file(path_str);
                         self.alert_message.clear();
                     } else {
-                        self.alert_message = format!("File '{}' is not an mp3!", MyApp::get_file_name(&path_str));
+                        self.report_warning(Some(&path_str), t!("files-not-mp3", file = MyApp::get_file_name(&path_str)));
                     }
                 }
             }
 
-            ui.label("Song list:");
+            ui.label(t!("files-song-list"));
             let mut selected_file_to_load = None;
             let mut file_to_remove: Option<String> = None;
 
@@ -385,31 +605,28 @@ impl App for MyApp {
                 let is_selected = Some(file) == self.selected_file.as_ref();
 
                 let button = egui::Button::new(
-                    egui::RichText::new(MyApp::get_file_name(&file)).color(
+                    egui::RichText::new(MyApp::get_file_name(file)).color(
                         if is_selected {
-                            egui::Color32::WHITE
+                            self.theme.color(Role::SelectedText)
                         } else {
-                            egui::Color32::GRAY
+                            self.theme.color(Role::MutedText)
                         },
                     ),
                 )
                 .fill(if is_selected {
-                    egui::Color32::from_rgb(100, 150, 255)
+                    self.theme.color(Role::Selection)
                 } else {
-                    egui::Color32::from_rgb(60, 60, 60)
+                    self.theme.color(Role::Item)
                 });
 
                 let response = ui.add(button);
 
                 if response.clicked() {
-                    self.selected_file = Some(file.clone());
-                    self.selected_album_art = None;
-                    self.cached_tag = None;
-                    selected_file_to_load = self.selected_file.clone();
+                    selected_file_to_load = Some(file.clone());
                 }
 
                 response.context_menu(|ui| {
-                    if ui.button("Remove").clicked() {
+                    if ui.button(t!("files-remove")).clicked() {
                         file_to_remove = Some(file.clone());
                         ui.close_menu();
                     }
@@ -420,200 +637,208 @@ impl App for MyApp {
                 self.dropped_files.retain(|f| f != &file);
                 if self.selected_file.as_ref() == Some(&file) {
                     self.selected_file = None;
+                    self.player = None;
                 }
             }
 
             if let Some(path) = selected_file_to_load {
-                let _ = self.load_album_art_texture(ctx, &path);
+                self.select_file(ctx, path);
             }
             });
         });
 
 
         egui::TopBottomPanel::top("top_panel")
-        .frame(egui::Frame::default().fill(egui::Color32::from_rgb(20, 20, 20)).inner_margin(5.0))
+        .frame(egui::Frame::default().fill(panel_fill).inner_margin(5.0))
         .show(ctx, |ui| {
             ui.horizontal_centered(|ui| {
+                ui.menu_button(t!("menu-recent"), |ui| self.recent_menu(ui));
+                ui.menu_button(t!("menu-language"), i18n::language_menu);
+                ui.menu_button(t!("menu-tools"), |ui| {
+                    if ui.button(t!("menu-export")).clicked() {
+                        self.spreadsheet.open_export();
+                        ui.close_menu();
+                    }
+                    if ui.button(t!("menu-import")).clicked() {
+                        self.spreadsheet.open_import();
+                        ui.close_menu();
+                    }
+                    if ui.button(t!("menu-cue")).clicked() {
+                        self.cue_import.open();
+                        ui.close_menu();
+                    }
+                    if ui.add_enabled(self.selected_file.is_some(), egui::Button::new(t!("menu-musicbrainz"))).clicked() {
+                        self.open_musicbrainz();
+                        ui.close_menu();
+                    }
+                    if ui.button(t!("menu-identify")).clicked() {
+                        self.open_identify();
+                        ui.close_menu();
+                    }
+                    if ui.button(t!("menu-duplicates")).clicked() {
+                        self.open_duplicates();
+                        ui.close_menu();
+                    }
+                    if ui.button(t!("menu-lint")).clicked() {
+                        self.open_linter();
+                        ui.close_menu();
+                    }
+                    if ui.button(t!("menu-mojibake")).clicked() {
+                        self.open_mojibake();
+                        ui.close_menu();
+                    }
+                    if ui.button(t!("menu-genres")).clicked() {
+                        self.open_genre_consolidation();
+                        ui.close_menu();
+                    }
+                    if ui.button(t!("menu-versions")).clicked() {
+                        self.open_version_converter();
+                        ui.close_menu();
+                    }
+                    if ui.button(t!("menu-tag-blocks")).clicked() {
+                        self.open_tag_blocks();
+                        ui.close_menu();
+                    }
+                    ui.separator();
+                    if ui.button(t!("menu-log")).clicked() {
+                        self.run_command(ctx, commands::Command::Log);
+                        ui.close_menu();
+                    }
+                    if ui.button(t!("menu-saving")).clicked() {
+                        self.open_write_settings();
+                        ui.close_menu();
+                    }
+                    if ui.button(t!("menu-appearance")).clicked() {
+                        self.run_command(ctx, commands::Command::Appearance);
+                        ui.close_menu();
+                    }
+                    if ui.button(t!("menu-shortcuts")).clicked() {
+                        self.run_command(ctx, commands::Command::Shortcuts);
+                        ui.close_menu();
+                    }
+                    if ui.button(t!("menu-palette")).clicked() {
+                        self.run_command(ctx, commands::Command::Palette);
+                        ui.close_menu();
+                    }
+                });
+
                 if let Some(selected) = &self.selected_file {
                     let file_name = MyApp::get_file_name(selected);
                     ui.heading(
                         egui::RichText::new(MyApp::truncate_filename_with_ext(file_name, 50))
                             .size(25.0)
-                            .color(egui::Color32::WHITE),
+                            .color(text_color),
                     );
                 } else {
                     ui.heading(
-                        egui::RichText::new("No File Selected")
+                        egui::RichText::new(t!("files-none-selected"))
                             .size(25.0)
-                            .color(egui::Color32::WHITE),
+                            .color(text_color),
                     );
                 }
             });
         });
 
+        self.spreadsheet_windows(ctx);
+        self.cue_import_window(ctx);
+        self.musicbrainz_window(ctx);
+        self.cover_search_window(ctx);
+        self.identify_window(ctx);
+        self.duplicates_window(ctx);
+        self.linter_window(ctx);
+        self.mojibake_window(ctx);
+        self.value_editor_window(ctx);
+        self.genre_consolidation_window(ctx);
+        self.version_converter_window(ctx);
+        self.tag_blocks_window(ctx);
+        self.write_settings_window(ctx);
+        self.file_conflict_window(ctx);
+        self.shortcuts_window(ctx);
+        self.theme_window(ctx);
+        self.command_palette_window(ctx);
+        self.log_window(ctx);
+
         egui::TopBottomPanel::bottom("alert_panel")
-        .frame(egui::Frame::default().fill(egui::Color32::from_rgb(20, 20, 20)).inner_margin(5.0))
+        .frame(egui::Frame::default().fill(panel_fill).inner_margin(5.0))
         .show(ctx, |ui| {
-            if !self.alert_message.is_empty() {
-                ui.colored_label(egui::Color32::RED, &self.alert_message);
-            } else {
-                if ui.link(egui::RichText::new("github").color(egui::Color32::WHITE).size(15.0)).clicked() {
-                    let _ = open::that("https://github.com/joshjkns");
+            ui.horizontal(|ui| {
+                if !self.alert_message.is_empty() {
+                    ui.colored_label(self.theme.color(Role::Error), &self.alert_message);
+                } else {
+                    if ui.link(egui::RichText::new("github").color(text_color).size(15.0)).clicked() {
+                        let _ = open::that("https://github.com/joshjkns");
+                    }
                 }
-            }
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| self.log_link(ui));
+            });
         });
 
-        egui::CentralPanel::default().show(ctx, |ui| {
-            let total_width = ui.available_width();
-            let right_width = 300.0;
-            let left_width = total_width - right_width;
-            if self.selected_file.is_some() {
-                ui.with_layout(egui::Layout::left_to_right(egui::Align::Min), |ui| {
-                egui::ScrollArea::vertical()
-                .show(ui, |ui| {
-                    ui.vertical(|ui| {
-                    ui.set_width(left_width);
-
-                    // artist
-                    ui.horizontal(|ui| {
-                        ui.heading("Artist:");
-                        if ui.button("edit").clicked() {
-                            self.editing_artist = true;
-                        }
-                    });
-
-                    let artist_label = self
-                        .selected_file
-                        .as_ref()
-                        .and_then(|path| app.get_artist(path).ok().flatten())
-                        .unwrap_or_else(|| "No artist info".to_string());
-                    ui.label(egui::RichText::new(artist_label).color(egui::Color32::WHITE).size(16.0));
-
-                    if self.editing_artist {
-                        egui::Window::new("Edit Artist").show(ctx, |ui| {
-                            ui.text_edit_singleline(&mut self.edited_artist);
-                            if ui.button("Save").clicked() {
-                                if let Some(path) = &self.selected_file {
-                                    let artist = self.edited_artist.clone();
-                                    if let Err(e) = app.set_artist(path, artist) {
-                                        self.alert_message = format!("Failed to save artist: {}", e);
-                                    } else {
-                                        self.editing_artist = false;
-                                    }
-                                }
-                            }
-                            if ui.button("Cancel").clicked() {
-                                self.editing_artist = false;
-                            }
-                        });
+        if self.selected_file.is_some() && self.tab == Tab::Tags {
+            egui::SidePanel::right("artwork_panel")
+            .resizable(true)
+            .default_width(300.0)
+            .width_range(200.0..=600.0)
+            .show(ctx, |ui| {
+                // image
+                ui.horizontal_wrapped(|ui| {
+                    ui.heading(t!("art-heading"));
+                    if ui.button(t!("art-edit")).clicked()
+                        && let Err(err) = self.pick_and_set_album_art(ctx)
+                    {
+                        let file = self.selected_file.clone();
+                        self.report_error(file.as_deref(), t!("art-set-failed", error = err.to_string()));
                     }
-                    ui.add_space(10.0);
-
-                    // title
-                    ui.horizontal(|ui| {
-                        ui.heading("Title:");
-                        if ui.button("edit").clicked() {
-                            self.editing_title = true;
-                        }
-                    });
-
-                    let title_label = self
-                        .selected_file
-                        .as_ref()
-                        .and_then(|path| app.get_title(path).ok().flatten())
-                        .unwrap_or_else(|| "No title info".to_string());
-                    ui.label(egui::RichText::new(title_label).color(egui::Color32::WHITE).size(16.0));
-
-                    if self.editing_title {
-                        egui::Window::new("Edit Title").show(ctx, |ui| {
-                            ui.text_edit_singleline(&mut self.edited_title);
-                            if ui.button("Save").clicked() {
-                                if let Some(path) = &self.selected_file {
-                                    let title = self.edited_title.clone();
-                                    if let Err(e) = app.set_title(path, title) {
-                                        self.alert_message = format!("Failed to save title: {}", e);
-                                    } else {
-                                        self.editing_title = false;
-                                    }
-                                }
-                            }
-                            if ui.button("Cancel").clicked() {
-                                self.editing_title = false;
-                            }
-                        });
+                    if ui.button(t!("art-find-cover")).clicked() {
+                        self.open_cover_search();
                     }
-
-                    ui.add_space(10.0);
-                    
-                    // album
-                    ui.horizontal(|ui| {
-                        ui.heading("Album:");
-                        if ui.button("edit").clicked() {
-                            self.editing_album = true;
-                        }
-                    });
-
-                    let album_label = self
-                        .selected_file
-                        .as_ref()
-                        .and_then(|path| app.get_album(path).ok().flatten())
-                        .unwrap_or_else(|| "No album info".to_string());
-                    ui.label(egui::RichText::new(album_label).color(egui::Color32::WHITE).size(16.0));
-
-                    if self.editing_album {
-                        egui::Window::new("Edit Album").show(ctx, |ui| {
-                            ui.text_edit_singleline(&mut self.edited_album);
-                            if ui.button("Save").clicked() {
-                                if let Some(path) = &self.selected_file {
-                                    let album = self.edited_album.clone();
-                                    if let Err(e) = app.set_album(path, album) {
-                                        self.alert_message = format!("Failed to save album: {}", e);
-                                    } else {
-                                        self.editing_album = false;
-                                    }
-                                }
-                            }
-                            if ui.button("Cancel").clicked() {
-                                self.editing_album = false;
-                            }
-                        });
+                    if self.selected_album_art.is_some()
+                        && ui.button(t!("art-save")).clicked()
+                        && let Some(path) = self.selected_file.clone()
+                        && let Err(err) = self.save_album_art(&path)
+                    {
+                        self.report_error(Some(&path), t!("art-save-failed", error = err.to_string()));
                     }
+                });
 
-                    ui.add_space(10.0);
+                if !self.album_art_ready { // not ready yet
+                    ui.label(t!("art-loading"));
+                } else if let Some(tex) = &self.selected_album_art {
+                    // as large as the panel allows
+                    let side = ui.available_width().min(ui.available_height()).max(100.0);
+                    ui.image((*tex, egui::Vec2::splat(side)));
+                } else {
+                    ui.label(t!("art-none"));
+                }
+            });
+        }
 
-                    // genre
-                    ui.horizontal(|ui| {
-                        ui.heading("Genre:");
-                        if ui.button("edit").clicked() {
-                            self.editing_genre = true;
-                        }
-                    });
+        egui::CentralPanel::default().show(ctx, |ui| {
+            // room for a second column of fields
+            let wide = ui.available_width() >= 560.0;
+            let field_width = if wide { ui.available_width() * 0.55 } else { ui.available_width() };
+            if self.selected_file.is_some() {
+                ui.horizontal(|ui| {
+                    ui.selectable_value(&mut self.tab, Tab::Tags, t!("tab-tags"));
+                    ui.selectable_value(&mut self.tab, Tab::Lyrics, t!("tab-lyrics"));
+                    ui.selectable_value(&mut self.tab, Tab::Chapters, t!("tab-chapters"));
+                });
+                ui.separator();
 
-                    let genre_label = self
-                        .selected_file
-                        .as_ref()
-                        .and_then(|path| app.get_genre(path).ok().flatten())
-                        .unwrap_or_else(|| "No genre info".to_string());
-                    ui.label(egui::RichText::new(genre_label).color(egui::Color32::WHITE).size(16.0));
-
-                    if self.editing_genre {
-                        egui::Window::new("Edit Genre").show(ctx, |ui| {
-                            ui.text_edit_singleline(&mut self.edited_genre);
-                            if ui.button("Save").clicked() {
-                                if let Some(path) = &self.selected_file {
-                                    let genre = self.edited_genre.clone();
-                                    if let Err(e) = app.set_genre(path, genre) {
-                                        self.alert_message = format!("Failed to save genre: {}", e);
-                                    } else {
-                                        self.editing_genre = false;
-                                    }
-                                }
-                            }
-                            if ui.button("Cancel").clicked() {
-                                self.editing_genre = false;
-                            }
-                        });
+                if self.tab == Tab::Lyrics {
+                    self.lyrics_tab(ui);
+                } else if self.tab == Tab::Chapters {
+                    self.chapters_tab(ui);
+                } else {
+                egui::ScrollArea::vertical()
+                .show(ui, |ui| {
+                ui.horizontal_top(|ui| {
+                    ui.vertical(|ui| {
+                    ui.set_width(field_width);
+
+                    for field in form::MAIN_FIELDS {
+                        self.form_field(ui, field);
                     }
+                    self.form_buttons(ui);
 
                     ui.add_space(5.0);
 
@@ -621,88 +846,77 @@ impl App for MyApp {
                     ui.vertical(|ui| {
                         ui.add_space(5.0);
                         ui.centered_and_justified(|ui| {
+                            let info = self.selected_file.clone().map(|path| self.audio_info(&path));
                             ui.columns(2, |columns| {
-                                if let Some(path) = &self.selected_file {
-                                    columns[0].heading(egui::RichText::new("Bitrate:").size(15.0));
-                                    if let Ok(Some(b)) = MyApp::get_bitrate(path) {
-                                        columns[0].label(egui::RichText::new(format!("{} kbps", b / 1000)).size(16.0).color(egui::Color32::WHITE));
+                                if let Some(info) = info {
+                                    columns[0].heading(egui::RichText::new(t!("info-bitrate")).size(15.0));
+                                    if let Some(b) = info.bitrate {
+                                        columns[0].label(egui::RichText::new(t!("info-kbps", kbps = b / 1000)).size(16.0).color(text_color));
                                     } else {
-                                        columns[0].label("Unknown bitrate");
+                                        columns[0].label(t!("info-unknown-bitrate"));
                                     }
 
                                     columns[0].add_space(5.0);
 
-                                    columns[1].heading(egui::RichText::new("Sample Rate:").size(15.0));
-                                    if let Ok(Some(sr)) = MyApp::get_sample_rate(path) {
-                                        columns[1].label(egui::RichText::new(format!("{} kHz", sr / 1000)).size(16.0).color(egui::Color32::WHITE));
+                                    columns[1].heading(egui::RichText::new(t!("info-sample-rate")).size(15.0));
+                                    if let Some(sr) = info.sample_rate {
+                                        columns[1].label(egui::RichText::new(t!("info-khz", khz = sr / 1000)).size(16.0).color(text_color));
                                     } else {
-                                        columns[1].label("Unknown sample rate");
+                                        columns[1].label(t!("info-unknown-sample-rate"));
                                     }
                                     
                                     columns[1].add_space(5.0);
 
-                                    columns[0].heading(egui::RichText::new("Duration:").size(15.0));
-                                    if let Ok(Some(s)) = MyApp::get_duration_seconds(path) {
-                                        columns[0].label(egui::RichText::new(format!("{} seconds", s.floor() as u64)).size(16.0).color(egui::Color32::WHITE));
+                                    columns[0].heading(egui::RichText::new(t!("info-duration")).size(15.0));
+                                    if let Some(s) = info.duration {
+                                        columns[0].label(egui::RichText::new(t!("info-seconds", seconds = s.floor() as u64)).size(16.0).color(text_color));
                                     } else {
-                                        columns[0].label("Unknown duration");
+                                        columns[0].label(t!("info-unknown-duration"));
                                     }
                                 }  
                             });
                         });
                     });
-                });
-                });
-                
-
-                ui.vertical(|ui| {
-                    ui.set_width(right_width);
-                    
-                    // image
-                    ui.horizontal(|ui| {
-                        ui.heading("Artwork:");
-                        if ui.button("edit").clicked() {
-                            if let Err(err) = self.pick_and_set_album_art(ctx) {
-                                self.alert_message = format!("Failed to pick/set album art: {}", err);
-                            }
-                        }
-                        if  self.selected_album_art.is_some() && ui.button("save image").clicked() {
-                            if let Some(path) = &self.selected_file {
-                                if let Err(err) = app.save_album_art(path) {
-                                    self.alert_message = format!("Failed to save album art: {}", err);
-                                }
-                            }
-                        }
+
+                    ui.add_space(10.0);
+
+                    // preview
+                    self.transport_ui(ui);
                     });
 
-                    if !self.album_art_ready { // not ready yet
-                        ui.label("Loading...");
-                    } else if let Some(tex) = &self.selected_album_art {
-                        ui.image((*tex, egui::Vec2::splat(300.0)));
-                    } else {
-                        ui.label("No album art");
+                    if wide {
+                        ui.vertical(|ui| self.more_fields_ui(ui));
                     }
                 });
-            });
+
+                if !wide {
+                    egui::CollapsingHeader::new(t!("tags-more-fields")).show(ui, |ui| self.more_fields_ui(ui));
+                }
+                });
+                }
             } else {
                let available = ui.available_size();
                 ui.allocate_ui(available, |ui| {
                     ui.with_layout(egui::Layout::centered_and_justified(egui::Direction::TopDown), |ui| {
-                        ui.heading(egui::RichText::new("Add or drag a file!").size(30.0).color(egui::Color32::WHITE));
+                        ui.heading(egui::RichText::new(t!("files-empty")).size(30.0).color(text_color));
                     });
                 });
             }
         });    
     }
+
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        self.save_session(storage);
+    }
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let mut options = NativeOptions::default();
-    options.viewport.resizable = Some(false);
-    options.viewport.inner_size = Some(egui::vec2(800.0, 400.0));
+    options.viewport.inner_size = Some(egui::vec2(1000.0, 600.0));
+    options.viewport.min_inner_size = Some(egui::vec2(720.0, 420.0));
     Ok(eframe::run_native(
         "Metadata Editor",
         options,
-        Box::new(|_cc| Ok(Box::new(MyApp::default()))),
+        Box::new(|cc| Ok(Box::new(MyApp::restore(cc)))),
     )?)
 }
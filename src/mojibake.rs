@@ -0,0 +1,293 @@
+use std::collections::HashSet;
+
+use eframe::egui;
+use encoding_rs::{Encoding, GBK, SHIFT_JIS, UTF_8, WINDOWS_1251};
+use id3::frame::{Comment, ExtendedText};
+use id3::{Content, Frame, Tag, TagLike, Version};
+
+use crate::MyApp;
+use crate::audio;
+use crate::errorlog::FileErrors;
+use crate::errors;
+use crate::i18n::t;
+
+/// Codepages legacy taggers commonly wrote while labelling the text Latin-1.
+/// Strict UTF-8 rarely decodes by accident, so it goes first; ties go to the earlier one.
+const CANDIDATES: [&Encoding; 4] = [UTF_8, WINDOWS_1251, SHIFT_JIS, GBK];
+
+/// The raw bytes behind text the id3 crate decoded as Latin-1, if it could have been.
+fn latin1_bytes(text: &str) -> Option<Vec<u8>> {
+    text.chars().map(|c| u8::try_from(c).ok()).collect()
+}
+
+/// Whether text already reads as Latin-1: accented letters mixed into ASCII,
+/// at most two in a row, and symbols like © or ° standing on their own.
+/// Other codepages shown as Latin-1 come out as long runs of accented letters
+/// and symbols, or C1 control characters.
+fn plausible_latin1(text: &str) -> bool {
+    let chars: Vec<char> = text.chars().collect();
+    let mut run = 0;
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_ascii() {
+            run = 0;
+            continue;
+        }
+        run += 1;
+        let lone = (i == 0 || chars[i - 1].is_ascii()) && chars.get(i + 1).is_none_or(|n| n.is_ascii());
+        if ('\u{80}'..'\u{a0}').contains(&c) || run > 2 || !(c.is_alphabetic() || lone) {
+            return false;
+        }
+    }
+    true
+}
+
+/// Whether text looks like another codepage shown as Latin-1: it isn't
+/// believable as Latin-1, but reads as words in one of the candidates.
+fn looks_misdecoded(text: &str) -> bool {
+    latin1_bytes(text).is_some_and(|b| !b.is_ascii())
+        && !plausible_latin1(text)
+        && CANDIDATES
+            .iter()
+            .any(|e| reinterpret(text, e).is_some_and(|t| t != text && plausible(&t)))
+}
+
+/// Whether decoded text is made of letters rather than symbols.
+fn plausible(text: &str) -> bool {
+    text.chars().all(|c| {
+        c.is_ascii()
+            || c.is_alphanumeric()
+            || c.is_whitespace()
+            || ('\u{2010}'..='\u{206f}').contains(&c)
+            || ('\u{3000}'..='\u{30ff}').contains(&c)
+            || ('\u{ff00}'..='\u{ffef}').contains(&c)
+    })
+}
+
+/// Text reinterpreted in `encoding`, or `None` if it isn't valid there.
+fn reinterpret(text: &str, encoding: &'static Encoding) -> Option<String> {
+    let bytes = latin1_bytes(text)?;
+    encoding
+        .decode_without_bom_handling_and_without_replacement(&bytes)
+        .map(|s| s.into_owned())
+}
+
+/// A frame whose text is probably mis-decoded.
+struct Suspect {
+    frame: Frame,
+    text: String,
+}
+
+struct Repair {
+    path: String,
+    suspects: Vec<Suspect>,
+    encoding: &'static Encoding,
+    apply: bool,
+}
+
+#[derive(Default)]
+pub struct MojibakeRepair {
+    open: bool,
+    repairs: Vec<Repair>,
+    version: Option<Version>,
+}
+
+fn frame_text(frame: &Frame) -> Option<&str> {
+    match frame.content() {
+        Content::Text(text) => Some(text),
+        Content::ExtendedText(extended) => Some(&extended.value),
+        Content::Comment(comment) => Some(&comment.text),
+        _ => None,
+    }
+}
+
+fn with_text(frame: &Frame, text: String) -> Frame {
+    let content = match frame.content() {
+        Content::ExtendedText(extended) => Content::ExtendedText(ExtendedText { value: text, ..extended.clone() }),
+        Content::Comment(comment) => Content::Comment(Comment { text, ..comment.clone() }),
+        _ => Content::Text(text),
+    };
+    Frame::with_content(frame.id(), content)
+}
+
+/// Ids of the frames whose every copy in the raw tag declares Latin-1; only
+/// those can hold another codepage. UTF-8 and UTF-16 frames decoded fine.
+fn latin1_frames(raw: &[u8]) -> HashSet<String> {
+    let frames = audio::id3v2_frames(raw);
+    let declares = |id: &str, latin1: bool| {
+        frames.iter().any(|(i, body)| i == id && (raw.get(body.start) == Some(&0)) == latin1)
+    };
+    frames
+        .iter()
+        .filter(|(id, _)| declares(id, true) && !declares(id, false))
+        .map(|(id, _)| id.clone())
+        .collect()
+}
+
+fn scan(path: &str) -> Option<Repair> {
+    let tag = id3::no_tag_ok(Tag::read_from_path(path)).ok().flatten()?;
+    let latin1 = latin1_frames(&audio::read_id3v2(path).ok()?);
+    let suspects: Vec<Suspect> = tag
+        .frames()
+        .filter(|frame| latin1.contains(frame.id()))
+        .filter_map(|frame| {
+            let text = frame_text(frame)?;
+            looks_misdecoded(text).then(|| Suspect { frame: frame.clone(), text: text.to_string() })
+        })
+        .collect();
+    if suspects.is_empty() {
+        return None;
+    }
+
+    // the codepage under which the most frames decode to letters
+    let mut encoding = CANDIDATES[0];
+    let mut best = 0;
+    for candidate in CANDIDATES {
+        let count = suspects
+            .iter()
+            .filter(|s| reinterpret(&s.text, candidate).is_some_and(|t| plausible(&t)))
+            .count();
+        if count > best {
+            (encoding, best) = (candidate, count);
+        }
+    }
+    Some(Repair { path: path.to_string(), suspects, encoding, apply: false })
+}
+
+impl MyApp {
+    pub(crate) fn open_mojibake(&mut self) {
+        let repair = &mut self.mojibake;
+        repair.repairs = self.dropped_files.iter().filter_map(|p| scan(p)).collect();
+        repair.version.get_or_insert(Version::Id3v24);
+        repair.open = true;
+    }
+
+    fn apply_mojibake(&mut self) -> FileErrors {
+        let version = self.mojibake.version.unwrap_or(Version::Id3v24);
+        let repairs = std::mem::take(&mut self.mojibake.repairs);
+        let mut errors = Vec::new();
+        for repair in repairs.iter().filter(|r| r.apply) {
+            let result = (|| -> errors::Result<()> {
+                let mut tag = Tag::read_from_path(&repair.path)?;
+                for suspect in &repair.suspects {
+                    let text = reinterpret(&suspect.text, repair.encoding)
+                        .ok_or_else(|| {
+                            t!("mojibake-invalid", text = suspect.text.as_str(), encoding = repair.encoding.name())
+                        })?;
+                    // a frame without an explicit encoding is written as UTF-8 in v2.4 and UTF-16 in v2.3
+                    tag.add_frame(with_text(&suspect.frame, text));
+                }
+                self.write_tag_as(&repair.path, tag, version)
+            })();
+            if let Err(e) = result {
+                errors.push((repair.path.clone(), e.to_string()));
+            }
+        }
+        errors
+    }
+
+    pub(crate) fn mojibake_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.mojibake.open;
+        let mut apply = false;
+        egui::Window::new(t!("mojibake-title")).open(&mut open).show(ctx, |ui| {
+            let repair = &mut self.mojibake;
+            ui.label(t!("mojibake-summary", count = repair.repairs.len()));
+            ui.horizontal(|ui| {
+                ui.label(t!("mojibake-write-as"));
+                ui.radio_value(&mut repair.version, Some(Version::Id3v24), t!("mojibake-v24"));
+                ui.radio_value(&mut repair.version, Some(Version::Id3v23), t!("mojibake-v23"));
+            });
+
+            ui.separator();
+            egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                for (index, file) in repair.repairs.iter_mut().enumerate() {
+                    ui.horizontal(|ui| {
+                        ui.checkbox(&mut file.apply, MyApp::get_file_name(&file.path).to_string());
+                        egui::ComboBox::from_id_salt(("mojibake_encoding", index))
+                            .selected_text(file.encoding.name())
+                            .show_ui(ui, |ui| {
+                                for encoding in CANDIDATES {
+                                    ui.selectable_value(&mut file.encoding, encoding, encoding.name());
+                                }
+                            });
+                    });
+                    for suspect in &file.suspects {
+                        let preview = reinterpret(&suspect.text, file.encoding);
+                        ui.label(format!(
+                            "    {}: {} -> {}",
+                            suspect.frame.id(),
+                            suspect.text,
+                            preview.unwrap_or_else(|| t!("mojibake-preview-invalid"))
+                        ));
+                    }
+                }
+            });
+
+            apply = ui
+                .add_enabled(repair.repairs.iter().any(|r| r.apply), egui::Button::new(t!("mojibake-rewrite")))
+                .clicked();
+        });
+        self.mojibake.open &= open;
+
+        if apply {
+            let errors = self.apply_mojibake();
+            if errors.is_empty() {
+                self.mojibake.open = false;
+            }
+            self.report_file_errors("mojibake-failed", errors);
+            self.lyrics.reset();
+            self.chapters.reset();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `text` encoded in `encoding` and read back as Latin-1, the way a mislabelled frame decodes.
+    fn mislabelled(text: &str, encoding: &'static Encoding) -> String {
+        encoding.encode(text).0.iter().map(|&b| b as char).collect()
+    }
+
+    #[test]
+    fn latin1_text_is_left_alone() {
+        for text in ["Café Müller", "ßé", "Ça été", "Bjørk", "Jäätelö", "Größe", "© 1999 Label", "50° Nord", "Plain ASCII"] {
+            assert!(!looks_misdecoded(text), "{text}");
+        }
+    }
+
+    #[test]
+    fn other_codepages_are_found() {
+        for (text, encoding) in [
+            ("Привет мир", WINDOWS_1251),
+            ("Кино", WINDOWS_1251),
+            ("こんにちは", SHIFT_JIS),
+            ("你好世界", GBK),
+            ("Beyoncé", UTF_8),
+        ] {
+            let garbled = mislabelled(text, encoding);
+            assert!(looks_misdecoded(&garbled), "{text}");
+            assert_eq!(reinterpret(&garbled, encoding).as_deref(), Some(text));
+        }
+    }
+
+    #[test]
+    fn only_latin1_frames_are_candidates() {
+        // TIT2 in Latin-1, TPE1 in UTF-8, TALB once in each
+        let frame = |id: &[u8], encoding: u8| {
+            let mut f = id.to_vec();
+            f.extend([0, 0, 0, 2, 0, 0, encoding, b'x']);
+            f
+        };
+        let mut body = frame(b"TIT2", 0);
+        body.extend(frame(b"TPE1", 3));
+        body.extend(frame(b"TALB", 0));
+        body.extend(frame(b"TALB", 1));
+        let mut raw = b"ID3\x03\x00\x00\x00\x00\x00".to_vec();
+        raw.push(body.len() as u8);
+        raw.extend(body);
+
+        let ids = latin1_frames(&raw);
+        assert_eq!(ids, HashSet::from(["TIT2".to_string()]));
+    }
+}
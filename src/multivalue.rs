@@ -0,0 +1,110 @@
+use eframe::egui;
+use id3::Tag;
+
+use crate::errors;
+use crate::i18n::t;
+use crate::{MyApp, genres};
+use crate::fields::{Field, Separator};
+
+/// Edits the values of a multi-valued field (artists, genres, ...) one per row.
+#[derive(Default)]
+pub struct ValueListEditor {
+    field: Option<Field>,
+    values: Vec<String>,
+}
+
+impl ValueListEditor {
+    pub fn is_open(&self) -> bool {
+        self.field.is_some()
+    }
+}
+
+impl MyApp {
+    pub(crate) fn open_value_editor(&mut self, field: Field) {
+        let Some(path) = &self.selected_file else {
+            return;
+        };
+        let values = id3::no_tag_ok(Tag::read_from_path(path))
+            .ok()
+            .flatten()
+            .map(|tag| field.values(&tag, self.separator))
+            .unwrap_or_default();
+        self.value_editor.values = if values.is_empty() { vec![String::new()] } else { values };
+        self.value_editor.field = Some(field);
+    }
+
+    fn save_values(&mut self, field: Field) -> errors::Result<()> {
+        let path = self.selected_file.clone().ok_or_else(|| t!("form-no-file"))?;
+        let mut tag = id3::no_tag_ok(Tag::read_from_path(&path))?.unwrap_or_default();
+        let mut values = self.value_editor.values.clone();
+        if field == Field::Genre {
+            for value in &mut values {
+                *value = genres::canonical(value.trim(), &self.genres.custom);
+            }
+        }
+        field.set_values(&mut tag, &values)?;
+        self.write_tag(&path, tag)
+    }
+
+    pub(crate) fn value_editor_window(&mut self, ctx: &egui::Context) {
+        let Some(field) = self.value_editor.field else {
+            return;
+        };
+        let mut open = true;
+        let mut save = false;
+        egui::Window::new(t!("values-title", field = field.label())).open(&mut open).show(ctx, |ui| {
+            let values = &mut self.value_editor.values;
+            let mut remove = None;
+            for (index, value) in values.iter_mut().enumerate() {
+                ui.horizontal(|ui| {
+                    ui.text_edit_singleline(value);
+                    if ui.small_button("x").on_hover_text(t!("values-remove")).clicked() {
+                        remove = Some(index);
+                    }
+                });
+            }
+            if let Some(index) = remove {
+                values.remove(index);
+            }
+            if ui.button(t!("values-add")).clicked() {
+                values.push(String::new());
+            }
+
+            if field == Field::Genre {
+                ui.separator();
+                if let Some(genre) = self.genre_picker(ui) {
+                    let values = &mut self.value_editor.values;
+                    if let Some(empty) = values.iter_mut().find(|v| v.trim().is_empty()) {
+                        *empty = genre;
+                    } else if !values.contains(&genre) {
+                        values.push(genre);
+                    }
+                }
+            }
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.label(t!("values-separator"));
+                for separator in Separator::ALL {
+                    ui.radio_value(&mut self.separator, separator, format!("\"{}\"", separator.as_str()));
+                }
+            })
+            .response
+            .on_hover_text(t!("values-separator-hint"));
+            save = ui.button(t!("form-save")).clicked();
+        });
+
+        if save {
+            match self.save_values(field) {
+                Ok(()) => open = false,
+                Err(e) => {
+                    let file = self.selected_file.clone();
+                    self.report_error(file.as_deref(), t!("values-save-failed", field = field.label(), error = e.to_string()));
+                }
+            }
+        }
+        if !open {
+            self.value_editor.field = None;
+        }
+    }
+}
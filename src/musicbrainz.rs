@@ -0,0 +1,509 @@
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::time::Duration;
+
+use eframe::egui;
+use id3::frame::{ExtendedText, UniqueFileIdentifier};
+use id3::{Tag, TagLike};
+use serde::Deserialize;
+
+use crate::MyApp;
+use crate::errorlog::FileErrors;
+use crate::errors;
+use crate::fields::Field;
+use crate::i18n::t;
+use crate::theme::Role;
+
+pub const DEFAULT_BASE_URL: &str = "https://musicbrainz.org/ws/2";
+pub const USER_AGENT: &str = concat!("metadata-editor/", env!("CARGO_PKG_VERSION"), " ( https://github.com/joshjkns )");
+
+/// Track lengths further apart than this are flagged in the alignment table.
+const LENGTH_TOLERANCE_MS: i64 = 5000;
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct ArtistCredit {
+    pub name: String,
+    #[serde(default)]
+    pub joinphrase: String,
+    pub artist: ArtistRef,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct ArtistRef {
+    pub id: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Recording {
+    pub id: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Track {
+    pub id: String,
+    pub position: u32,
+    pub title: String,
+    pub length: Option<u32>,
+    #[serde(rename = "artist-credit", default)]
+    pub artist_credit: Vec<ArtistCredit>,
+    pub recording: Recording,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Medium {
+    #[serde(default)]
+    pub position: u32,
+    pub format: Option<String>,
+    #[serde(rename = "track-count", default)]
+    pub track_count: u32,
+    #[serde(default)]
+    pub tracks: Vec<Track>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Release {
+    pub id: String,
+    pub title: String,
+    pub date: Option<String>,
+    pub country: Option<String>,
+    pub score: Option<u32>,
+    #[serde(rename = "artist-credit", default)]
+    pub artist_credit: Vec<ArtistCredit>,
+    #[serde(default)]
+    pub media: Vec<Medium>,
+}
+
+#[derive(Deserialize)]
+struct ReleaseList {
+    #[serde(default)]
+    releases: Vec<Release>,
+}
+
+/// `"Artist A feat. Artist B"`, joined the way MusicBrainz credits them.
+pub fn credit_string(credits: &[ArtistCredit]) -> String {
+    credits.iter().map(|c| format!("{}{}", c.name, c.joinphrase)).collect()
+}
+
+impl Release {
+    pub fn track_count(&self) -> u32 {
+        self.media.iter().map(|m| m.track_count).sum()
+    }
+
+    /// Every track with the medium it's on, in play order.
+    pub fn tracks(&self) -> Vec<(&Medium, &Track)> {
+        self.media.iter().flat_map(|m| m.tracks.iter().map(move |t| (m, t))).collect()
+    }
+
+    pub fn summary(&self) -> String {
+        let formats: Vec<&str> = self.media.iter().filter_map(|m| m.format.as_deref()).collect();
+        format!(
+            "{} - {} ({}, {}, {}{})",
+            credit_string(&self.artist_credit),
+            self.title,
+            self.date.clone().unwrap_or_else(|| t!("mb-no-date")),
+            self.country.as_deref().unwrap_or("??"),
+            t!("mb-tracks", count = self.track_count()),
+            if formats.is_empty() { String::new() } else { format!(", {}", formats.join("+")) }
+        )
+    }
+}
+
+/// Escapes a value for a Lucene phrase query.
+fn phrase(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// A CD-style table of contents built from track lengths, for fuzzy disc lookups.
+/// Offsets are in 1/75 s sectors, starting after the usual 2 second pregap.
+pub fn toc_from_durations(durations_ms: &[u32]) -> String {
+    let mut offsets = Vec::new();
+    let mut position = 150u64;
+    for ms in durations_ms {
+        offsets.push(position);
+        position += *ms as u64 * 75 / 1000;
+    }
+    let mut parts = vec!["1".to_string(), durations_ms.len().to_string(), position.to_string()];
+    parts.extend(offsets.iter().map(|o| o.to_string()));
+    parts.join(" ")
+}
+
+pub struct Client {
+    base_url: String,
+    agent: ureq::Agent,
+}
+
+impl Client {
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            agent: ureq::AgentBuilder::new()
+                .user_agent(USER_AGENT)
+                .timeout(Duration::from_secs(20))
+                .build(),
+        }
+    }
+
+    fn get<T: for<'de> Deserialize<'de>>(&self, path: &str, query: &[(&str, &str)]) -> errors::Result<T> {
+        let mut request = self.agent.get(&format!("{}/{}", self.base_url, path)).query("fmt", "json");
+        for (key, value) in query {
+            request = request.query(key, value);
+        }
+        Ok(serde_json::from_reader(request.call()?.into_reader())?)
+    }
+
+    pub fn search_releases(&self, artist: &str, album: &str, track_count: usize) -> errors::Result<Vec<Release>> {
+        let mut terms = Vec::new();
+        if !artist.is_empty() {
+            terms.push(format!("artist:{}", phrase(artist)));
+        }
+        if !album.is_empty() {
+            terms.push(format!("release:{}", phrase(album)));
+        }
+        if terms.is_empty() {
+            return Err(t!("mb-need-terms").into());
+        }
+        if track_count > 0 {
+            // rank releases with the same number of tracks higher without excluding the rest
+            terms.push(format!("tracks:{}^2", track_count));
+        }
+        let query = terms.join(" ");
+        let list: ReleaseList = self.get("release", &[("query", &query), ("limit", "15")])?;
+        Ok(list.releases)
+    }
+
+    pub fn lookup_durations(&self, durations_ms: &[u32]) -> errors::Result<Vec<Release>> {
+        let toc = toc_from_durations(durations_ms);
+        let list: ReleaseList = self.get("discid/-", &[("toc", &toc), ("cdstubs", "no"), ("inc", "artist-credits")])?;
+        Ok(list.releases)
+    }
+
+    pub fn release(&self, id: &str) -> errors::Result<Release> {
+        self.get(&format!("release/{}", id), &[("inc", "recordings artist-credits")])
+    }
+}
+
+enum Reply {
+    Candidates(Vec<Release>),
+    Release(Box<Release>),
+}
+
+/// One file of the album being matched.
+struct AlbumFile {
+    path: String,
+    duration_ms: Option<u32>,
+}
+
+#[derive(Default)]
+pub struct MusicBrainzLookup {
+    open: bool,
+    base_url: String,
+    artist: String,
+    album: String,
+    files: Vec<AlbumFile>,
+    candidates: Vec<Release>,
+    release: Option<Release>,
+    pending: Option<Receiver<errors::Result<Reply>>>,
+    status: String,
+}
+
+impl MusicBrainzLookup {
+    /// The server to use, which is also where cover searches look up releases.
+    pub fn base_url(&self) -> &str {
+        if self.base_url.is_empty() { DEFAULT_BASE_URL } else { &self.base_url }
+    }
+
+    fn request(&mut self, work: impl FnOnce(Client) -> errors::Result<Reply> + Send + 'static) {
+        let (sender, receiver) = mpsc::channel();
+        let client = Client::new(self.base_url());
+        std::thread::spawn(move || {
+            let _ = sender.send(work(client));
+        });
+        self.pending = Some(receiver);
+        self.status = t!("mb-waiting");
+    }
+
+    fn poll(&mut self) {
+        let Some(receiver) = &self.pending else {
+            return;
+        };
+        let reply = match receiver.try_recv() {
+            Ok(reply) => reply,
+            Err(TryRecvError::Empty) => return,
+            Err(TryRecvError::Disconnected) => Err(t!("mb-dropped").into()),
+        };
+        self.pending = None;
+        match reply {
+            Ok(Reply::Candidates(releases)) => {
+                self.status = t!("mb-candidates", count = releases.len());
+                self.candidates = releases;
+                self.release = None;
+            }
+            Ok(Reply::Release(release)) => {
+                self.status.clear();
+                self.release = Some(*release);
+            }
+            Err(e) => self.status = t!("mb-lookup-failed", error = e.to_string()),
+        }
+    }
+}
+
+impl MyApp {
+    /// Loaded files sharing the selected file's album, in disc and track order.
+    /// Files without an album tag are grouped by folder instead.
+    pub(crate) fn selected_album_files(&self) -> Vec<String> {
+        let Some(selected) = &self.selected_file else {
+            return Vec::new();
+        };
+        let read = |path: &String| id3::no_tag_ok(Tag::read_from_path(path)).ok().flatten();
+        let album = read(selected).and_then(|t| t.album().map(str::to_string));
+        let folder = |path: &String| std::path::Path::new(path).parent().map(|p| p.to_path_buf());
+
+        let mut files: Vec<(u32, u32, String)> = self
+            .dropped_files
+            .iter()
+            .filter_map(|path| {
+                let tag = read(path);
+                let same = match &album {
+                    Some(album) => tag.as_ref().and_then(|t| t.album()) == Some(album.as_str()),
+                    None => folder(path) == folder(selected),
+                };
+                same.then(|| {
+                    let disc = tag.as_ref().and_then(|t| t.disc()).unwrap_or(0);
+                    let track = tag.as_ref().and_then(|t| t.track()).unwrap_or(0);
+                    (disc, track, path.clone())
+                })
+            })
+            .collect();
+        files.sort();
+        files.into_iter().map(|(_, _, path)| path).collect()
+    }
+
+    pub(crate) fn open_musicbrainz(&mut self) {
+        let files = self.selected_album_files();
+        let lookup = &mut self.musicbrainz;
+        if lookup.base_url.is_empty() {
+            lookup.base_url = DEFAULT_BASE_URL.to_string();
+        }
+        if let Some(first) = files.first() {
+            let tag = id3::no_tag_ok(Tag::read_from_path(first)).ok().flatten();
+            lookup.artist = tag
+                .as_ref()
+                .and_then(|t| t.album_artist().or(t.artist()))
+                .unwrap_or_default()
+                .to_string();
+            lookup.album = tag.as_ref().and_then(|t| t.album()).unwrap_or_default().to_string();
+        }
+        lookup.files = files
+            .into_iter()
+            .map(|path| AlbumFile {
+                duration_ms: MyApp::get_duration_seconds(&path).ok().flatten().map(|s| (s * 1000.0) as u32),
+                path,
+            })
+            .collect();
+        lookup.candidates.clear();
+        lookup.release = None;
+        lookup.status.clear();
+        lookup.open = true;
+    }
+
+    fn apply_release(&mut self) -> FileErrors {
+        let Some(release) = self.musicbrainz.release.clone() else {
+            return vec![(String::new(), t!("mb-no-release"))];
+        };
+        let album_artist = credit_string(&release.artist_credit);
+        let album_artist_id = release.artist_credit.first().map(|c| c.artist.id.clone());
+        let multi_disc = release.media.len() > 1;
+        let tracks = release.tracks();
+        let paths: Vec<String> = self.musicbrainz.files.iter().map(|f| f.path.clone()).collect();
+
+        let mut errors = Vec::new();
+        for (path, (medium, track)) in paths.iter().zip(tracks) {
+            let result = (|| -> errors::Result<()> {
+                let mut tag = id3::no_tag_ok(Tag::read_from_path(path))?.unwrap_or_default();
+                let artist = if track.artist_credit.is_empty() {
+                    album_artist.clone()
+                } else {
+                    credit_string(&track.artist_credit)
+                };
+
+                Field::Title.set(&mut tag, &track.title)?;
+                Field::Artist.set(&mut tag, &artist)?;
+                Field::Album.set(&mut tag, &release.title)?;
+                Field::AlbumArtist.set(&mut tag, &album_artist)?;
+                Field::Track.set(&mut tag, &format!("{}/{}", track.position, medium.track_count))?;
+                if multi_disc {
+                    Field::Disc.set(&mut tag, &format!("{}/{}", medium.position, release.media.len()))?;
+                }
+                if let Some(date) = &release.date {
+                    // a malformed date shouldn't stop the rest of the match being written
+                    let _ = Field::Year.set(&mut tag, date);
+                }
+
+                let mut ids = vec![
+                    ("MusicBrainz Album Id", Some(release.id.clone())),
+                    ("MusicBrainz Release Track Id", Some(track.id.clone())),
+                    ("MusicBrainz Album Artist Id", album_artist_id.clone()),
+                ];
+                let artist_id = track.artist_credit.first().map(|c| c.artist.id.clone());
+                ids.push(("MusicBrainz Artist Id", artist_id.or(album_artist_id.clone())));
+                for (description, value) in ids {
+                    tag.remove_extended_text(Some(description), None);
+                    if let Some(value) = value {
+                        tag.add_frame(ExtendedText { description: description.to_string(), value });
+                    }
+                }
+                tag.add_frame(UniqueFileIdentifier {
+                    owner_identifier: "http://musicbrainz.org".to_string(),
+                    identifier: track.recording.id.clone().into_bytes(),
+                });
+
+                self.write_tag(path, tag)
+            })();
+            if let Err(e) = result {
+                errors.push((path.clone(), e.to_string()));
+            }
+        }
+        errors
+    }
+
+    pub(crate) fn musicbrainz_window(&mut self, ctx: &egui::Context) {
+        self.musicbrainz.poll();
+        if self.musicbrainz.pending.is_some() {
+            ctx.request_repaint_after(Duration::from_millis(200));
+        }
+
+        let mut open = self.musicbrainz.open;
+        egui::Window::new(t!("mb-title")).open(&mut open).show(ctx, |ui| {
+            let lookup = &mut self.musicbrainz;
+            let busy = lookup.pending.is_some();
+
+            ui.horizontal(|ui| {
+                ui.label(t!("mb-server"));
+                ui.text_edit_singleline(&mut lookup.base_url);
+            });
+            ui.horizontal(|ui| {
+                ui.label(t!("online-artist"));
+                ui.add(egui::TextEdit::singleline(&mut lookup.artist).desired_width(140.0));
+                ui.label(t!("online-album"));
+                ui.add(egui::TextEdit::singleline(&mut lookup.album).desired_width(140.0));
+            });
+            ui.horizontal(|ui| {
+                if ui.add_enabled(!busy, egui::Button::new(t!("mb-search-tags"))).clicked() {
+                    let (artist, album, count) = (lookup.artist.clone(), lookup.album.clone(), lookup.files.len());
+                    lookup.request(move |client| Ok(Reply::Candidates(client.search_releases(&artist, &album, count)?)));
+                }
+                let durations: Option<Vec<u32>> = lookup.files.iter().map(|f| f.duration_ms).collect();
+                let can_use_durations = durations.as_ref().is_some_and(|d| !d.is_empty());
+                if ui
+                    .add_enabled(!busy && can_use_durations, egui::Button::new(t!("mb-search-durations")))
+                    .clicked()
+                {
+                    let durations = durations.unwrap_or_default();
+                    lookup.request(move |client| Ok(Reply::Candidates(client.lookup_durations(&durations)?)));
+                }
+                ui.label(&lookup.status);
+            });
+
+            ui.separator();
+            ui.label(t!("mb-album-files", count = lookup.files.len()));
+
+            let mut chosen = None;
+            egui::ScrollArea::vertical().id_salt("mb_candidates").max_height(120.0).show(ui, |ui| {
+                for release in &lookup.candidates {
+                    let selected = lookup.release.as_ref().is_some_and(|r| r.id == release.id);
+                    let text = match release.score {
+                        Some(score) => format!("[{}] {}", score, release.summary()),
+                        None => release.summary(),
+                    };
+                    if ui.selectable_label(selected, text).clicked() {
+                        chosen = Some(release.id.clone());
+                    }
+                }
+            });
+            if let Some(id) = chosen
+                && !busy
+            {
+                lookup.request(move |client| Ok(Reply::Release(Box::new(client.release(&id)?))));
+            }
+
+            let Some(release) = &lookup.release else {
+                return;
+            };
+            ui.separator();
+            let tracks = release.tracks();
+            egui::ScrollArea::vertical().id_salt("mb_alignment").max_height(200.0).show(ui, |ui| {
+                egui::Grid::new("mb_alignment_grid").num_columns(3).striped(true).show(ui, |ui| {
+                    for (i, file) in lookup.files.iter().enumerate() {
+                        ui.label(MyApp::get_file_name(&file.path));
+                        match tracks.get(i) {
+                            Some((medium, track)) => {
+                                ui.label(format!("{}.{} {}", medium.position, track.position, track.title));
+                                let diff = match (file.duration_ms, track.length) {
+                                    (Some(a), Some(b)) => Some(a as i64 - b as i64),
+                                    _ => None,
+                                };
+                                match diff {
+                                    Some(d) if d.abs() > LENGTH_TOLERANCE_MS => {
+                                        ui.colored_label(self.theme.color(Role::Error), format!("{:+}s", d / 1000))
+                                    }
+                                    Some(d) => ui.label(format!("{:+}s", d / 1000)),
+                                    None => ui.label("?"),
+                                };
+                            }
+                            None => {
+                                ui.colored_label(self.theme.color(Role::Error), t!("mb-no-track"));
+                                ui.label("");
+                            }
+                        }
+                        ui.end_row();
+                    }
+                });
+            });
+            if tracks.len() != lookup.files.len() {
+                ui.colored_label(
+                    self.theme.color(Role::Warning),
+                    t!("mb-count-mismatch", tracks = tracks.len(), files = lookup.files.len()),
+                );
+            }
+
+            if ui.button(t!("mb-apply")).clicked() {
+                let errors = self.apply_release();
+                if errors.is_empty() {
+                    self.alert_message.clear();
+                    self.musicbrainz.open = false;
+                }
+                self.report_file_errors("mb-apply-failed", errors);
+            }
+        });
+        self.musicbrainz.open &= open;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn toc_offsets_in_sectors() {
+        assert_eq!(toc_from_durations(&[60_000, 120_000, 1_000]), "1 3 13725 150 4650 13650");
+        assert_eq!(toc_from_durations(&[]), "1 0 150");
+    }
+
+    #[test]
+    fn credits_use_join_phrases() {
+        let credits: Vec<ArtistCredit> = serde_json::from_str(
+            r#"[
+                {"name": "Artist A", "joinphrase": " feat. ", "artist": {"id": "a"}},
+                {"name": "Artist B", "artist": {"id": "b"}}
+            ]"#,
+        )
+        .unwrap();
+        assert_eq!(credit_string(&credits), "Artist A feat. Artist B");
+        assert_eq!(credit_string(&[]), "");
+    }
+
+    #[test]
+    fn phrases_are_escaped() {
+        assert_eq!(phrase("AC/DC"), r#""AC/DC""#);
+        assert_eq!(phrase(r#"Say "Hi" \o/"#), r#""Say \"Hi\" \\o/""#);
+    }
+}
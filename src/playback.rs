@@ -0,0 +1,461 @@
+use std::fs::File;
+use std::io::{Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use symphonia::core::{
+    audio::SampleBuffer,
+    codecs::DecoderOptions,
+    errors::Error as SymphoniaError,
+    formats::{FormatOptions, SeekMode, SeekTo},
+    io::MediaSourceStream,
+    meta::MetadataOptions,
+    probe::Hint,
+    units::Time,
+};
+use symphonia::default::{get_codecs, get_probe};
+
+use crate::errors::{self, Error};
+use crate::i18n::t;
+
+/// Somewhere decoded audio ends up. Samples are interleaved `f32` in `[-1.0, 1.0]`.
+///
+/// `write` is expected to block for roughly as long as the audio takes to play,
+/// which is what keeps the transport position in step with what you hear.
+pub trait AudioSink {
+    fn configure(&mut self, sample_rate: u32, channels: usize) -> errors::Result<()>;
+    fn write(&mut self, samples: &[f32]) -> errors::Result<()>;
+    fn flush(&mut self) -> errors::Result<()> {
+        Ok(())
+    }
+    /// An error the sink ran into off the playback thread since it was last asked.
+    fn take_error(&mut self) -> Option<Error> {
+        None
+    }
+}
+
+/// Builds the sink on the playback thread, so sinks don't have to be `Send`.
+pub type SinkFactory = Box<dyn FnOnce() -> errors::Result<Box<dyn AudioSink>> + Send>;
+
+/// Throws samples away. With `realtime` set it sleeps for the length of each
+/// buffer so playback still advances at normal speed.
+pub struct NullSink {
+    realtime: bool,
+    sample_rate: u32,
+    channels: usize,
+}
+
+impl NullSink {
+    pub fn new(realtime: bool) -> Self {
+        Self { realtime, sample_rate: 44_100, channels: 2 }
+    }
+}
+
+impl AudioSink for NullSink {
+    fn configure(&mut self, sample_rate: u32, channels: usize) -> errors::Result<()> {
+        self.sample_rate = sample_rate.max(1);
+        self.channels = channels.max(1);
+        Ok(())
+    }
+
+    fn write(&mut self, samples: &[f32]) -> errors::Result<()> {
+        if self.realtime {
+            let frames = samples.len() / self.channels;
+            std::thread::sleep(Duration::from_secs_f64(frames as f64 / self.sample_rate as f64));
+        }
+        Ok(())
+    }
+}
+
+/// Writes everything it receives to a 16-bit PCM wav file, as fast as it is decoded.
+pub struct WavFileSink {
+    file: File,
+    data_len: u32,
+}
+
+impl WavFileSink {
+    pub fn create<P: AsRef<Path>>(path: P) -> errors::Result<Self> {
+        Ok(Self { file: File::create(path)?, data_len: 0 })
+    }
+
+    fn write_header(&mut self, sample_rate: u32, channels: u16) -> std::io::Result<()> {
+        let block_align = channels * 2;
+        self.file.seek(SeekFrom::Start(0))?;
+        self.file.write_all(b"RIFF")?;
+        self.file.write_all(&(36 + self.data_len).to_le_bytes())?;
+        self.file.write_all(b"WAVEfmt ")?;
+        self.file.write_all(&16u32.to_le_bytes())?;
+        self.file.write_all(&1u16.to_le_bytes())?;
+        self.file.write_all(&channels.to_le_bytes())?;
+        self.file.write_all(&sample_rate.to_le_bytes())?;
+        self.file.write_all(&(sample_rate * block_align as u32).to_le_bytes())?;
+        self.file.write_all(&block_align.to_le_bytes())?;
+        self.file.write_all(&16u16.to_le_bytes())?;
+        self.file.write_all(b"data")?;
+        self.file.write_all(&self.data_len.to_le_bytes())?;
+        Ok(())
+    }
+}
+
+impl AudioSink for WavFileSink {
+    fn configure(&mut self, sample_rate: u32, channels: usize) -> errors::Result<()> {
+        self.data_len = 0;
+        self.file.set_len(0)?;
+        self.write_header(sample_rate, channels as u16)?;
+        Ok(())
+    }
+
+    fn write(&mut self, samples: &[f32]) -> errors::Result<()> {
+        let mut bytes = Vec::with_capacity(samples.len() * 2);
+        for s in samples {
+            let v = (s.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+            bytes.extend_from_slice(&v.to_le_bytes());
+        }
+        self.file.seek(SeekFrom::End(0))?;
+        self.file.write_all(&bytes)?;
+        self.data_len += bytes.len() as u32;
+        Ok(())
+    }
+
+    fn flush(&mut self) -> errors::Result<()> {
+        // patch the two length fields now that the data size is known
+        self.file.seek(SeekFrom::Start(4))?;
+        self.file.write_all(&(36 + self.data_len).to_le_bytes())?;
+        self.file.seek(SeekFrom::Start(40))?;
+        self.file.write_all(&self.data_len.to_le_bytes())?;
+        self.file.flush()?;
+        Ok(())
+    }
+}
+
+/// Plays through the default output device. Needs the `cpal` feature (and ALSA headers on Linux).
+#[cfg(feature = "cpal")]
+#[derive(Default)]
+pub struct DeviceSink {
+    stream: Option<cpal::Stream>,
+    buffer: Arc<Mutex<std::collections::VecDeque<f32>>>,
+    max_buffered: usize,
+    /// Set by the output stream's error callback.
+    error: Arc<Mutex<Option<Error>>>,
+}
+
+#[cfg(feature = "cpal")]
+impl DeviceSink {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[cfg(feature = "cpal")]
+impl AudioSink for DeviceSink {
+    fn configure(&mut self, sample_rate: u32, channels: usize) -> errors::Result<()> {
+        use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+
+        let device = cpal::default_host()
+            .default_output_device()
+            .ok_or_else(|| t!("playback-no-device"))?;
+        let config = cpal::StreamConfig {
+            channels: channels as u16,
+            sample_rate: cpal::SampleRate(sample_rate),
+            buffer_size: cpal::BufferSize::Default,
+        };
+
+        self.buffer.lock().unwrap().clear();
+        // keep a quarter of a second queued up
+        self.max_buffered = sample_rate as usize * channels / 4;
+
+        let buffer = self.buffer.clone();
+        let error = self.error.clone();
+        let stream = device.build_output_stream(
+            &config,
+            move |data: &mut [f32], _: &cpal::OutputCallbackInfo| {
+                let mut queued = buffer.lock().unwrap();
+                for sample in data.iter_mut() {
+                    *sample = queued.pop_front().unwrap_or(0.0);
+                }
+            },
+            move |err| *error.lock().unwrap() = Some(Error::Io(std::io::Error::other(err))),
+            None,
+        )
+        .map_err(|e| Error::Unsupported(e.to_string()))?;
+        stream.play().map_err(std::io::Error::other)?;
+        self.stream = Some(stream);
+        Ok(())
+    }
+
+    fn write(&mut self, samples: &[f32]) -> errors::Result<()> {
+        while self.buffer.lock().unwrap().len() > self.max_buffered {
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        self.buffer.lock().unwrap().extend(samples.iter().copied());
+        Ok(())
+    }
+
+    fn take_error(&mut self) -> Option<Error> {
+        self.error.lock().unwrap().take()
+    }
+}
+
+/// Whether `default_sink` reaches a sound card; without `cpal` it plays silently.
+pub const HAS_OUTPUT: bool = cfg!(feature = "cpal");
+
+/// The sink the editor uses: the sound card when built with `cpal`, otherwise a silent realtime sink.
+pub fn default_sink() -> SinkFactory {
+    #[cfg(feature = "cpal")]
+    {
+        Box::new(|| Ok(Box::new(DeviceSink::new()) as Box<dyn AudioSink>))
+    }
+    #[cfg(not(feature = "cpal"))]
+    {
+        Box::new(|| Ok(Box::new(NullSink::new(true)) as Box<dyn AudioSink>))
+    }
+}
+
+pub fn file_sink(path: PathBuf) -> SinkFactory {
+    Box::new(move || Ok(Box::new(WavFileSink::create(path)?) as Box<dyn AudioSink>))
+}
+
+enum Command {
+    Play,
+    Pause,
+    Seek(f64),
+    Stop,
+}
+
+struct Shared {
+    position_bits: AtomicU64,
+    paused: AtomicBool,
+    finished: AtomicBool,
+    error: Mutex<Option<Error>>,
+}
+
+/// Decodes a file on a background thread and feeds it to a sink.
+pub struct Player {
+    path: String,
+    commands: Sender<Command>,
+    shared: Arc<Shared>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Player {
+    pub fn start(path: &str, sink: SinkFactory) -> Self {
+        let (commands, receiver) = mpsc::channel();
+        let shared = Arc::new(Shared {
+            position_bits: AtomicU64::new(0f64.to_bits()),
+            paused: AtomicBool::new(false),
+            finished: AtomicBool::new(false),
+            error: Mutex::new(None),
+        });
+
+        let thread_shared = shared.clone();
+        let thread_path = path.to_string();
+        let handle = std::thread::spawn(move || {
+            if let Err(e) = run(&thread_path, sink, receiver, &thread_shared) {
+                *thread_shared.error.lock().unwrap() = Some(e);
+            }
+            thread_shared.finished.store(true, Ordering::SeqCst);
+        });
+
+        Self { path: path.to_string(), commands, shared, handle: Some(handle) }
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn play(&self) {
+        let _ = self.commands.send(Command::Play);
+    }
+
+    pub fn pause(&self) {
+        let _ = self.commands.send(Command::Pause);
+    }
+
+    pub fn seek(&self, seconds: f64) {
+        self.shared.position_bits.store(seconds.to_bits(), Ordering::SeqCst);
+        let _ = self.commands.send(Command::Seek(seconds));
+    }
+
+    pub fn position_seconds(&self) -> f64 {
+        f64::from_bits(self.shared.position_bits.load(Ordering::SeqCst))
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.shared.paused.load(Ordering::SeqCst)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.shared.finished.load(Ordering::SeqCst)
+    }
+
+    pub fn take_error(&self) -> Option<Error> {
+        self.shared.error.lock().unwrap().take()
+    }
+}
+
+impl Drop for Player {
+    fn drop(&mut self) {
+        let _ = self.commands.send(Command::Stop);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+fn run(
+    path: &str,
+    sink: SinkFactory,
+    commands: Receiver<Command>,
+    shared: &Shared,
+) -> errors::Result<()> {
+    let file = File::open(path)?;
+    let mss = MediaSourceStream::new(Box::new(file), Default::default());
+    let probed = get_probe().format(
+        &Hint::new(),
+        mss,
+        &FormatOptions::default(),
+        &MetadataOptions::default(),
+    )?;
+    let mut format = probed.format;
+    let track = format.default_track().ok_or_else(|| t!("audio-no-track"))?;
+    let track_id = track.id;
+    let time_base = track.codec_params.time_base;
+    let mut decoder = get_codecs().make(&track.codec_params, &DecoderOptions::default())?;
+
+    let mut sink = sink()?;
+    let mut configured: Option<(u32, usize)> = None;
+    let mut samples: Option<SampleBuffer<f32>> = None;
+    // where playback really is, since `Player::seek` shows the target before it's reached
+    let mut played = 0f64;
+
+    loop {
+        // while paused, block until something tells us to carry on
+        let command = if shared.paused.load(Ordering::SeqCst) {
+            Some(commands.recv().unwrap_or(Command::Stop))
+        } else {
+            match commands.try_recv() {
+                Ok(command) => Some(command),
+                Err(TryRecvError::Empty) => None,
+                Err(TryRecvError::Disconnected) => Some(Command::Stop),
+            }
+        };
+
+        match command {
+            Some(Command::Play) => shared.paused.store(false, Ordering::SeqCst),
+            Some(Command::Pause) => {
+                shared.paused.store(true, Ordering::SeqCst);
+                continue;
+            }
+            Some(Command::Seek(seconds)) => {
+                let target = SeekTo::Time { time: Time::from(seconds.max(0.0)), track_id: Some(track_id) };
+                match format.seek(SeekMode::Accurate, target) {
+                    Ok(_) => decoder.reset(),
+                    // carry on from where we were
+                    Err(e) => {
+                        *shared.error.lock().unwrap() = Some(e.into());
+                        shared.position_bits.store(played.to_bits(), Ordering::SeqCst);
+                    }
+                }
+                continue;
+            }
+            Some(Command::Stop) => break,
+            None => {}
+        }
+
+        let packet = match format.next_packet() {
+            Ok(packet) => packet,
+            Err(SymphoniaError::IoError(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => break,
+            Err(SymphoniaError::ResetRequired) => {
+                decoder.reset();
+                continue;
+            }
+            Err(e) => return Err(e.into()),
+        };
+        if packet.track_id() != track_id {
+            continue;
+        }
+
+        let decoded = match decoder.decode(&packet) {
+            Ok(decoded) => decoded,
+            // a corrupt frame shouldn't stop the preview
+            Err(SymphoniaError::DecodeError(_)) => continue,
+            Err(e) => return Err(e.into()),
+        };
+
+        let spec = *decoded.spec();
+        let format_key = (spec.rate, spec.channels.count());
+        if configured != Some(format_key) {
+            sink.configure(spec.rate, spec.channels.count())?;
+            configured = Some(format_key);
+        }
+
+        let buffer = match &mut samples {
+            Some(buffer) if buffer.capacity() >= decoded.capacity() * spec.channels.count() => buffer,
+            _ => samples.insert(SampleBuffer::new(decoded.capacity() as u64, spec)),
+        };
+        buffer.copy_interleaved_ref(decoded);
+        sink.write(buffer.samples())?;
+        if let Some(e) = sink.take_error() {
+            return Err(e);
+        }
+
+        if let Some(tb) = time_base {
+            let time = tb.calc_time(packet.ts() + packet.dur());
+            played = time.seconds as f64 + time.frac;
+            shared.position_bits.store(played.to_bits(), Ordering::SeqCst);
+        }
+    }
+
+    sink.flush()
+}
+
+/// `mm:ss`, for the transport readout.
+pub fn format_time(seconds: f64) -> String {
+    let total = seconds.max(0.0).floor() as u64;
+    format!("{}:{:02}", total / 60, total % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_into_wav_file() {
+        let dir = std::env::temp_dir().join(format!("metadata-playback-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let (input, output) = (dir.join("in.wav"), dir.join("out.wav"));
+
+        // a tenth of a second of a stereo ramp at 8 kHz
+        let samples: Vec<f32> = (0..1600).map(|i| (i % 200) as f32 / 200.0 - 0.5).collect();
+        let mut sink = WavFileSink::create(&input).unwrap();
+        sink.configure(8_000, 2).unwrap();
+        sink.write(&samples).unwrap();
+        sink.flush().unwrap();
+        drop(sink);
+
+        let player = Player::start(input.to_str().unwrap(), file_sink(output.clone()));
+        for _ in 0..500 {
+            if player.is_finished() {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert!(player.is_finished());
+        assert!(player.take_error().is_none());
+        assert!((player.position_seconds() - 0.1).abs() < 1e-6);
+        drop(player);
+
+        let (written, decoded) = (std::fs::read(&input).unwrap(), std::fs::read(&output).unwrap());
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(decoded.len(), written.len());
+        assert_eq!(decoded[..44], written[..44]);
+        for (a, b) in written[44..].chunks(2).zip(decoded[44..].chunks(2)) {
+            let (a, b) = (i16::from_le_bytes([a[0], a[1]]), i16::from_le_bytes([b[0], b[1]]));
+            assert!((a - b).abs() <= 1, "{a} != {b}");
+        }
+    }
+}
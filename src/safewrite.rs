@@ -0,0 +1,246 @@
+use std::fs::{File, FileTimes};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use eframe::egui;
+
+use crate::MyApp;
+use crate::audio;
+use crate::errors::{self, Error};
+use crate::i18n::t;
+
+const BACKUP_EXTENSION: &str = "bak";
+
+#[derive(Default)]
+pub struct WriteSettings {
+    /// Give the rewritten file the original's access and modification times and mode.
+    pub preserve_times: bool,
+    pub keep_backups: bool,
+    /// Where the previous version of each file is copied before it's replaced.
+    pub backup_dir: Option<PathBuf>,
+    /// Backups older than this many days are deleted; 0 keeps them forever.
+    pub backup_days: u32,
+    open: bool,
+}
+
+/// The temporary sibling a file is edited as, so the rename over it stays on one filesystem.
+fn temp_path(path: &Path) -> PathBuf {
+    let name = path.file_name().map(|n| n.to_string_lossy()).unwrap_or_default();
+    path.with_file_name(format!(".{}.tmp", name))
+}
+
+/// Flushes the directory entry so a rename into it survives a crash.
+#[cfg(unix)]
+fn sync_parent(path: &Path) -> errors::Result<()> {
+    let parent = path.parent().filter(|p| !p.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    File::open(parent)?.sync_all()?;
+    Ok(())
+}
+
+/// Windows can't open a directory as a file to flush it.
+#[cfg(not(unix))]
+fn sync_parent(_path: &Path) -> errors::Result<()> {
+    Ok(())
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+/// Copies `path` into the backup folder as `<name>.<unix time>.bak`.
+fn back_up(path: &Path, dir: &Path) -> errors::Result<()> {
+    std::fs::create_dir_all(dir)?;
+    let name = path.file_name().ok_or_else(|| t!("save-no-name"))?.to_string_lossy();
+    let mut stamp = now_secs();
+    let mut target = dir.join(format!("{}.{}.{}", name, stamp, BACKUP_EXTENSION));
+    while target.exists() {
+        stamp += 1;
+        target = dir.join(format!("{}.{}.{}", name, stamp, BACKUP_EXTENSION));
+    }
+    std::fs::copy(path, target)?;
+    Ok(())
+}
+
+/// The unix time in the name of a backup `back_up` made, `<name>.mp3.<unix time>.bak`.
+fn backup_stamp(file_name: &str) -> Option<u64> {
+    let rest = file_name.strip_suffix(BACKUP_EXTENSION)?.strip_suffix('.')?;
+    let (name, stamp) = rest.rsplit_once('.')?;
+    let is_mp3 = name.len() > 4 && name[name.len() - 4..].eq_ignore_ascii_case(".mp3");
+    if !is_mp3 || stamp.is_empty() || !stamp.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    stamp.parse().ok()
+}
+
+/// Deletes backups whose timestamp is more than `days` old. Returns how many went.
+/// Only files named the way `back_up` names them are touched.
+pub fn prune_backups(dir: &Path, days: u32) -> errors::Result<usize> {
+    if days == 0 || !dir.exists() {
+        return Ok(0);
+    }
+    let cutoff = now_secs().saturating_sub(days as u64 * 24 * 60 * 60);
+    let mut removed = 0;
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        if !entry.file_type()?.is_file() {
+            continue;
+        }
+        let stamp = entry.file_name().to_str().and_then(backup_stamp);
+        if stamp.is_some_and(|s| s < cutoff) {
+            let path = entry.path();
+            std::fs::remove_file(path)?;
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+/// Applies `edit` to a temporary copy of `path`, checks the audio came through
+/// untouched, backs up the original if configured and renames the copy over it.
+/// The original is left as it was if any step fails.
+pub fn replace<F>(path: &str, settings: &WriteSettings, edit: F) -> errors::Result<()>
+where
+    F: FnOnce(&Path) -> errors::Result<()>,
+{
+    let original = Path::new(path);
+    let metadata = std::fs::metadata(original).map_err(|e| Error::from(e).at(original))?;
+    // the rename would go through even so, as long as the folder is writable
+    if metadata.permissions().readonly() {
+        return Err(Error::ReadOnly(original.to_path_buf()));
+    }
+    let temp = temp_path(original);
+    let result = (|| -> errors::Result<()> {
+        std::fs::copy(original, &temp)?;
+        edit(&temp)?;
+        if audio::stream_hash(&temp)? != audio::stream_hash(original)? {
+            return Err(t!("save-audio-changed").into());
+        }
+        if settings.preserve_times {
+            let times = FileTimes::new().set_accessed(metadata.accessed()?).set_modified(metadata.modified()?);
+            File::options().write(true).open(&temp)?.set_times(times)?;
+            std::fs::set_permissions(&temp, metadata.permissions())?;
+        }
+        // the data has to be on disk before the rename makes it the only copy
+        File::options().write(true).open(&temp)?.sync_all()?;
+        if settings.keep_backups
+            && let Some(dir) = &settings.backup_dir
+        {
+            back_up(original, dir)?;
+            prune_backups(dir, settings.backup_days)?;
+        }
+        std::fs::rename(&temp, original)?;
+        sync_parent(original)?;
+        Ok(())
+    })()
+    .map_err(|e| e.at(original));
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp);
+    }
+    result
+}
+
+impl MyApp {
+    /// `replace` with the current settings, noting the new state of the file so it isn't taken for an outside change
+    /// and the tags it had before so the write can be undone.
+    pub(crate) fn replace_file<F>(&mut self, path: &str, edit: F) -> errors::Result<()>
+    where
+        F: FnOnce(&Path) -> errors::Result<()>,
+    {
+        let before = std::fs::read(path).ok().map(|bytes| audio::tag_regions(&bytes));
+        replace(path, &self.write_settings, edit)?;
+        self.watcher.remember(path);
+        if let Some(regions) = before {
+            self.undo.push((path.to_string(), regions));
+            if self.undo.len() > crate::MAX_UNDO {
+                self.undo.remove(0);
+            }
+        }
+        // the bitrate shown is worked out from the file size
+        self.audio_info = None;
+        Ok(())
+    }
+
+    pub(crate) fn open_write_settings(&mut self) {
+        self.write_settings.open = true;
+    }
+
+    pub(crate) fn write_settings_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.write_settings.open;
+        egui::Window::new(t!("save-title")).open(&mut open).show(ctx, |ui| {
+            ui.label(t!("save-explanation"));
+            let settings = &mut self.write_settings;
+            ui.checkbox(&mut settings.preserve_times, t!("save-preserve-times"));
+            ui.separator();
+            ui.checkbox(&mut settings.keep_backups, t!("save-keep-backups"));
+            if settings.keep_backups {
+                ui.horizontal(|ui| {
+                    let label = settings
+                        .backup_dir
+                        .as_ref()
+                        .map(|d| d.display().to_string())
+                        .unwrap_or_else(|| t!("save-no-folder"));
+                    ui.label(label);
+                    if ui.button(t!("save-choose-folder")).clicked()
+                        && let Some(dir) = rfd::FileDialog::new().pick_folder()
+                    {
+                        settings.backup_dir = Some(dir);
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.label(t!("save-prune-before"));
+                    ui.add(egui::DragValue::new(&mut settings.backup_days).range(0..=3650));
+                    ui.label(t!("save-prune-after"));
+                });
+                if let Some(dir) = settings.backup_dir.clone()
+                    && ui.button(t!("save-prune-now")).clicked()
+                {
+                    match prune_backups(&dir, settings.backup_days) {
+                        Ok(n) => self.alert_message = t!("save-pruned", count = n),
+                        Err(e) => self.report_error(None, t!("save-prune-failed", error = e.to_string())),
+                    }
+                }
+            }
+        });
+        self.write_settings.open &= open;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backup_names() {
+        assert_eq!(backup_stamp("song.mp3.1700000000.bak"), Some(1700000000));
+        assert_eq!(backup_stamp("a.b.MP3.12.bak"), Some(12));
+        assert_eq!(backup_stamp("notes.txt.1700000000.bak"), None);
+        assert_eq!(backup_stamp("song.mp3.bak"), None);
+        assert_eq!(backup_stamp("song.mp3.12a.bak"), None);
+        assert_eq!(backup_stamp("1700000000.bak"), None);
+        assert_eq!(backup_stamp("song.mp3.1700000000.bak.old"), None);
+    }
+
+    #[test]
+    fn prune_keeps_recent_and_foreign_files() {
+        let dir = std::env::temp_dir().join(format!("metadata-prune-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let now = now_secs();
+        let old = now - 10 * 24 * 60 * 60;
+        let names = [
+            format!("old.mp3.{}.bak", old),
+            format!("new.mp3.{}.bak", now),
+            format!("other.db.{}.bak", old),
+            "readme.bak".to_string(),
+        ];
+        for name in &names {
+            std::fs::write(dir.join(name), b"x").unwrap();
+        }
+
+        assert_eq!(prune_backups(&dir, 0).unwrap(), 0);
+        assert_eq!(prune_backups(&dir, 5).unwrap(), 1);
+        assert!(!dir.join(&names[0]).exists());
+        assert!(names[1..].iter().all(|n| dir.join(n).exists()));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
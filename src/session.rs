@@ -0,0 +1,206 @@
+use std::path::{Path, PathBuf};
+
+use eframe::egui::{self, Color32, KeyboardShortcut};
+use serde::{Deserialize, Serialize};
+
+use crate::MyApp;
+use crate::commands::Command;
+use crate::errors;
+use crate::fields::Separator;
+use crate::i18n::{self, t};
+use crate::theme::{Look, Preset, Role};
+use crate::versions;
+
+const MAX_RECENT: usize = 10;
+
+/// What's kept between runs, stored through eframe's persistence.
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+struct Session {
+    files: Vec<String>,
+    selected: Option<String>,
+    recent: Recent,
+    export_columns: Vec<(String, bool)>,
+    /// The minor number of the preferred ID3 version, since `id3::Version` isn't serializable.
+    preferred_version: Option<u8>,
+    always_preferred: bool,
+    separator: Separator,
+    custom_genres: Vec<String>,
+    max_art_size: u32,
+    preserve_times: bool,
+    keep_backups: bool,
+    backup_dir: Option<PathBuf>,
+    backup_days: u32,
+    acoustid_key: String,
+    shortcuts: Vec<(Command, Option<KeyboardShortcut>)>,
+    theme: Preset,
+    colors: Vec<(Look, Role, Color32)>,
+    /// The interface language picked from the menu; the system's language when unset.
+    language: Option<String>,
+}
+
+/// Recently added files and the folders they came from, newest first.
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Recent {
+    files: Vec<String>,
+    folders: Vec<String>,
+}
+
+fn push_front(list: &mut Vec<String>, item: String) {
+    list.retain(|i| *i != item);
+    list.insert(0, item);
+    list.truncate(MAX_RECENT);
+}
+
+impl Recent {
+    fn add(&mut self, path: &str) {
+        push_front(&mut self.files, path.to_string());
+        if let Some(dir) = Path::new(path).parent() {
+            push_front(&mut self.folders, dir.display().to_string());
+        }
+    }
+}
+
+fn is_mp3(path: &Path) -> bool {
+    path.extension().and_then(|e| e.to_str()).is_some_and(|e| e.eq_ignore_ascii_case("mp3"))
+}
+
+impl MyApp {
+    /// The app as it was when it was last closed.
+    pub(crate) fn restore(cc: &eframe::CreationContext) -> MyApp {
+        let session = cc.storage.and_then(|s| eframe::get_value::<Session>(s, eframe::APP_KEY));
+        match session.as_ref().and_then(|s| s.language.as_deref()) {
+            Some(language) => i18n::choose_language(language),
+            None => i18n::set_language(i18n::system_language()),
+        }
+        let mut app = MyApp::default();
+        if let Some(session) = session {
+            app.apply_session(session, &cc.egui_ctx);
+        }
+        app
+    }
+
+    fn apply_session(&mut self, session: Session, ctx: &egui::Context) {
+        self.dropped_files = session.files.into_iter().filter(|f| Path::new(f).exists()).collect();
+        self.recent = session.recent;
+        self.spreadsheet.set_column_layout(&session.export_columns);
+        self.version_settings.preferred = versions::ALL.into_iter().find(|v| Some(v.minor()) == session.preferred_version);
+        self.version_settings.always_preferred = session.always_preferred;
+        self.separator = session.separator;
+        self.genres.custom = session.custom_genres;
+        self.linter.max_art_size = session.max_art_size;
+        self.write_settings.preserve_times = session.preserve_times;
+        self.write_settings.keep_backups = session.keep_backups;
+        self.write_settings.backup_dir = session.backup_dir;
+        self.write_settings.backup_days = session.backup_days;
+        self.identify.api_key = session.acoustid_key;
+        self.commands.overrides = session.shortcuts.into_iter().collect();
+        self.theme.preset = session.theme;
+        self.theme.overrides = session.colors.into_iter().map(|(look, role, color)| ((look, role), color)).collect();
+        if let Some(selected) = session.selected
+            && self.dropped_files.contains(&selected)
+        {
+            self.selected_file = Some(selected.clone());
+            self.show_album_art(ctx, &selected);
+        }
+    }
+
+    fn session(&self) -> Session {
+        Session {
+            files: self.dropped_files.clone(),
+            selected: self.selected_file.clone(),
+            recent: self.recent.clone(),
+            export_columns: self.spreadsheet.column_layout(),
+            preferred_version: self.version_settings.preferred.map(|v| v.minor()),
+            always_preferred: self.version_settings.always_preferred,
+            separator: self.separator,
+            custom_genres: self.genres.custom.clone(),
+            max_art_size: self.linter.max_art_size,
+            preserve_times: self.write_settings.preserve_times,
+            keep_backups: self.write_settings.keep_backups,
+            backup_dir: self.write_settings.backup_dir.clone(),
+            backup_days: self.write_settings.backup_days,
+            acoustid_key: self.identify.api_key.clone(),
+            shortcuts: self.commands.overrides.iter().map(|(c, s)| (*c, *s)).collect(),
+            theme: self.theme.preset,
+            colors: self.theme.overrides.iter().map(|((look, role), color)| (*look, *role, *color)).collect(),
+            language: i18n::chosen_language().map(str::to_string),
+        }
+    }
+
+    pub(crate) fn save_session(&self, storage: &mut dyn eframe::Storage) {
+        eframe::set_value(storage, eframe::APP_KEY, &self.session());
+    }
+
+    /// Adds a file to the song list and the recent files.
+    pub(crate) fn add_file(&mut self, path: String) {
+        self.recent.add(&path);
+        if !self.dropped_files.contains(&path) {
+            self.dropped_files.push(path);
+            self.dropped_files.sort();
+        }
+    }
+
+    /// Adds every mp3 directly inside `dir`.
+    pub(crate) fn add_folder(&mut self, dir: &str) -> errors::Result<()> {
+        let mut files = Vec::new();
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.is_file() && is_mp3(&path) {
+                files.push(path.display().to_string());
+            }
+        }
+        if files.is_empty() {
+            return Err(t!("recent-no-mp3s", folder = dir).into());
+        }
+        for file in files {
+            self.add_file(file);
+        }
+        Ok(())
+    }
+
+    pub(crate) fn recent_menu(&mut self, ui: &mut egui::Ui) {
+        let mut add_file = None;
+        let mut add_folder = None;
+        if self.recent.files.is_empty() {
+            ui.label(t!("recent-none"));
+        }
+        for file in &self.recent.files {
+            if ui.button(MyApp::get_file_name(file)).on_hover_text(file).clicked() {
+                add_file = Some(file.clone());
+            }
+        }
+        if !self.recent.folders.is_empty() {
+            ui.separator();
+            ui.label(t!("recent-folders"));
+            for folder in &self.recent.folders {
+                let name = Path::new(folder).file_name().map(|n| n.to_string_lossy()).unwrap_or_default();
+                if ui.button(name).on_hover_text(folder).clicked() {
+                    add_folder = Some(folder.clone());
+                }
+            }
+        }
+        ui.separator();
+        if ui.button(t!("recent-clear")).clicked() {
+            self.recent = Recent::default();
+            ui.close_menu();
+        }
+
+        if let Some(file) = add_file {
+            if Path::new(&file).exists() {
+                self.add_file(file);
+            } else {
+                self.recent.files.retain(|f| *f != file);
+                self.report_warning(Some(&file), t!("recent-missing", file = MyApp::get_file_name(&file)));
+            }
+            ui.close_menu();
+        }
+        if let Some(folder) = add_folder {
+            if let Err(e) = self.add_folder(&folder) {
+                self.report_error(None, t!("recent-folder-failed", error = e.to_string()));
+            }
+            ui.close_menu();
+        }
+    }
+}
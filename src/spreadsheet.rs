@@ -0,0 +1,445 @@
+use std::collections::HashMap;
+
+use eframe::egui;
+use id3::Tag;
+use serde_json::{Map, Value};
+
+use crate::MyApp;
+use crate::audio;
+use crate::errorlog::FileErrors;
+use crate::errors;
+use crate::fields::Field;
+use crate::i18n::t;
+
+/// A column of the exported sheet.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Column {
+    Path,
+    FileName,
+    /// Hash of the audio stream, for matching rows after files were moved or renamed.
+    Key,
+    Field(Field),
+}
+
+impl Column {
+    fn all() -> Vec<Column> {
+        let mut columns = vec![Column::Path, Column::FileName, Column::Key];
+        columns.extend(Field::ALL.into_iter().map(Column::Field));
+        columns
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Column::Path => "Path",
+            Column::FileName => "File Name",
+            Column::Key => "Key",
+            Column::Field(field) => field.name(),
+        }
+    }
+
+    fn value(self, path: &str, tag: Option<&Tag>) -> errors::Result<String> {
+        Ok(match self {
+            Column::Path => path.to_string(),
+            Column::FileName => MyApp::get_file_name(&path.to_string()).to_string(),
+            Column::Key => audio::stream_hash(path)?,
+            Column::Field(field) => tag.and_then(|t| field.get(t)).unwrap_or_default(),
+        })
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum MatchBy {
+    Path,
+    Key,
+}
+
+/// Changes one imported row would make to one file.
+struct PendingImport {
+    path: String,
+    changes: Vec<(Field, Option<String>, String)>,
+    apply: bool,
+}
+
+#[derive(Default)]
+pub struct SpreadsheetTool {
+    export_open: bool,
+    columns: Vec<(Column, bool)>,
+    import_open: bool,
+    match_by: Option<MatchBy>,
+    source: Option<String>,
+    rows: Vec<HashMap<String, String>>,
+    preview: Vec<PendingImport>,
+    unmatched: usize,
+}
+
+impl SpreadsheetTool {
+    pub fn open_export(&mut self) {
+        if self.columns.is_empty() {
+            self.columns = Column::all()
+                .into_iter()
+                .map(|c| (c, !matches!(c, Column::FileName | Column::Key)))
+                .collect();
+        }
+        self.export_open = true;
+    }
+
+    pub fn open_import(&mut self) {
+        self.import_open = true;
+    }
+
+    /// The export columns by name, in order, with whether each is ticked.
+    pub fn column_layout(&self) -> Vec<(String, bool)> {
+        self.columns.iter().map(|(c, on)| (c.name().to_string(), *on)).collect()
+    }
+
+    /// Restores a saved `column_layout`; columns it doesn't name go last, unticked.
+    pub fn set_column_layout(&mut self, layout: &[(String, bool)]) {
+        let all = Column::all();
+        self.columns = layout
+            .iter()
+            .filter_map(|(name, on)| Some((*all.iter().find(|c| c.name() == name)?, *on)))
+            .collect();
+        if self.columns.is_empty() {
+            return;
+        }
+        for column in all {
+            if !self.columns.iter().any(|(c, _)| *c == column) {
+                self.columns.push((column, false));
+            }
+        }
+    }
+}
+
+/// Every loaded file's selected columns, one row per file. Files that can't be read are left out.
+fn collect_rows(files: &[String], columns: &[Column]) -> (Vec<Vec<String>>, FileErrors) {
+    let mut rows = Vec::new();
+    let mut errors = Vec::new();
+    for path in files {
+        let row = (|| -> errors::Result<Vec<String>> {
+            let tag = id3::no_tag_ok(Tag::read_from_path(path))?;
+            columns.iter().map(|column| column.value(path, tag.as_ref())).collect()
+        })();
+        match row {
+            Ok(row) => rows.push(row),
+            Err(e) => errors.push((path.clone(), e.to_string())),
+        }
+    }
+    (rows, errors)
+}
+
+pub fn write_csv(columns: &[Column], rows: &[Vec<String>]) -> errors::Result<Vec<u8>> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(columns.iter().map(|c| c.name()))?;
+    for row in rows {
+        writer.write_record(row)?;
+    }
+    Ok(writer.into_inner().map_err(|e| e.into_error())?)
+}
+
+pub fn write_json(columns: &[Column], rows: &[Vec<String>]) -> errors::Result<Vec<u8>> {
+    let objects: Vec<Value> = rows
+        .iter()
+        .map(|row| {
+            let map: Map<String, Value> = columns
+                .iter()
+                .zip(row)
+                .map(|(c, v)| (c.name().to_string(), Value::String(v.clone())))
+                .collect();
+            Value::Object(map)
+        })
+        .collect();
+    Ok(serde_json::to_vec_pretty(&objects)?)
+}
+
+pub fn read_csv(input: &[u8]) -> errors::Result<Vec<HashMap<String, String>>> {
+    let mut reader = csv::ReaderBuilder::new().flexible(true).from_reader(input);
+    let headers = reader.headers()?.clone();
+    let mut rows = Vec::new();
+    for record in reader.records() {
+        let record = record?;
+        rows.push(
+            headers
+                .iter()
+                .zip(record.iter())
+                .map(|(h, v)| (h.to_string(), v.to_string()))
+                .collect(),
+        );
+    }
+    Ok(rows)
+}
+
+pub fn read_json(input: &[u8]) -> errors::Result<Vec<HashMap<String, String>>> {
+    let value: Value = serde_json::from_slice(input)?;
+    let entries = value.as_array().ok_or_else(|| t!("sheet-not-array"))?;
+    let mut rows = Vec::new();
+    for entry in entries {
+        let object = entry.as_object().ok_or_else(|| t!("sheet-not-array"))?;
+        rows.push(
+            object
+                .iter()
+                .map(|(k, v)| {
+                    let value = match v {
+                        Value::String(s) => s.clone(),
+                        Value::Null => String::new(),
+                        other => other.to_string(),
+                    };
+                    (k.clone(), value)
+                })
+                .collect(),
+        );
+    }
+    Ok(rows)
+}
+
+/// Looks a column up by its exported name, ignoring case.
+fn cell<'a>(row: &'a HashMap<String, String>, name: &str) -> Option<&'a String> {
+    row.iter().find(|(k, _)| k.eq_ignore_ascii_case(name)).map(|(_, v)| v)
+}
+
+impl MyApp {
+    /// Writes the sheet, returning the files that were left out of it.
+    fn export_spreadsheet(&mut self) -> errors::Result<FileErrors> {
+        let columns: Vec<Column> = self
+            .spreadsheet
+            .columns
+            .iter()
+            .filter(|(_, enabled)| *enabled)
+            .map(|(c, _)| *c)
+            .collect();
+        if columns.is_empty() {
+            return Err(t!("sheet-no-columns").into());
+        }
+
+        let Some(save_path) = rfd::FileDialog::new()
+            .add_filter(t!("filter-csv"), &["csv"])
+            .add_filter(t!("filter-json"), &["json"])
+            .set_file_name("tags.csv")
+            .save_file()
+        else {
+            return Ok(Vec::new());
+        };
+
+        let (rows, skipped) = collect_rows(&self.dropped_files, &columns);
+        let is_json = save_path.extension().and_then(|e| e.to_str()) == Some("json");
+        let output = if is_json {
+            write_json(&columns, &rows)?
+        } else {
+            write_csv(&columns, &rows)?
+        };
+        std::fs::write(save_path, output)?;
+        Ok(skipped)
+    }
+
+    fn load_import_sheet(&mut self) -> errors::Result<FileErrors> {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter(t!("filter-spreadsheet"), &["csv", "json"])
+            .pick_file()
+        else {
+            return Ok(Vec::new());
+        };
+        let input = std::fs::read(&path)?;
+        let rows = if path.extension().and_then(|e| e.to_str()) == Some("json") {
+            read_json(&input)?
+        } else {
+            read_csv(&input)?
+        };
+
+        let tool = &mut self.spreadsheet;
+        let has_key = rows.first().is_some_and(|r| cell(r, Column::Key.name()).is_some());
+        tool.match_by = Some(if has_key { MatchBy::Key } else { MatchBy::Path });
+        tool.source = Some(path.display().to_string());
+        tool.rows = rows;
+        Ok(self.preview_import())
+    }
+
+    /// Matches the loaded rows to files and works out what would change, leaving out files whose tag can't be read.
+    fn preview_import(&mut self) -> FileErrors {
+        let match_by = self.spreadsheet.match_by.unwrap_or(MatchBy::Path);
+        let keys: HashMap<String, String> = if match_by == MatchBy::Key {
+            self.dropped_files
+                .iter()
+                .filter_map(|path| Some((audio::stream_hash(path).ok()?, path.clone())))
+                .collect()
+        } else {
+            HashMap::new()
+        };
+
+        let mut preview = Vec::new();
+        let mut skipped = Vec::new();
+        let mut unmatched = 0;
+        for row in &self.spreadsheet.rows {
+            let path = match match_by {
+                MatchBy::Path => cell(row, Column::Path.name())
+                    .filter(|p| self.dropped_files.contains(p))
+                    .cloned(),
+                MatchBy::Key => cell(row, Column::Key.name()).and_then(|k| keys.get(k.trim())).cloned(),
+            };
+            let Some(path) = path else {
+                unmatched += 1;
+                continue;
+            };
+
+            let tag = match id3::no_tag_ok(Tag::read_from_path(&path)) {
+                Ok(tag) => tag,
+                Err(e) => {
+                    skipped.push((path, errors::Error::from(e).to_string()));
+                    continue;
+                }
+            };
+            let mut changes = Vec::new();
+            for (header, value) in row {
+                let Some(field) = Field::from_name(header) else {
+                    continue;
+                };
+                let old = tag.as_ref().and_then(|t| field.get(t));
+                if old.as_deref().unwrap_or("") != value.trim() {
+                    changes.push((field, old, value.trim().to_string()));
+                }
+            }
+            if !changes.is_empty() {
+                changes.sort_by_key(|(f, _, _)| Field::ALL.iter().position(|a| a == f));
+                preview.push(PendingImport { path, changes, apply: true });
+            }
+        }
+
+        self.spreadsheet.preview = preview;
+        self.spreadsheet.unmatched = unmatched;
+        skipped
+    }
+
+    fn apply_import(&mut self) -> FileErrors {
+        let pending = std::mem::take(&mut self.spreadsheet.preview);
+        let mut errors = Vec::new();
+        for import in pending.iter().filter(|p| p.apply) {
+            let result = (|| -> errors::Result<()> {
+                let mut tag = id3::no_tag_ok(Tag::read_from_path(&import.path))?.unwrap_or_default();
+                for (field, _, value) in &import.changes {
+                    field.set(&mut tag, value)?;
+                }
+                self.write_tag(&import.path, tag)
+            })();
+            if let Err(e) = result {
+                errors.push((import.path.clone(), e.to_string()));
+            }
+        }
+        errors
+    }
+
+    pub(crate) fn spreadsheet_windows(&mut self, ctx: &egui::Context) {
+        let mut export_open = self.spreadsheet.export_open;
+        egui::Window::new(t!("sheet-export-title")).open(&mut export_open).show(ctx, |ui| {
+            ui.label(t!("sheet-export-summary", count = self.dropped_files.len()));
+            for (column, enabled) in &mut self.spreadsheet.columns {
+                ui.checkbox(enabled, column.name());
+            }
+            if ui.button(t!("sheet-export")).clicked() {
+                match self.export_spreadsheet() {
+                    Ok(skipped) => {
+                        self.report_file_errors("sheet-export-skipped", skipped);
+                        self.spreadsheet.export_open = false;
+                    }
+                    Err(e) => self.report_error(None, t!("sheet-export-failed", error = e.to_string())),
+                }
+            }
+        });
+        self.spreadsheet.export_open &= export_open;
+
+        let mut import_open = self.spreadsheet.import_open;
+        egui::Window::new(t!("sheet-import-title")).open(&mut import_open).show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                if ui.button(t!("sheet-open")).clicked() {
+                    match self.load_import_sheet() {
+                        Ok(skipped) => self.report_file_errors("sheet-import-skipped", skipped),
+                        Err(e) => self.report_error(None, t!("sheet-read-failed", error = e.to_string())),
+                    }
+                }
+                if let Some(source) = &self.spreadsheet.source {
+                    ui.label(MyApp::get_file_name(source).to_string());
+                }
+            });
+
+            if self.spreadsheet.source.is_none() {
+                return;
+            }
+
+            let before = self.spreadsheet.match_by;
+            ui.horizontal(|ui| {
+                ui.label(t!("sheet-match-by"));
+                ui.radio_value(&mut self.spreadsheet.match_by, Some(MatchBy::Path), t!("sheet-by-path"));
+                ui.radio_value(&mut self.spreadsheet.match_by, Some(MatchBy::Key), t!("sheet-by-key"));
+            });
+            if before != self.spreadsheet.match_by {
+                let skipped = self.preview_import();
+                self.report_file_errors("sheet-import-skipped", skipped);
+            }
+
+            ui.label(t!(
+                "sheet-import-summary",
+                files = self.spreadsheet.preview.len(),
+                unmatched = self.spreadsheet.unmatched
+            ));
+
+            egui::ScrollArea::vertical().max_height(250.0).show(ui, |ui| {
+                for import in &mut self.spreadsheet.preview {
+                    ui.checkbox(&mut import.apply, MyApp::get_file_name(&import.path).to_string());
+                    for (field, old, new) in &import.changes {
+                        ui.label(format!(
+                            "    {}: {} -> {}",
+                            field.label(),
+                            old.clone().unwrap_or_else(|| t!("sheet-empty")),
+                            if new.is_empty() { t!("sheet-empty") } else { new.clone() }
+                        ));
+                    }
+                }
+            });
+
+            if ui.button(t!("sheet-apply")).clicked() {
+                let errors = self.apply_import();
+                if errors.is_empty() {
+                    self.alert_message.clear();
+                    self.spreadsheet.import_open = false;
+                }
+                self.report_file_errors("sheet-import-failed", errors);
+                self.lyrics.reset();
+                self.chapters.reset();
+            }
+        });
+        self.spreadsheet.import_open &= import_open;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unreadable_files_are_skipped() {
+        let dir = std::env::temp_dir().join(format!("metadata-sheet-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let good = dir.join("good.mp3");
+        std::fs::write(&good, [0xff; 64]).unwrap();
+        let missing = dir.join("missing.mp3");
+        let files = [good.display().to_string(), missing.display().to_string()];
+
+        let (rows, skipped) = collect_rows(&files, &[Column::FileName]);
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(rows, [["good.mp3"]]);
+        assert_eq!(skipped.len(), 1);
+        assert_eq!(skipped[0].0, files[1]);
+    }
+
+    #[test]
+    fn preview_leaves_out_unreadable_files() {
+        let missing = std::env::temp_dir().join(format!("metadata-preview-{}.mp3", std::process::id()));
+        let missing = missing.display().to_string();
+        let mut app = MyApp { dropped_files: vec![missing.clone()], ..Default::default() };
+        app.spreadsheet.match_by = Some(MatchBy::Path);
+        let row = [(Column::Path.name().to_string(), missing.clone()), ("Title".to_string(), "New".to_string())];
+        app.spreadsheet.rows = vec![row.into_iter().collect()];
+
+        let skipped = app.preview_import();
+        assert!(app.spreadsheet.preview.is_empty());
+        assert_eq!(skipped.len(), 1);
+        assert_eq!(skipped[0].0, missing);
+    }
+}
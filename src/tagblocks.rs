@@ -0,0 +1,466 @@
+use std::io::Write;
+use std::path::Path;
+
+use eframe::egui;
+use id3::Tag;
+
+use crate::MyApp;
+use crate::errors;
+use crate::fields::{Field, Separator};
+use crate::genres::GENRES;
+use crate::i18n::t;
+use crate::theme::Role;
+
+/// The fields all three tag types can hold.
+const SHARED: [Field; 7] = [
+    Field::Title,
+    Field::Artist,
+    Field::Album,
+    Field::Year,
+    Field::Track,
+    Field::Genre,
+    Field::Comment,
+];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Block {
+    Id3v2,
+    Id3v1,
+    Ape,
+}
+
+impl Block {
+    pub const ALL: [Block; 3] = [Block::Id3v2, Block::Id3v1, Block::Ape];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Block::Id3v2 => "ID3v2",
+            Block::Id3v1 => "ID3v1",
+            Block::Ape => "APEv2",
+        }
+    }
+
+    fn index(self) -> usize {
+        Block::ALL.iter().position(|b| *b == self).unwrap_or(0)
+    }
+}
+
+fn ape_key(field: Field) -> &'static str {
+    match field {
+        Field::Track => "Track",
+        Field::Comment => "Comment",
+        other => other.name(),
+    }
+}
+
+/// How many bytes ID3v1 has for a field, if it's limited.
+fn id3v1_limit(field: Field) -> Option<usize> {
+    match field {
+        Field::Title | Field::Artist | Field::Album => Some(30),
+        // two bytes go to the ID3v1.1 track number
+        Field::Comment => Some(28),
+        Field::Year => Some(4),
+        _ => None,
+    }
+}
+
+/// The value as ID3v1 would store it: Latin-1, cut to length, track without a total.
+pub fn id3v1_value(field: Field, value: &str) -> String {
+    let value: String = value.chars().map(|c| if (c as u32) < 0x100 { c } else { '?' }).collect();
+    match field {
+        Field::Track => value.split('/').next().unwrap_or_default().trim().to_string(),
+        Field::Genre => value,
+        _ => match id3v1_limit(field) {
+            Some(limit) => value.chars().take(limit).collect(),
+            None => value,
+        },
+    }
+}
+
+/// The shared fields of each tag type found in one file, `None` where a block is absent.
+pub struct Blocks {
+    pub values: [Option<Vec<(Field, String)>>; 3],
+}
+
+impl Blocks {
+    pub fn read(path: &str, separator: Separator) -> Blocks {
+        let v2 = id3::no_tag_ok(Tag::read_from_path(path)).ok().flatten().map(|tag| {
+            SHARED
+                .iter()
+                .map(|f| {
+                    let value = if *f == Field::Genre {
+                        f.values(&tag, separator).join("; ")
+                    } else {
+                        f.get(&tag).unwrap_or_default().replace('\0', "; ")
+                    };
+                    (*f, value)
+                })
+                .collect()
+        });
+        let v1 = id3::v1::Tag::read_from_path(path).ok().map(|tag| {
+            SHARED
+                .iter()
+                .map(|f| {
+                    let value = match f {
+                        Field::Title => tag.title.clone(),
+                        Field::Artist => tag.artist.clone(),
+                        Field::Album => tag.album.clone(),
+                        Field::Year => tag.year.clone(),
+                        Field::Track => tag.track.map(|t| t.to_string()).unwrap_or_default(),
+                        Field::Genre => tag.genre().unwrap_or_default().to_string(),
+                        _ => tag.comment.clone(),
+                    };
+                    (*f, value.trim().to_string())
+                })
+                .collect()
+        });
+        let ape = ape::read_from_path(path).ok().map(|tag| {
+            SHARED
+                .iter()
+                .map(|f| {
+                    let value = tag
+                        .item(ape_key(*f))
+                        .and_then(|item| <&str>::try_from(item).ok())
+                        .unwrap_or_default();
+                    (*f, value.trim().to_string())
+                })
+                .collect()
+        });
+        Blocks { values: [v2, v1, ape] }
+    }
+
+    pub fn get(&self, block: Block, field: Field) -> Option<&str> {
+        self.values[block.index()]
+            .as_ref()?
+            .iter()
+            .find(|(f, _)| *f == field)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Whether the blocks that have the field disagree on it. ID3v1 only
+    /// conflicts when it differs from what it could have stored.
+    pub fn conflicts(&self, field: Field) -> bool {
+        let present: Vec<(Block, &str)> = Block::ALL
+            .iter()
+            .filter_map(|b| Some((*b, self.get(*b, field).filter(|v| !v.is_empty())?)))
+            .collect();
+        for (i, (a_block, a)) in present.iter().enumerate() {
+            for (b_block, b) in &present[i + 1..] {
+                let (a, b) = match (a_block, b_block) {
+                    (Block::Id3v1, _) => (a.to_string(), id3v1_value(field, b)),
+                    (_, Block::Id3v1) => (id3v1_value(field, a), b.to_string()),
+                    _ => (a.to_string(), b.to_string()),
+                };
+                if a.to_lowercase() != b.to_lowercase() {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+}
+
+/// Replaces any ID3v1 trailer with one holding `values`.
+fn write_id3v1(path: &Path, values: &[(Field, String)]) -> errors::Result<()> {
+    let get = |field: Field| values.iter().find(|(f, _)| *f == field).map(|(_, v)| id3v1_value(field, v)).unwrap_or_default();
+    let mut block = [0u8; 128];
+    block[..3].copy_from_slice(b"TAG");
+    let mut put = |offset: usize, len: usize, text: &str| {
+        for (i, c) in text.chars().take(len).enumerate() {
+            block[offset + i] = c as u32 as u8;
+        }
+    };
+    put(3, 30, &get(Field::Title));
+    put(33, 30, &get(Field::Artist));
+    put(63, 30, &get(Field::Album));
+    put(93, 4, &get(Field::Year));
+    put(97, 28, &get(Field::Comment));
+    if let Ok(track) = get(Field::Track).parse::<u8>() {
+        block[126] = track;
+    }
+    let genre = get(Field::Genre);
+    block[127] = GENRES
+        .iter()
+        .position(|g| g.eq_ignore_ascii_case(&genre))
+        .map(|i| i as u8)
+        .unwrap_or(255);
+
+    id3::v1::Tag::remove_from_path(path)?;
+    std::fs::OpenOptions::new().append(true).open(path)?.write_all(&block)?;
+    Ok(())
+}
+
+fn write_ape(path: &Path, values: &[(Field, String)]) -> errors::Result<()> {
+    let mut tag = ape::read_from_path(path).unwrap_or_default();
+    for (field, value) in values {
+        if value.is_empty() {
+            tag.remove_items(ape_key(*field));
+        } else {
+            tag.set_item(ape::Item::new(ape_key(*field), ape::ItemType::Text, value.as_bytes())?);
+        }
+    }
+    ape::write_to_path(&tag, path)?;
+    Ok(())
+}
+
+/// A change made to one file at a time, over the selected file or all of them.
+type FileAction = fn(&mut MyApp, &str) -> errors::Result<()>;
+
+#[derive(Default)]
+pub struct TagBlocksTool {
+    open: bool,
+    blocks: Option<Blocks>,
+    source: Option<Block>,
+    targets: [bool; 3],
+    strip: [bool; 3],
+    all_files: bool,
+}
+
+impl MyApp {
+    pub(crate) fn open_tag_blocks(&mut self) {
+        let separator = self.separator;
+        let tool = &mut self.tag_blocks;
+        tool.blocks = self.selected_file.as_deref().map(|p| Blocks::read(p, separator));
+        tool.source.get_or_insert(Block::Id3v2);
+        tool.open = true;
+    }
+
+    fn scope(&self) -> Vec<String> {
+        if self.tag_blocks.all_files {
+            self.dropped_files.clone()
+        } else {
+            self.selected_file.iter().cloned().collect()
+        }
+    }
+
+    /// Copies the source block's non-empty values into the target blocks of one file.
+    fn sync_blocks(&mut self, path: &str) -> errors::Result<()> {
+        let source = self.tag_blocks.source.unwrap_or(Block::Id3v2);
+        let blocks = Blocks::read(path, self.separator);
+        let Some(values) = blocks.values[source.index()].clone() else {
+            return Err(t!("blocks-no-tag", block = source.name()).into());
+        };
+        let values: Vec<(Field, String)> = values.into_iter().filter(|(_, v)| !v.is_empty()).collect();
+
+        for target in Block::ALL {
+            if target == source || !self.tag_blocks.targets[target.index()] {
+                continue;
+            }
+            // keep what the target has for fields the source leaves empty
+            let mut merged = blocks.values[target.index()].clone().unwrap_or_default();
+            for (field, value) in &values {
+                merged.retain(|(f, _)| f != field);
+                merged.push((*field, value.clone()));
+            }
+            match target {
+                Block::Id3v2 => {
+                    let mut tag = id3::no_tag_ok(Tag::read_from_path(path))?.unwrap_or_default();
+                    for (field, value) in &values {
+                        field.set(&mut tag, value)?;
+                    }
+                    self.write_tag(path, tag)?;
+                }
+                Block::Id3v1 => self.replace_file(path, |temp| write_id3v1(temp, &merged))?,
+                Block::Ape => self.replace_file(path, |temp| write_ape(temp, &values))?,
+            }
+        }
+        Ok(())
+    }
+
+    fn strip_blocks(&mut self, path: &str) -> errors::Result<()> {
+        let strip = self.tag_blocks.strip;
+        self.replace_file(path, |temp| {
+            // APE first: it's found relative to the ID3v1 trailer
+            if strip[Block::Ape.index()] {
+                ape::remove_from_path(temp)?;
+            }
+            if strip[Block::Id3v1.index()] {
+                id3::v1::Tag::remove_from_path(temp)?;
+            }
+            if strip[Block::Id3v2.index()] {
+                Tag::remove_from_path(temp)?;
+            }
+            Ok(())
+        })?;
+        if strip[Block::Id3v2.index()] && self.selected_file.as_deref() == Some(path) {
+            self.cached_tag = None;
+        }
+        Ok(())
+    }
+
+    /// Runs `action` on each file in scope; `failed` is the key of the message listing the errors.
+    fn run_on_scope(&mut self, action: FileAction, failed: &str) {
+        let mut errors = Vec::new();
+        for path in self.scope() {
+            if let Err(e) = action(self, &path) {
+                errors.push((path.clone(), e.to_string()));
+            }
+        }
+        self.report_file_errors(failed, errors);
+        self.tag_blocks.blocks = self.selected_file.as_deref().map(|p| Blocks::read(p, self.separator));
+    }
+
+    pub(crate) fn tag_blocks_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.tag_blocks.open;
+        let mut sync = false;
+        let mut strip = false;
+        egui::Window::new(t!("blocks-title")).open(&mut open).show(ctx, |ui| {
+            let tool = &mut self.tag_blocks;
+            let Some(blocks) = &tool.blocks else {
+                ui.label(t!("blocks-no-file"));
+                return;
+            };
+
+            egui::Grid::new("tag_blocks").num_columns(4).striped(true).show(ui, |ui| {
+                ui.label("");
+                for block in Block::ALL {
+                    let present = blocks.values[block.index()].is_some();
+                    ui.label(egui::RichText::new(block.name()).strong())
+                        .on_hover_text(if present { t!("blocks-present") } else { t!("blocks-absent") });
+                }
+                ui.end_row();
+                for field in SHARED {
+                    let color = if blocks.conflicts(field) {
+                        self.theme.color(Role::Warning)
+                    } else {
+                        self.theme.color(Role::Text)
+                    };
+                    ui.label(egui::RichText::new(field.label()).color(color));
+                    for block in Block::ALL {
+                        ui.label(blocks.get(block, field).unwrap_or("-"));
+                    }
+                    ui.end_row();
+                }
+            });
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.label(t!("blocks-copy-from"));
+                egui::ComboBox::from_id_salt("tag_blocks_source")
+                    .selected_text(tool.source.map(Block::name).unwrap_or_default())
+                    .show_ui(ui, |ui| {
+                        for block in Block::ALL {
+                            ui.selectable_value(&mut tool.source, Some(block), block.name());
+                        }
+                    });
+                ui.label(t!("blocks-copy-to"));
+                for block in Block::ALL {
+                    if Some(block) != tool.source {
+                        ui.checkbox(&mut tool.targets[block.index()], block.name());
+                    }
+                }
+            });
+
+            let source = tool.source.unwrap_or(Block::Id3v2);
+            if tool.targets[Block::Id3v1.index()] && source != Block::Id3v1 {
+                for field in SHARED {
+                    if let Some(value) = blocks.get(source, field)
+                        && id3v1_value(field, value) != value
+                    {
+                        let preview = t!("blocks-v1-preview", field = field.label(), value = id3v1_value(field, value));
+                        ui.label(format!("    {}", preview));
+                    }
+                }
+            }
+            sync = ui.button(t!("blocks-sync")).clicked();
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.label(t!("blocks-strip-label"));
+                for block in Block::ALL {
+                    ui.checkbox(&mut tool.strip[block.index()], block.name());
+                }
+                strip = ui.add_enabled(tool.strip.iter().any(|s| *s), egui::Button::new(t!("blocks-strip"))).clicked();
+            });
+
+            ui.separator();
+            ui.checkbox(&mut tool.all_files, t!("blocks-all-files", count = self.dropped_files.len()));
+        });
+        self.tag_blocks.open &= open;
+
+        if sync {
+            self.run_on_scope(MyApp::sync_blocks, "blocks-sync-failed");
+        }
+        if strip {
+            self.run_on_scope(MyApp::strip_blocks, "blocks-strip-failed");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(pairs: &[(Field, &str)]) -> Option<Vec<(Field, String)>> {
+        Some(pairs.iter().map(|(f, v)| (*f, v.to_string())).collect())
+    }
+
+    #[test]
+    fn id3v1_limits() {
+        let long = "a".repeat(35);
+        assert_eq!(id3v1_value(Field::Title, &long).len(), 30);
+        assert_eq!(id3v1_value(Field::Comment, &long).len(), 28);
+        assert_eq!(id3v1_value(Field::Year, "1999-05-01"), "1999");
+        assert_eq!(id3v1_value(Field::Track, " 7 /12"), "7");
+        assert_eq!(id3v1_value(Field::Artist, "Björk 東京"), "Björk ??");
+    }
+
+    #[test]
+    fn writes_id3v1_trailer() {
+        let dir = std::env::temp_dir().join(format!("metadata-blocks-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("file.mp3");
+        std::fs::write(&path, [0xff; 300]).unwrap();
+
+        let tags = [
+            (Field::Title, "t".repeat(35)),
+            (Field::Comment, "c".repeat(31)),
+            (Field::Year, "19995".to_string()),
+            (Field::Track, "7/12".to_string()),
+            (Field::Genre, "pop".to_string()),
+        ];
+        write_id3v1(&path, &tags).unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+        assert_eq!(bytes.len(), 428);
+        let block = &bytes[300..];
+        assert_eq!(&block[..3], b"TAG");
+        assert_eq!(&block[3..33], "t".repeat(30).as_bytes());
+        assert_eq!(&block[93..97], b"1999");
+        assert_eq!(&block[97..125], "c".repeat(28).as_bytes());
+        assert_eq!(block[125], 0);
+        assert_eq!(block[126], 7);
+        assert_eq!(block[127], 13);
+
+        let blocks = Blocks::read(&path.display().to_string(), Separator::default());
+        assert!(blocks.values[Block::Id3v2.index()].is_none());
+        assert_eq!(blocks.get(Block::Id3v1, Field::Track), Some("7"));
+        assert_eq!(blocks.get(Block::Id3v1, Field::Genre), Some("Pop"));
+
+        // a second write replaces the trailer, and an unknown genre is stored as 255
+        write_id3v1(&path, &[(Field::Genre, "Chiptune Noise".to_string())]).unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(bytes.len(), 428);
+        assert_eq!(bytes[427], 255);
+        assert_eq!(bytes[426], 0);
+    }
+
+    #[test]
+    fn conflicts_allow_for_id3v1_limits() {
+        let long = "A Title Much Longer Than Thirty Bytes";
+        let blocks = Blocks {
+            values: [
+                values(&[(Field::Title, long), (Field::Track, "7/12"), (Field::Artist, "Band")]),
+                values(&[(Field::Title, &long[..30]), (Field::Track, "7"), (Field::Artist, "band")]),
+                values(&[(Field::Title, long), (Field::Artist, "Other Band")]),
+            ],
+        };
+        assert!(!blocks.conflicts(Field::Title));
+        assert!(!blocks.conflicts(Field::Track));
+        assert!(blocks.conflicts(Field::Artist));
+        assert!(!blocks.conflicts(Field::Comment));
+
+        let only_v1 = Blocks { values: [None, values(&[(Field::Title, "x")]), None] };
+        assert!(!only_v1.conflicts(Field::Title));
+    }
+}
@@ -0,0 +1,232 @@
+use std::collections::HashMap;
+
+use eframe::egui::{self, Color32, Stroke, Theme, ThemePreference, Visuals};
+use serde::{Deserialize, Serialize};
+
+use crate::MyApp;
+use crate::i18n::t;
+
+/// The theme the user picked.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Preset {
+    /// Dark or light, following the operating system.
+    #[default]
+    System,
+    Dark,
+    Light,
+    HighContrast,
+}
+
+impl Preset {
+    const ALL: [Preset; 4] = [Preset::System, Preset::Dark, Preset::Light, Preset::HighContrast];
+
+    fn name(self) -> String {
+        t!(match self {
+            Preset::System => "theme-system",
+            Preset::Dark => "theme-dark",
+            Preset::Light => "theme-light",
+            Preset::HighContrast => "theme-high-contrast",
+        })
+    }
+}
+
+/// A theme as it's drawn: `Preset::System` resolved to dark or light.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Look {
+    #[default]
+    Dark,
+    Light,
+    HighContrast,
+}
+
+impl Look {
+    /// The key of the heading above this look's colors.
+    fn colors_heading(self) -> &'static str {
+        match self {
+            Look::Dark => "theme-colors-dark",
+            Look::Light => "theme-colors-light",
+            Look::HighContrast => "theme-colors-high-contrast",
+        }
+    }
+}
+
+/// The colors the app picks itself, on top of egui's own visuals.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Role {
+    Panel,
+    Text,
+    MutedText,
+    Item,
+    Selection,
+    SelectedText,
+    Changed,
+    Error,
+    Warning,
+}
+
+impl Role {
+    const ALL: [Role; 9] = [
+        Role::Panel,
+        Role::Text,
+        Role::MutedText,
+        Role::Item,
+        Role::Selection,
+        Role::SelectedText,
+        Role::Changed,
+        Role::Error,
+        Role::Warning,
+    ];
+
+    fn name(self) -> String {
+        t!(match self {
+            Role::Panel => "role-panel",
+            Role::Text => "role-text",
+            Role::MutedText => "role-muted-text",
+            Role::Item => "role-item",
+            Role::Selection => "role-selection",
+            Role::SelectedText => "role-selected-text",
+            Role::Changed => "role-changed",
+            Role::Error => "role-error",
+            Role::Warning => "role-warning",
+        })
+    }
+
+    fn preset(self, look: Look) -> Color32 {
+        match (look, self) {
+            (Look::Dark, Role::Panel) => Color32::from_rgb(20, 20, 20),
+            (Look::Dark, Role::Text) => Color32::WHITE,
+            (Look::Dark, Role::MutedText) => Color32::GRAY,
+            (Look::Dark, Role::Item) => Color32::from_rgb(60, 60, 60),
+            (Look::Dark, Role::Selection) => Color32::from_rgb(100, 150, 255),
+            (Look::Dark, Role::SelectedText) => Color32::WHITE,
+            (Look::Dark, Role::Changed) => Color32::from_rgb(80, 65, 20),
+            (Look::Dark, Role::Error) => Color32::RED,
+            (Look::Dark, Role::Warning) => Color32::from_rgb(255, 140, 90),
+
+            (Look::Light, Role::Panel) => Color32::from_rgb(235, 235, 235),
+            (Look::Light, Role::Text) => Color32::from_rgb(20, 20, 20),
+            (Look::Light, Role::MutedText) => Color32::from_rgb(90, 90, 90),
+            (Look::Light, Role::Item) => Color32::from_rgb(210, 210, 210),
+            (Look::Light, Role::Selection) => Color32::from_rgb(60, 110, 220),
+            (Look::Light, Role::SelectedText) => Color32::WHITE,
+            (Look::Light, Role::Changed) => Color32::from_rgb(255, 230, 150),
+            (Look::Light, Role::Error) => Color32::from_rgb(190, 0, 0),
+            (Look::Light, Role::Warning) => Color32::from_rgb(170, 80, 0),
+
+            (Look::HighContrast, Role::Panel) => Color32::BLACK,
+            (Look::HighContrast, Role::Text) => Color32::WHITE,
+            (Look::HighContrast, Role::MutedText) => Color32::from_rgb(210, 210, 210),
+            (Look::HighContrast, Role::Item) => Color32::from_rgb(40, 40, 40),
+            (Look::HighContrast, Role::Selection) => Color32::YELLOW,
+            (Look::HighContrast, Role::SelectedText) => Color32::BLACK,
+            (Look::HighContrast, Role::Changed) => Color32::from_rgb(0, 90, 160),
+            (Look::HighContrast, Role::Error) => Color32::from_rgb(255, 90, 90),
+            (Look::HighContrast, Role::Warning) => Color32::YELLOW,
+        }
+    }
+}
+
+/// Dark visuals with pure black and white and heavier outlines.
+fn high_contrast_visuals() -> Visuals {
+    let mut visuals = Visuals::dark();
+    visuals.override_text_color = Some(Color32::WHITE);
+    visuals.panel_fill = Color32::BLACK;
+    visuals.window_fill = Color32::BLACK;
+    visuals.extreme_bg_color = Color32::BLACK;
+    visuals.hyperlink_color = Color32::YELLOW;
+    visuals.selection.bg_fill = Color32::from_rgb(0, 90, 160);
+    visuals.selection.stroke = Stroke::new(1.0, Color32::WHITE);
+    visuals.window_stroke = Stroke::new(2.0, Color32::WHITE);
+    for widget in [&mut visuals.widgets.noninteractive, &mut visuals.widgets.inactive] {
+        widget.bg_stroke = Stroke::new(1.0, Color32::WHITE);
+        widget.fg_stroke = Stroke::new(1.5, Color32::WHITE);
+    }
+    for widget in [&mut visuals.widgets.hovered, &mut visuals.widgets.active] {
+        widget.bg_stroke = Stroke::new(2.0, Color32::YELLOW);
+        widget.fg_stroke = Stroke::new(2.0, Color32::YELLOW);
+    }
+    visuals
+}
+
+#[derive(Default)]
+pub struct ThemeSettings {
+    pub preset: Preset,
+    /// Colors the user changed from each look's own.
+    pub overrides: HashMap<(Look, Role), Color32>,
+    look: Look,
+    /// What was last handed to egui, so visuals are only replaced when the look changes.
+    applied: Option<Look>,
+    open: bool,
+}
+
+impl ThemeSettings {
+    pub fn color(&self, role: Role) -> Color32 {
+        self.overrides.get(&(self.look, role)).copied().unwrap_or_else(|| role.preset(self.look))
+    }
+}
+
+impl MyApp {
+    /// Points egui at the chosen theme; with `Preset::System` this follows the OS as it changes.
+    pub(crate) fn apply_theme(&mut self, ctx: &egui::Context) {
+        let theme = &mut self.theme;
+        let preference = match theme.preset {
+            Preset::System => ThemePreference::System,
+            Preset::Dark | Preset::HighContrast => ThemePreference::Dark,
+            Preset::Light => ThemePreference::Light,
+        };
+        if ctx.options(|o| o.theme_preference) != preference {
+            ctx.set_theme(preference);
+        }
+        theme.look = match (theme.preset, ctx.theme()) {
+            (Preset::HighContrast, _) => Look::HighContrast,
+            (_, Theme::Light) => Look::Light,
+            (_, Theme::Dark) => Look::Dark,
+        };
+        if theme.applied != Some(theme.look) {
+            let dark = if theme.look == Look::HighContrast { high_contrast_visuals() } else { Visuals::dark() };
+            ctx.set_visuals_of(Theme::Dark, dark);
+            theme.applied = Some(theme.look);
+        }
+    }
+
+    pub(crate) fn open_theme_settings(&mut self) {
+        self.theme.open = true;
+    }
+
+    pub(crate) fn theme_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.theme.open;
+        egui::Window::new(t!("theme-title")).open(&mut open).show(ctx, |ui| {
+            let theme = &mut self.theme;
+            ui.horizontal(|ui| {
+                for preset in Preset::ALL {
+                    ui.radio_value(&mut theme.preset, preset, preset.name());
+                }
+            });
+
+            ui.separator();
+            ui.label(t!(theme.look.colors_heading()));
+            egui::Grid::new("theme_colors").show(ui, |ui| {
+                for role in Role::ALL {
+                    ui.label(role.name());
+                    let mut color = theme.color(role);
+                    let key = (theme.look, role);
+                    if ui.color_edit_button_srgba(&mut color).changed() {
+                        theme.overrides.insert(key, color);
+                    }
+                    if ui.add_enabled(theme.overrides.contains_key(&key), egui::Button::new(t!("theme-reset"))).clicked() {
+                        theme.overrides.remove(&key);
+                    }
+                    ui.end_row();
+                }
+            });
+            let look = theme.look;
+            if ui
+                .add_enabled(theme.overrides.keys().any(|(l, _)| *l == look), egui::Button::new(t!("theme-reset-all")))
+                .clicked()
+            {
+                theme.overrides.retain(|(l, _), _| *l != look);
+            }
+        });
+        self.theme.open &= open;
+    }
+}
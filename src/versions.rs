@@ -0,0 +1,300 @@
+use eframe::egui;
+use id3::{Frame, Tag, TagLike, Timestamp, Version};
+
+use crate::MyApp;
+use crate::audio;
+use crate::errorlog::FileErrors;
+use crate::errors;
+use crate::i18n::t;
+
+/// Frames ID3v2.4 added, which v2.3 and v2.2 readers don't know.
+const V24_ONLY: [&str; 18] = [
+    "ASPI", "EQU2", "RVA2", "SEEK", "SIGN", "TDEN", "TDOR", "TDRC", "TDRL", "TDTG", "TIPL", "TMCL", "TMOO", "TPRO",
+    "TSOA", "TSOP", "TSOT", "TSST",
+];
+/// Frames ID3v2.4 dropped.
+const V23_ONLY: [&str; 9] = ["EQUA", "IPLS", "RVAD", "TDAT", "TIME", "TORY", "TRDA", "TSIZ", "TYER"];
+
+pub const ALL: [Version; 3] = [Version::Id3v22, Version::Id3v23, Version::Id3v24];
+
+fn text(tag: &Tag, id: &str) -> Option<String> {
+    tag.get(id).and_then(|f| f.content().text()).map(|t| t.trim().to_string())
+}
+
+/// `DDMM` and `HHMM` pairs of ID3v2.3's TDAT and TIME.
+fn pair(value: Option<String>) -> Option<(u8, u8)> {
+    let value = value?;
+    if value.len() != 4 {
+        return None;
+    }
+    Some((value[..2].parse().ok()?, value[2..].parse().ok()?))
+}
+
+/// Rewrites `tag` in place so it can be written as `version`: dates move
+/// between TDRC/TDOR and TYER/TDAT/TIME/TORY, and frames the version has no
+/// place for are dropped. Returns a line for each change.
+pub fn convert(tag: &mut Tag, version: Version) -> Vec<String> {
+    let mut report = Vec::new();
+    if version == Version::Id3v24 {
+        if let Some(year) = text(tag, "TYER").and_then(|y| y.parse().ok())
+            && tag.get("TDRC").is_none()
+        {
+            let date = pair(text(tag, "TDAT"));
+            let time = pair(text(tag, "TIME"));
+            tag.set_date_recorded(Timestamp {
+                year,
+                month: date.map(|(_, m)| m),
+                day: date.map(|(d, _)| d),
+                hour: time.map(|(h, _)| h),
+                minute: time.map(|(_, m)| m),
+                second: None,
+            });
+            report.push(t!("versions-merged-date"));
+        }
+        if let Some(year) = text(tag, "TORY").and_then(|y| y.parse().ok())
+            && tag.get("TDOR").is_none()
+        {
+            tag.set_original_date_released(Timestamp { year, month: None, day: None, hour: None, minute: None, second: None });
+            report.push(t!("versions-moved", from = "TORY", to = "TDOR"));
+        }
+        for id in ["TYER", "TDAT", "TIME", "TORY"] {
+            tag.remove(id);
+        }
+    } else {
+        if let Some(date) = tag.date_recorded() {
+            tag.remove("TDRC");
+            tag.set_text("TYER", format!("{:04}", date.year));
+            if let (Some(month), Some(day)) = (date.month, date.day) {
+                tag.set_text("TDAT", format!("{:02}{:02}", day, month));
+            }
+            if let (Some(hour), Some(minute)) = (date.hour, date.minute) {
+                tag.set_text("TIME", format!("{:02}{:02}", hour, minute));
+            }
+            report.push(t!("versions-split-date"));
+        }
+        if let Some(date) = tag.original_date_released() {
+            tag.remove("TDOR");
+            tag.set_text("TORY", format!("{:04}", date.year));
+            report.push(t!("versions-moved", from = "TDOR", to = "TORY"));
+        }
+    }
+
+    let unsupported = |frame: &Frame| match version {
+        Version::Id3v24 => V23_ONLY.contains(&frame.id()),
+        Version::Id3v23 => V24_ONLY.contains(&frame.id()),
+        Version::Id3v22 => V24_ONLY.contains(&frame.id()) || frame.id_for_version(Version::Id3v22).is_none(),
+    };
+    let dropped: Vec<String> = tag.frames().filter(|f| unsupported(f)).map(|f| f.id().to_string()).collect();
+    for id in &dropped {
+        tag.remove(id);
+        report.push(t!("versions-dropped", frame = id.as_str(), version = version.to_string()));
+    }
+    report
+}
+
+/// Which version tags are written in.
+#[derive(Default)]
+pub struct VersionSettings {
+    /// Write `preferred` even over a file's existing version.
+    pub always_preferred: bool,
+    /// For files without a tag, and for every file when `always_preferred` is set. Defaults to v2.4.
+    pub preferred: Option<Version>,
+}
+
+impl VersionSettings {
+    pub fn preferred(&self) -> Version {
+        self.preferred.unwrap_or(Version::Id3v24)
+    }
+
+    /// The version to write `path` in.
+    pub fn for_file(&self, path: &str) -> Version {
+        match audio::id3v2_version(path) {
+            Some(existing) if !self.always_preferred => existing,
+            _ => self.preferred(),
+        }
+    }
+}
+
+struct Conversion {
+    path: String,
+    current: Option<Version>,
+    report: Vec<String>,
+}
+
+#[derive(Default)]
+pub struct VersionConverter {
+    open: bool,
+    target: Option<Version>,
+    files: Vec<Conversion>,
+}
+
+impl MyApp {
+    pub(crate) fn open_version_converter(&mut self) {
+        let converter = &mut self.version_converter;
+        converter.target.get_or_insert(Version::Id3v23);
+        converter.open = true;
+        self.preview_conversion();
+    }
+
+    /// What converting each loaded file to the target version would change.
+    fn preview_conversion(&mut self) {
+        let target = self.version_converter.target.unwrap_or(Version::Id3v23);
+        self.version_converter.files = self
+            .dropped_files
+            .iter()
+            .map(|path| {
+                let tag = id3::no_tag_ok(Tag::read_from_path(path)).ok().flatten();
+                let report = match tag {
+                    Some(mut tag) => convert(&mut tag, target),
+                    None => vec![t!("versions-no-tag")],
+                };
+                Conversion { path: path.clone(), current: audio::id3v2_version(path), report }
+            })
+            .collect();
+    }
+
+    fn convert_versions(&mut self) -> FileErrors {
+        let target = self.version_converter.target.unwrap_or(Version::Id3v23);
+        let mut errors = Vec::new();
+        for path in self.dropped_files.clone() {
+            let result = (|| -> errors::Result<()> {
+                if let Some(tag) = id3::no_tag_ok(Tag::read_from_path(&path))? {
+                    self.write_tag_as(&path, tag, target)?;
+                }
+                Ok(())
+            })();
+            if let Err(e) = result {
+                errors.push((path.clone(), e.to_string()));
+            }
+        }
+        errors
+    }
+
+    pub(crate) fn version_converter_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.version_converter.open;
+        let mut retarget = false;
+        let mut convert_clicked = false;
+        egui::Window::new(t!("versions-title")).open(&mut open).show(ctx, |ui| {
+            ui.heading(t!("versions-when-saving"));
+            let settings = &mut self.version_settings;
+            ui.radio_value(&mut settings.always_preferred, false, t!("versions-keep"));
+            ui.radio_value(&mut settings.always_preferred, true, t!("versions-always-preferred"));
+            ui.horizontal(|ui| {
+                ui.label(t!("versions-preferred"));
+                for version in ALL {
+                    ui.radio_value(&mut settings.preferred, Some(version), version.to_string());
+                }
+            });
+            if settings.preferred.is_none() {
+                settings.preferred = Some(Version::Id3v24);
+            }
+
+            ui.separator();
+            ui.heading(t!("versions-convert-heading"));
+            let converter = &mut self.version_converter;
+            ui.horizontal(|ui| {
+                ui.label(t!("versions-convert-to"));
+                for version in ALL {
+                    retarget |= ui.radio_value(&mut converter.target, Some(version), version.to_string()).changed();
+                }
+            });
+            egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                for file in &converter.files {
+                    let current = file.current.map(|v| v.to_string()).unwrap_or_else(|| t!("versions-no-v2-tag"));
+                    ui.label(format!("{} ({})", MyApp::get_file_name(&file.path), current));
+                    for line in &file.report {
+                        ui.label(format!("    {}", line));
+                    }
+                }
+            });
+            convert_clicked = ui.button(t!("versions-convert")).clicked();
+        });
+        self.version_converter.open &= open;
+
+        if retarget {
+            self.preview_conversion();
+        }
+        if convert_clicked {
+            let errors = self.convert_versions();
+            self.report_file_errors("versions-failed", errors);
+            self.preview_conversion();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `tag` written as `version` and read back.
+    fn round_trip(tag: &Tag, version: Version) -> Tag {
+        let mut bytes = Vec::new();
+        tag.write_to(&mut bytes, version).unwrap();
+        Tag::read_from2(std::io::Cursor::new(bytes)).unwrap()
+    }
+
+    fn ids(tag: &Tag) -> Vec<&str> {
+        let mut ids: Vec<&str> = tag.frames().map(|f| f.id()).collect();
+        ids.sort();
+        ids
+    }
+
+    #[test]
+    fn dates_survive_v23_to_v24_and_back() {
+        let mut tag = Tag::with_version(Version::Id3v23);
+        tag.set_text("TYER", "1999");
+        tag.set_text("TDAT", "3112");
+        tag.set_text("TIME", "2359");
+        tag.set_text("TORY", "1998");
+
+        convert(&mut tag, Version::Id3v24);
+        let mut tag = round_trip(&tag, Version::Id3v24);
+        let recorded = tag.date_recorded().unwrap();
+        assert_eq!(
+            (recorded.year, recorded.month, recorded.day, recorded.hour, recorded.minute),
+            (1999, Some(12), Some(31), Some(23), Some(59))
+        );
+        assert_eq!(tag.original_date_released().map(|d| d.year), Some(1998));
+        assert_eq!(ids(&tag), ["TDOR", "TDRC"]);
+
+        convert(&mut tag, Version::Id3v23);
+        let tag = round_trip(&tag, Version::Id3v23);
+        assert_eq!(ids(&tag), ["TDAT", "TIME", "TORY", "TYER"]);
+        for (id, value) in [("TYER", "1999"), ("TDAT", "3112"), ("TIME", "2359"), ("TORY", "1998")] {
+            assert_eq!(text(&tag, id).as_deref(), Some(value), "{id}");
+        }
+    }
+
+    #[test]
+    fn year_alone_stays_a_year() {
+        let mut tag = Tag::with_version(Version::Id3v23);
+        tag.set_text("TYER", "2004");
+        convert(&mut tag, Version::Id3v24);
+        convert(&mut tag, Version::Id3v23);
+        assert_eq!(ids(&tag), ["TYER"]);
+        assert_eq!(text(&tag, "TYER").as_deref(), Some("2004"));
+    }
+
+    #[test]
+    fn frames_without_a_place_are_dropped() {
+        let mut tag = Tag::new();
+        tag.set_text("TIT2", "Title");
+        tag.set_text("TMOO", "Calm");
+        tag.set_text("TSIZ", "1234");
+
+        let mut v23 = tag.clone();
+        let report = convert(&mut v23, Version::Id3v23);
+        assert_eq!(ids(&v23), ["TIT2", "TSIZ"]);
+        assert_eq!(report.len(), 1);
+
+        let mut v24 = tag.clone();
+        let report = convert(&mut v24, Version::Id3v24);
+        assert_eq!(ids(&v24), ["TIT2", "TMOO"]);
+        assert_eq!(report.len(), 1);
+
+        let mut v22 = tag;
+        v22.set_text("TSOP", "Sort");
+        convert(&mut v22, Version::Id3v22);
+        assert_eq!(ids(&v22), ["TIT2", "TSIZ"]);
+    }
+}
@@ -0,0 +1,232 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{Receiver, channel};
+use std::time::SystemTime;
+
+use eframe::egui;
+use notify::event::{ModifyKind, RenameMode};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+
+use crate::i18n::t;
+use crate::MyApp;
+
+/// Size and modification time, enough to tell our own writes from someone else's.
+type Stamp = (u64, Option<SystemTime>);
+
+fn stamp(path: &str) -> Option<Stamp> {
+    let metadata = std::fs::metadata(path).ok()?;
+    Some((metadata.len(), metadata.modified().ok()))
+}
+
+/// Watches the folders of the loaded files for changes made by other programs.
+#[derive(Default)]
+pub struct FileWatcher {
+    watcher: Option<RecommendedWatcher>,
+    events: Option<Receiver<notify::Result<Event>>>,
+    dirs: HashSet<PathBuf>,
+    /// Each file as we last read or wrote it.
+    known: HashMap<String, Stamp>,
+    /// The selected file, changed on disk while it had unsaved edits.
+    conflict: Option<String>,
+}
+
+impl FileWatcher {
+    /// Records `path` as it is now, so the change we just made isn't reported back.
+    pub fn remember(&mut self, path: &str) {
+        if let Some(stamp) = stamp(path) {
+            self.known.insert(path.to_string(), stamp);
+        }
+    }
+}
+
+impl MyApp {
+    /// Whether the selected file has edits that a reload would throw away.
+    fn has_unsaved_edits(&self) -> bool {
+        self.form.is_dirty()
+            || self.value_editor.is_open()
+            || self.lyrics.is_dirty()
+            || self.chapters.is_dirty()
+    }
+
+    /// Rereads the selected file's tag and art.
+    pub(crate) fn reload_selected(&mut self, ctx: &egui::Context) {
+        let Some(path) = self.selected_file.clone() else {
+            return;
+        };
+        self.cached_tag = None;
+        self.audio_info = None;
+        self.album_art_cache.remove(&path);
+        self.selected_album_art = None;
+        self.lyrics.reset();
+        self.chapters.reset();
+        self.show_album_art(ctx, &path);
+    }
+
+    /// Keeps the watched folders in step with the file list and handles what changed since the last frame.
+    pub(crate) fn watch_files(&mut self, ctx: &egui::Context) {
+        let watcher = &mut self.watcher;
+        if watcher.watcher.is_none() {
+            let (tx, rx) = channel();
+            let repaint = ctx.clone();
+            match notify::recommended_watcher(move |event| {
+                let _ = tx.send(event);
+                repaint.request_repaint();
+            }) {
+                Ok(w) => {
+                    watcher.watcher = Some(w);
+                    watcher.events = Some(rx);
+                }
+                Err(e) => {
+                    self.report_error(None, t!("watch-failed", error = e.to_string()));
+                    return;
+                }
+            }
+        }
+
+        let files: HashSet<&String> = self.dropped_files.iter().collect();
+        watcher.known.retain(|path, _| files.contains(path));
+        for path in &self.dropped_files {
+            if !watcher.known.contains_key(path)
+                && let Some(stamp) = stamp(path)
+            {
+                watcher.known.insert(path.clone(), stamp);
+            }
+        }
+        let dirs: HashSet<PathBuf> = self
+            .dropped_files
+            .iter()
+            .filter_map(|p| Path::new(p).parent().map(Path::to_path_buf))
+            .collect();
+        let mut failures = Vec::new();
+        if let Some(w) = &mut watcher.watcher {
+            for dir in watcher.dirs.difference(&dirs) {
+                // a deleted folder stops being watched on its own
+                if let Err(e) = w.unwatch(dir)
+                    && dir.exists()
+                {
+                    failures.push(t!("watch-unwatch-failed", folder = dir.display().to_string(), error = e.to_string()));
+                }
+            }
+            for dir in dirs.difference(&watcher.dirs) {
+                if let Err(e) = w.watch(dir, RecursiveMode::NonRecursive) {
+                    failures.push(t!("watch-folder-failed", folder = dir.display().to_string(), error = e.to_string()));
+                }
+            }
+        }
+        watcher.dirs = dirs;
+        for failure in failures {
+            self.report_warning(None, failure);
+        }
+
+        let events: Vec<Event> = match &self.watcher.events {
+            Some(rx) => rx.try_iter().filter_map(Result::ok).collect(),
+            None => Vec::new(),
+        };
+        if events.is_empty() {
+            return;
+        }
+
+        // renames first, so the separate "from" event doesn't look like a deletion
+        for event in &events {
+            if let EventKind::Modify(ModifyKind::Name(RenameMode::Both)) = event.kind
+                && let [from, to] = event.paths.as_slice()
+            {
+                self.file_renamed(&from.display().to_string(), &to.display().to_string());
+            }
+        }
+        let mut touched: Vec<String> = events
+            .iter()
+            .flat_map(|e| e.paths.iter().map(|p| p.display().to_string()))
+            .filter(|p| self.dropped_files.contains(p))
+            .collect();
+        touched.sort();
+        touched.dedup();
+        for path in touched {
+            self.file_changed(ctx, &path);
+        }
+    }
+
+    fn file_renamed(&mut self, from: &str, to: &str) {
+        let Some(index) = self.dropped_files.iter().position(|f| f == from) else {
+            return;
+        };
+        // keep the list where the user had it; the new name may already be listed
+        if self.dropped_files.iter().any(|f| f == to) {
+            self.dropped_files.remove(index);
+        } else {
+            self.dropped_files[index] = to.to_string();
+        }
+        if let Some(stamp) = self.watcher.known.remove(from) {
+            self.watcher.known.insert(to.to_string(), stamp);
+        }
+        if let Some(texture) = self.album_art_cache.remove(from) {
+            self.album_art_cache.insert(to.to_string(), texture);
+        }
+        if self.selected_file.as_deref() == Some(from) {
+            self.selected_file = Some(to.to_string());
+            self.lyrics.reset();
+            self.chapters.reset();
+        }
+        if self.watcher.conflict.as_deref() == Some(from) {
+            self.watcher.conflict = Some(to.to_string());
+        }
+    }
+
+    fn file_changed(&mut self, ctx: &egui::Context, path: &str) {
+        let Some(current) = stamp(path) else {
+            self.dropped_files.retain(|f| f != path);
+            self.watcher.known.remove(path);
+            self.album_art_cache.remove(path);
+            if self.selected_file.as_deref() == Some(path) {
+                self.selected_file = None;
+                self.selected_album_art = None;
+                self.cached_tag = None;
+                self.player = None;
+                self.form.reset();
+                self.watcher.conflict = None;
+            }
+            let path = path.to_string();
+            self.report_warning(Some(&path), t!("watch-deleted", file = MyApp::get_file_name(&path)));
+            return;
+        };
+        if self.watcher.known.get(path) == Some(&current) {
+            return;
+        }
+        if self.selected_file.as_deref() != Some(path) {
+            self.watcher.known.insert(path.to_string(), current);
+            self.album_art_cache.remove(path);
+        } else if self.has_unsaved_edits() {
+            // keep the old stamp until the user decides
+            self.watcher.conflict = Some(path.to_string());
+        } else {
+            self.watcher.known.insert(path.to_string(), current);
+            self.reload_selected(ctx);
+        }
+    }
+
+    pub(crate) fn file_conflict_window(&mut self, ctx: &egui::Context) {
+        let Some(path) = self.watcher.conflict.clone() else {
+            return;
+        };
+        let mut reload = false;
+        let mut keep = false;
+        egui::Window::new(t!("conflict-title")).collapsible(false).show(ctx, |ui| {
+            ui.label(t!("conflict-changed", file = MyApp::get_file_name(&path)));
+            ui.label(t!("conflict-keep-warning"));
+            ui.horizontal(|ui| {
+                reload = ui.button(t!("conflict-reload")).clicked();
+                keep = ui.button(t!("conflict-keep")).clicked();
+            });
+        });
+
+        if reload {
+            self.form.reset();
+            self.value_editor = Default::default();
+            self.reload_selected(ctx);
+        }
+        if reload || keep {
+            self.watcher.remember(&path);
+            self.watcher.conflict = None;
+        }
+    }
+}